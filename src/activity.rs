@@ -0,0 +1,53 @@
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserActivity {
+    Active,
+    // Idle for the timeout
+    Away,
+    // Still at the computer, but ImHex has been in the background for the timeout
+    Paused,
+}
+
+// Decides when the user has stepped away from ImHex
+pub struct ActivityDetector {
+    away_timeout: Option<Duration>,
+    last_focused: Option<Instant>,
+}
+
+impl ActivityDetector {
+    // A timeout of None never reports away
+    pub fn new(away_timeout: Option<Duration>) -> Self {
+        Self { away_timeout, last_focused: None }
+    }
+
+    // Updates the detector with whether ImHex has focus (None if unknown) && the system idle time
+    pub fn update(&mut self, imhex_focused: Option<bool>, idle: Option<Duration>, now: Instant) -> UserActivity {
+        let timeout = match self.away_timeout {
+            Some(timeout) => timeout,
+            None => return UserActivity::Active,
+        };
+
+        // Count background time from the first observation when ImHex never had focus
+        if imhex_focused == Some(true) || self.last_focused.is_none() {
+            self.last_focused = Some(now);
+        }
+
+        let idle_too_long = idle.is_some_and(|idle| idle >= timeout);
+        let background_too_long = imhex_focused == Some(false)
+            && self.last_focused.is_some_and(|last_focused| now.saturating_duration_since(last_focused) >= timeout);
+
+        if idle_too_long {
+            UserActivity::Away
+        } else if background_too_long {
+            UserActivity::Paused
+        } else {
+            UserActivity::Active
+        }
+    }
+
+    // Forgets the focus history, e.g. when ImHex exits
+    pub fn reset(&mut self) {
+        self.last_focused = None;
+    }
+}
@@ -0,0 +1,404 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use thiserror::Error;
+
+// Upper bound for section or load command tables, real binaries stay far below it
+const MAX_TABLE_LEN: u64 = 16 * 1024 * 1024;
+
+const SHT_SYMTAB: u32 = 2;
+const IMAGE_DIRECTORY_ENTRY_DEBUG: u64 = 6;
+const LC_SEGMENT: u32 = 0x1;
+const LC_SYMTAB: u32 = 0x2;
+const LC_SEGMENT_64: u32 = 0x19;
+const LC_MAIN: u32 = 0x8000_0028;
+
+#[derive(Debug, Error)]
+pub enum BinaryError {
+    #[error("Failed to read binary: {0}")]
+    Io(io::Error),
+    #[error("Binary is truncated")]
+    Truncated,
+    #[error("Not an ELF, PE or Mach-O binary")]
+    UnknownFormat,
+    #[error("Malformed binary: {0}")]
+    Malformed(String),
+}
+
+impl From<io::Error> for BinaryError {
+    fn from(e: io::Error) -> Self {
+        if e.kind() == io::ErrorKind::UnexpectedEof {
+            BinaryError::Truncated
+        } else {
+            BinaryError::Io(e)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryFormat {
+    Elf,
+    Pe,
+    MachO,
+}
+
+impl fmt::Display for BinaryFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BinaryFormat::Elf => write!(f, "ELF"),
+            BinaryFormat::Pe => write!(f, "PE"),
+            BinaryFormat::MachO => write!(f, "Mach-O"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endianness {
+    Little,
+    Big,
+}
+
+impl fmt::Display for Endianness {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Endianness::Little => write!(f, "little-endian"),
+            Endianness::Big => write!(f, "big-endian"),
+        }
+    }
+}
+
+// What the headers of an executable say about it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BinarySummary {
+    pub format: BinaryFormat,
+    pub architecture: String,
+    pub bits: u8,
+    pub endianness: Endianness,
+    // Virtual address for ELF && PE, offset into __TEXT for Mach-O LC_MAIN
+    pub entry_point: Option<u64>,
+    pub stripped: bool,
+    pub section_count: usize,
+}
+
+impl fmt::Display for BinarySummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}, {}-bit {}", self.architecture, self.bits, self.endianness)?;
+        if let Some(entry_point) = self.entry_point {
+            write!(f, ", entry 0x{:X}", entry_point)?;
+        }
+        write!(
+            f,
+            ", {}, {} section{}",
+            if self.stripped { "stripped" } else { "not stripped" },
+            self.section_count,
+            if self.section_count == 1 { "" } else { "s" }
+        )
+    }
+}
+
+// Gets the ELF e_machine name
+pub fn elf_machine_name(machine: u16) -> Option<&'static str> {
+    Some(match machine {
+        0x02 | 0x2B => "SPARC",
+        0x03 => "x86",
+        0x08 => "MIPS",
+        0x14 => "PowerPC",
+        0x15 => "PowerPC64",
+        0x28 => "ARM",
+        0x3E => "x86-64",
+        0x53 => "AVR",
+        0x5E => "Xtensa",
+        0xB7 => "AArch64",
+        0xF3 => "RISC-V",
+        _ => return None,
+    })
+}
+
+// Gets the PE/COFF machine name
+pub fn pe_machine_name(machine: u16) -> Option<&'static str> {
+    Some(match machine {
+        0x14C => "Intel 80386",
+        0x8664 => "x86-64",
+        0x1C0 | 0x1C4 => "ARM",
+        0xAA64 => "AArch64",
+        _ => return None,
+    })
+}
+
+// Gets the Mach-O cputype name
+pub fn mach_o_cpu_name(cpu: u32) -> Option<&'static str> {
+    Some(match cpu {
+        7 => "x86",
+        0x0100_0007 => "x86-64",
+        12 => "ARM",
+        0x0100_000C => "ARM64",
+        18 => "PowerPC",
+        0x0100_0012 => "PowerPC64",
+        _ => return None,
+    })
+}
+
+// Reads fixed-width integers at absolute offsets of a seekable source
+struct BinaryReader<'a, R> {
+    reader: &'a mut R,
+    endianness: Endianness,
+}
+
+impl<R: Read + Seek> BinaryReader<'_, R> {
+    fn bytes(&mut self, offset: u64, len: u64) -> Result<Vec<u8>, BinaryError> {
+        if len > MAX_TABLE_LEN {
+            return Err(BinaryError::Malformed(format!("table of {} bytes", len)));
+        }
+        let mut buffer = vec![0; len as usize];
+        self.reader.seek(SeekFrom::Start(offset))?;
+        self.reader.read_exact(&mut buffer)?;
+        Ok(buffer)
+    }
+
+    fn u16(&mut self, offset: u64) -> Result<u16, BinaryError> {
+        let bytes: [u8; 2] = self.bytes(offset, 2)?.try_into().unwrap();
+        Ok(match self.endianness {
+            Endianness::Little => u16::from_le_bytes(bytes),
+            Endianness::Big => u16::from_be_bytes(bytes),
+        })
+    }
+
+    fn u32(&mut self, offset: u64) -> Result<u32, BinaryError> {
+        let bytes: [u8; 4] = self.bytes(offset, 4)?.try_into().unwrap();
+        Ok(match self.endianness {
+            Endianness::Little => u32::from_le_bytes(bytes),
+            Endianness::Big => u32::from_be_bytes(bytes),
+        })
+    }
+
+    fn u64(&mut self, offset: u64) -> Result<u64, BinaryError> {
+        let bytes: [u8; 8] = self.bytes(offset, 8)?.try_into().unwrap();
+        Ok(match self.endianness {
+            Endianness::Little => u64::from_le_bytes(bytes),
+            Endianness::Big => u64::from_be_bytes(bytes),
+        })
+    }
+
+    // Reads a 32 or 64-bit word depending on the binary's class
+    fn word(&mut self, offset: u64, is_64: bool) -> Result<u64, BinaryError> {
+        if is_64 {
+            self.u64(offset)
+        } else {
+            self.u32(offset).map(u64::from)
+        }
+    }
+}
+
+// Summarizes the headers of an executable file
+pub fn inspect_file(path: &Path) -> Result<BinarySummary, BinaryError> {
+    let file = File::open(path).map_err(BinaryError::Io)?;
+    inspect(&mut BufReader::new(file))
+}
+
+// Summarizes the headers of an ELF, PE or Mach-O binary
+pub fn inspect<R: Read + Seek>(reader: &mut R) -> Result<BinarySummary, BinaryError> {
+    let mut magic = [0u8; 4];
+    reader.seek(SeekFrom::Start(0))?;
+    reader.read_exact(&mut magic)?;
+
+    match magic {
+        [0x7F, b'E', b'L', b'F'] => inspect_elf(reader),
+        [b'M', b'Z', _, _] => inspect_pe(reader),
+        [0xFE, 0xED, 0xFA, 0xCE] | [0xFE, 0xED, 0xFA, 0xCF] => inspect_mach_o(reader, 0, Endianness::Big),
+        [0xCE, 0xFA, 0xED, 0xFE] | [0xCF, 0xFA, 0xED, 0xFE] => inspect_mach_o(reader, 0, Endianness::Little),
+        [0xCA, 0xFE, 0xBA, 0xBE] => inspect_fat_mach_o(reader),
+        _ => Err(BinaryError::UnknownFormat),
+    }
+}
+
+fn inspect_elf<R: Read + Seek>(reader: &mut R) -> Result<BinarySummary, BinaryError> {
+    let mut ident = [0u8; 2];
+    reader.seek(SeekFrom::Start(4))?;
+    reader.read_exact(&mut ident)?;
+    let is_64 = match ident[0] {
+        1 => false,
+        2 => true,
+        class => return Err(BinaryError::Malformed(format!("unknown ELF class {}", class))),
+    };
+    let endianness = match ident[1] {
+        1 => Endianness::Little,
+        2 => Endianness::Big,
+        data => return Err(BinaryError::Malformed(format!("unknown ELF data encoding {}", data))),
+    };
+    let mut r = BinaryReader { reader, endianness };
+
+    let machine = r.u16(18)?;
+    let entry_point = r.word(24, is_64)?;
+    let (section_offset, entry_size, count) = if is_64 {
+        (r.u64(0x28)?, r.u16(0x3A)?, r.u16(0x3C)?)
+    } else {
+        (u64::from(r.u32(0x20)?), r.u16(0x2E)?, r.u16(0x30)?)
+    };
+
+    let mut section_count = usize::from(count);
+    let mut stripped = true;
+    if section_offset != 0 {
+        // Past 0xFF00 sections the real count lives in the size field of section 0
+        if section_count == 0 {
+            let size_field = section_offset
+                .checked_add(if is_64 { 32 } else { 20 })
+                .ok_or_else(|| BinaryError::Malformed(format!("section table at 0x{:X}", section_offset)))?;
+            let extended = r.word(size_field, is_64)?;
+            if extended > MAX_TABLE_LEN {
+                return Err(BinaryError::Malformed(format!("{} sections", extended)));
+            }
+            section_count = extended as usize;
+        }
+        let entry_size = u64::from(entry_size);
+        if entry_size < 8 {
+            return Err(BinaryError::Malformed(format!("section header size {}", entry_size)));
+        }
+        let table_len = entry_size
+            .checked_mul(section_count as u64)
+            .ok_or_else(|| BinaryError::Malformed(format!("{} sections of {} bytes", section_count, entry_size)))?;
+        let table = r.bytes(section_offset, table_len)?;
+        stripped = !table.chunks_exact(entry_size as usize).any(|section| {
+            let kind: [u8; 4] = section[4..8].try_into().unwrap();
+            let kind = match endianness {
+                Endianness::Little => u32::from_le_bytes(kind),
+                Endianness::Big => u32::from_be_bytes(kind),
+            };
+            kind == SHT_SYMTAB
+        });
+    }
+
+    Ok(BinarySummary {
+        format: BinaryFormat::Elf,
+        architecture: elf_machine_name(machine).map_or_else(|| format!("machine 0x{:X}", machine), str::to_string),
+        bits: if is_64 { 64 } else { 32 },
+        endianness,
+        entry_point: (entry_point != 0).then_some(entry_point),
+        stripped,
+        section_count,
+    })
+}
+
+fn inspect_pe<R: Read + Seek>(reader: &mut R) -> Result<BinarySummary, BinaryError> {
+    let mut r = BinaryReader { reader, endianness: Endianness::Little };
+
+    let pe_offset = u64::from(r.u32(0x3C)?);
+    if r.bytes(pe_offset, 4)? != b"PE\0\0" {
+        return Err(BinaryError::Malformed("missing PE signature".to_string()));
+    }
+    let coff = pe_offset + 4;
+    let machine = r.u16(coff)?;
+    let section_count = usize::from(r.u16(coff + 2)?);
+    let symbol_table = r.u32(coff + 8)?;
+    let symbol_count = r.u32(coff + 12)?;
+
+    let optional = coff + 20;
+    let is_64 = match r.u16(optional)? {
+        0x10B => false,
+        0x20B => true,
+        magic => return Err(BinaryError::Malformed(format!("unknown optional header magic 0x{:X}", magic))),
+    };
+    let entry_rva = u64::from(r.u32(optional + 16)?);
+    let image_base = if is_64 { r.u64(optional + 24)? } else { u64::from(r.u32(optional + 28)?) };
+    let (directory_count, directories) =
+        if is_64 { (r.u32(optional + 108)?, optional + 112) } else { (r.u32(optional + 92)?, optional + 96) };
+    let debug_size = if u64::from(directory_count) > IMAGE_DIRECTORY_ENTRY_DEBUG {
+        r.u32(directories + IMAGE_DIRECTORY_ENTRY_DEBUG * 8 + 4)?
+    } else {
+        0
+    };
+
+    Ok(BinarySummary {
+        format: BinaryFormat::Pe,
+        architecture: pe_machine_name(machine).map_or_else(|| format!("machine 0x{:X}", machine), str::to_string),
+        bits: if is_64 { 64 } else { 32 },
+        endianness: Endianness::Little,
+        entry_point: (entry_rva != 0).then(|| image_base.wrapping_add(entry_rva)),
+        // Symbols either stay in the COFF table or are referenced through the debug directory
+        stripped: symbol_table == 0 && symbol_count == 0 && debug_size == 0,
+        section_count,
+    })
+}
+
+// Summarizes the first architecture of a universal binary
+fn inspect_fat_mach_o<R: Read + Seek>(reader: &mut R) -> Result<BinarySummary, BinaryError> {
+    let mut r = BinaryReader { reader, endianness: Endianness::Big };
+    let arch_count = r.u32(4)?;
+    // Java class files share the magic but keep their version number here
+    if arch_count == 0 || arch_count >= 32 {
+        return Err(BinaryError::UnknownFormat);
+    }
+
+    let slice_offset = u64::from(r.u32(8 + 8)?);
+    let magic = r.bytes(slice_offset, 4)?;
+    let endianness = match magic.as_slice() {
+        [0xFE, 0xED, 0xFA, 0xCE] | [0xFE, 0xED, 0xFA, 0xCF] => Endianness::Big,
+        [0xCE, 0xFA, 0xED, 0xFE] | [0xCF, 0xFA, 0xED, 0xFE] => Endianness::Little,
+        _ => return Err(BinaryError::Malformed("universal binary slice is not Mach-O".to_string())),
+    };
+    inspect_mach_o(reader, slice_offset, endianness)
+}
+
+fn inspect_mach_o<R: Read + Seek>(reader: &mut R, base: u64, endianness: Endianness) -> Result<BinarySummary, BinaryError> {
+    let mut r = BinaryReader { reader, endianness };
+
+    let is_64 = r.u32(base)? & 1 == 1;
+    let cpu = r.u32(base + 4)?;
+    let command_count = r.u32(base + 16)?;
+    let commands_len = u64::from(r.u32(base + 20)?);
+    let commands = r.bytes(base + if is_64 { 32 } else { 28 }, commands_len)?;
+
+    let read_u32 = |offset: usize| -> Option<u32> {
+        let bytes: [u8; 4] = commands.get(offset..offset + 4)?.try_into().ok()?;
+        Some(match endianness {
+            Endianness::Little => u32::from_le_bytes(bytes),
+            Endianness::Big => u32::from_be_bytes(bytes),
+        })
+    };
+    let read_u64 = |offset: usize| -> Option<u64> {
+        let first = u64::from(read_u32(offset)?);
+        let second = u64::from(read_u32(offset + 4)?);
+        Some(match endianness {
+            Endianness::Little => second << 32 | first,
+            Endianness::Big => first << 32 | second,
+        })
+    };
+    let truncated = || BinaryError::Malformed("load command runs past the header".to_string());
+    let overflow = |what: &str| BinaryError::Malformed(format!("{} overflows", what));
+
+    let mut section_count = 0usize;
+    let mut symbol_count = 0u32;
+    let mut entry_point = None;
+    let mut offset = 0usize;
+    for _ in 0..command_count {
+        let command = read_u32(offset).ok_or_else(truncated)?;
+        let size = read_u32(offset + 4).ok_or_else(truncated)? as usize;
+        if size < 8 {
+            return Err(BinaryError::Malformed(format!("load command of {} bytes", size)));
+        }
+        match command {
+            LC_SEGMENT | LC_SEGMENT_64 => {
+                let nsects = read_u32(offset + if command == LC_SEGMENT { 48 } else { 64 }).ok_or_else(truncated)?;
+                section_count = section_count.checked_add(nsects as usize).ok_or_else(|| overflow("section count"))?;
+            }
+            LC_SYMTAB => {
+                let symbols = read_u32(offset + 12).ok_or_else(truncated)?;
+                symbol_count = symbol_count.checked_add(symbols).ok_or_else(|| overflow("symbol count"))?;
+            }
+            LC_MAIN => entry_point = Some(read_u64(offset + 8).ok_or_else(truncated)?),
+            _ => {}
+        }
+        offset = offset.checked_add(size).ok_or_else(|| overflow("load command offset"))?;
+    }
+
+    Ok(BinarySummary {
+        format: BinaryFormat::MachO,
+        architecture: mach_o_cpu_name(cpu).map_or_else(|| format!("CPU 0x{:X}", cpu), str::to_string),
+        bits: if is_64 { 64 } else { 32 },
+        endianness,
+        entry_point,
+        stripped: symbol_count == 0,
+        section_count,
+    })
+}
@@ -0,0 +1,128 @@
+use std::path::Path;
+
+use log::info;
+use serde_json::{json, Value};
+
+use crate::connection::Connection;
+use crate::discord::{DiscordError, IpcConnection};
+use crate::error::AppError;
+use crate::presence::{ActivityAssets, ActivityImage, ActivitySink};
+use crate::settings::{MAX_BUTTONS, MAX_BUTTON_LABEL_LEN};
+
+// Discord rejects the state, details && hover texts outside of 2 to 128 characters
+const MIN_TEXT_LEN: usize = 2;
+const MAX_TEXT_LEN: usize = 128;
+
+#[derive(Debug, Eq, PartialEq)]
+struct ActivityState {
+    state: String,
+    details: String,
+    assets: ActivityAssets,
+}
+
+pub struct DiscordClient {
+    client: IpcConnection,
+    last_activity: Option<ActivityState>,
+}
+
+impl DiscordClient {
+    pub fn new(client_id: &str) -> Result<Self, AppError> {
+        Self::connected(IpcConnection::connect(client_id))
+    }
+
+    // Connects to the Discord client listening on this socket only
+    pub fn connect_to(path: &Path, client_id: &str) -> Result<Self, AppError> {
+        Self::connected(IpcConnection::connect_to(path, client_id))
+    }
+
+    fn connected(client: Result<IpcConnection, DiscordError>) -> Result<Self, AppError> {
+        let client = client.map_err(discord_error)?;
+        info!("Connected to Discord as {} through {}", client.ready(), client.path().display());
+
+        Ok(Self {
+            client,
+            last_activity: None,
+        })
+    }
+}
+
+impl ActivitySink for DiscordClient {
+    fn update_activity(&mut self, state: String, details: String, start_time: Option<i64>, assets: ActivityAssets) -> Result<(), AppError> {
+        let new_activity = ActivityState { state, details, assets };
+
+        if Some(&new_activity) != self.last_activity.as_ref() {
+            let activity = create_activity(&new_activity.state, &new_activity.details, start_time, &new_activity.assets);
+            self.client.set_activity(Some(activity)).map_err(discord_error)?;
+            self.last_activity = Some(new_activity);
+        }
+        Ok(())
+    }
+
+    fn clear_activity(&mut self) -> Result<(), AppError> {
+        self.client.clear_activity().map_err(discord_error)?;
+        self.last_activity = None;
+        Ok(())
+    }
+}
+
+impl Connection for DiscordClient {
+    fn ping(&mut self) -> Result<(), AppError> {
+        self.client.ping().map_err(discord_error)
+    }
+}
+
+// RPC errors leave the socket usable, everything else means it is gone
+fn discord_error(e: DiscordError) -> AppError {
+    match e {
+        DiscordError::Rpc { .. } => AppError::DiscordRejected(e.to_string()),
+        e => AppError::Discord(e.to_string()),
+    }
+}
+
+// Builds the SET_ACTIVITY payload, leaving out lines too short for Discord && timestamps, assets && buttons that are not known
+pub fn create_activity(state: &str, details: &str, start_time: Option<i64>, assets: &ActivityAssets) -> Value {
+    let mut activity = json!({});
+    for (key, text) in [("state", state), ("details", details)] {
+        if text.chars().count() >= MIN_TEXT_LEN {
+            activity[key] = json!(truncate(text, MAX_TEXT_LEN));
+        }
+    }
+    if let Some(start_time) = start_time {
+        activity["timestamps"] = json!({ "start": start_time });
+    }
+
+    let mut images = serde_json::Map::new();
+    for (name, image) in [("large", &assets.large_image), ("small", &assets.small_image)] {
+        add_image(&mut images, name, image.as_ref());
+    }
+    if !images.is_empty() {
+        activity["assets"] = Value::Object(images);
+    }
+
+    let buttons: Vec<Value> = assets
+        .buttons
+        .iter()
+        .filter(|button| !button.label.trim().is_empty())
+        .take(MAX_BUTTONS)
+        .map(|button| json!({ "label": truncate(&button.label, MAX_BUTTON_LABEL_LEN), "url": button.url }))
+        .collect();
+    if !buttons.is_empty() {
+        activity["buttons"] = Value::Array(buttons);
+    }
+    activity
+}
+
+fn add_image(images: &mut serde_json::Map<String, Value>, name: &str, image: Option<&ActivityImage>) {
+    let image = match image {
+        Some(image) => image,
+        None => return,
+    };
+    images.insert(format!("{}_image", name), json!(image.key));
+    if image.text.chars().count() >= MIN_TEXT_LEN {
+        images.insert(format!("{}_text", name), json!(truncate(&image.text, MAX_TEXT_LEN)));
+    }
+}
+
+fn truncate(text: &str, max_len: usize) -> String {
+    text.chars().take(max_len).collect()
+}
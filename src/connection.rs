@@ -0,0 +1,478 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use log::{error, info};
+
+use crate::error::AppError;
+use crate::presence::{Activity, ActivityAssets, ActivitySink};
+use crate::scheduler::{Clock, SystemClock};
+
+// Reconnect delays double from the first to the last, each one randomly shortened by up to half
+pub const FIRST_RETRY_DELAY: Duration = Duration::from_secs(1);
+pub const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+// How often to look for a Discord client while none is running
+pub const DETECT_INTERVAL: Duration = Duration::from_secs(2);
+// Quiet time after which the socket is pinged to find out whether Discord is still there
+pub const PING_INTERVAL: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    // No connection, waiting for the next attempt
+    Disconnected,
+    Connecting,
+    Ready,
+    // Connected, but Discord refused the last activity
+    Degraded,
+}
+
+impl fmt::Display for ConnectionState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            ConnectionState::Disconnected => "Disconnected",
+            ConnectionState::Connecting => "Connecting",
+            ConnectionState::Ready => "Ready",
+            ConnectionState::Degraded => "Degraded",
+        };
+        f.pad(name)
+    }
+}
+
+// A sink backed by a socket that can die under it
+pub trait Connection: ActivitySink {
+    // Checks that the other end still answers
+    fn ping(&mut self) -> Result<(), AppError>;
+}
+
+// Opens new connections, e.g. to the Discord client
+pub trait Connector {
+    type Connection: Connection;
+
+    fn connect(&mut self) -> Result<Self::Connection, AppError>;
+}
+
+impl<F, C> Connector for F
+where
+    F: FnMut() -> Result<C, AppError>,
+    C: Connection,
+{
+    type Connection = C;
+
+    fn connect(&mut self) -> Result<C, AppError> {
+        self()
+    }
+}
+
+// Exponential backoff with jitter, so clients restarted together don't retry in lockstep
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    first: Duration,
+    max: Duration,
+    attempts: u32,
+    // xorshift state, never zero
+    seed: u64,
+}
+
+impl Backoff {
+    pub fn new(first: Duration, max: Duration) -> Self {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.subsec_nanos() as u64);
+        Self { first, max, attempts: 0, seed: 0 }.with_seed(nanos ^ ((std::process::id() as u64) << 32))
+    }
+
+    // Makes the jitter repeatable
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed.max(1);
+        self
+    }
+
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    // Gets the delay before the next attempt, somewhere in the upper half of first * 2^attempts
+    pub fn next_delay(&mut self) -> Duration {
+        let ceiling = self.first.saturating_mul(1 << self.attempts.min(16)).min(self.max);
+        self.attempts = self.attempts.saturating_add(1);
+        let half = ceiling / 2;
+        half + half.mul_f64(self.next_random())
+    }
+
+    pub fn reset(&mut self) {
+        self.attempts = 0;
+    }
+
+    // Uniform in [0, 1)
+    fn next_random(&mut self) -> f64 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+        (self.seed >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+// Keeps a connection to Discord alive, reconnecting with backoff && replaying the last activity.
+// Connection errors are logged && never reach the presence loop, so its session survives Discord restarts.
+pub struct ConnectionManager<K: Connector, C = SystemClock> {
+    connector: K,
+    clock: C,
+    state: ConnectionState,
+    connection: Option<K::Connection>,
+    backoff: Backoff,
+    retry_at: Option<Instant>,
+    // Last time the connection was known to work
+    last_contact: Instant,
+    // What the presence loop wants shown, None when cleared
+    activity: Option<Activity>,
+    observer: Option<Box<dyn FnMut(ConnectionState)>>,
+    // Describes the running Discord client, None while there is none to connect to
+    detector: Option<Box<dyn FnMut() -> Option<String>>>,
+    // Waiting for a Discord client to start, without connection attempts
+    parked: bool,
+}
+
+impl<K: Connector> ConnectionManager<K> {
+    pub fn new(connector: K) -> Self {
+        Self::with_clock(connector, SystemClock)
+    }
+}
+
+impl<K: Connector, C: Clock> ConnectionManager<K, C> {
+    pub fn with_clock(connector: K, clock: C) -> Self {
+        let now = clock.now();
+        Self {
+            connector,
+            clock,
+            state: ConnectionState::Disconnected,
+            connection: None,
+            backoff: Backoff::new(FIRST_RETRY_DELAY, MAX_RETRY_DELAY),
+            retry_at: None,
+            last_contact: now,
+            activity: None,
+            observer: None,
+            detector: None,
+            parked: false,
+        }
+    }
+
+    pub fn with_backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    // Gets told about every state change, e.g. to show it in the tray
+    pub fn with_observer<F: FnMut(ConnectionState) + 'static>(mut self, observer: F) -> Self {
+        self.observer = Some(Box::new(observer));
+        self
+    }
+
+    // Only tries to connect while the detector finds a Discord client
+    pub fn with_detector<F: FnMut() -> Option<String> + 'static>(mut self, detector: F) -> Self {
+        self.detector = Some(Box::new(detector));
+        self
+    }
+
+    pub fn state(&self) -> ConnectionState {
+        self.state
+    }
+
+    pub fn connection(&self) -> Option<&K::Connection> {
+        self.connection.as_ref()
+    }
+
+    // Gets when the next connection attempt is due, None while connected
+    pub fn retry_at(&self) -> Option<Instant> {
+        self.retry_at
+    }
+
+    pub fn is_parked(&self) -> bool {
+        self.parked
+    }
+
+    fn set_state(&mut self, state: ConnectionState) {
+        if state == self.state {
+            return;
+        }
+        info!("Discord connection: {} -> {}", self.state, state);
+        self.state = state;
+        if let Some(observer) = self.observer.as_mut() {
+            observer(state);
+        }
+    }
+
+    // Connects if an attempt is due, replaying the last activity on the new connection
+    fn ensure_connected(&mut self) {
+        let now = self.clock.now();
+        if self.connection.is_some() || self.retry_at.is_some_and(|retry_at| now < retry_at) {
+            return;
+        }
+        if !self.detect_discord(now) {
+            return;
+        }
+
+        self.set_state(ConnectionState::Connecting);
+        match self.connector.connect() {
+            Ok(connection) => {
+                self.connection = Some(connection);
+                self.retry_at = None;
+                self.last_contact = now;
+                self.set_state(ConnectionState::Ready);
+                if let Some(activity) = self.activity.clone() {
+                    info!("Replaying the last activity after connecting");
+                    self.send(|connection| activity.send_to(connection));
+                }
+                // Only a connection that took the replay resets the backoff
+                if self.connection.is_some() {
+                    self.backoff.reset();
+                }
+            }
+            Err(e) => self.disconnect(&e),
+        }
+    }
+
+    // Parks until the detector finds a Discord client, then connects right away
+    fn detect_discord(&mut self, now: Instant) -> bool {
+        let detector = match self.detector.as_mut() {
+            Some(detector) => detector,
+            None => return true,
+        };
+        match detector() {
+            Some(found) => {
+                if self.parked {
+                    info!("Found {}, connecting", found);
+                    self.parked = false;
+                }
+                true
+            }
+            None => {
+                if !self.parked {
+                    info!("No Discord client is running, waiting for one to start");
+                    self.parked = true;
+                }
+                self.backoff.reset();
+                self.retry_at = Some(now + DETECT_INTERVAL);
+                false
+            }
+        }
+    }
+
+    // Drops the connection && schedules the next attempt
+    fn disconnect(&mut self, reason: &AppError) {
+        self.connection = None;
+        let delay = self.backoff.next_delay();
+        self.retry_at = Some(self.clock.now() + delay);
+        error!("Discord connection lost or unavailable ({}), retrying in {:.1}s", reason, delay.as_secs_f64());
+        self.set_state(ConnectionState::Disconnected);
+    }
+
+    // Runs a request on the open connection, sorting its error into degraded or dead
+    fn send<F>(&mut self, request: F)
+    where
+        F: FnOnce(&mut K::Connection) -> Result<(), AppError>,
+    {
+        let connection = match self.connection.as_mut() {
+            Some(connection) => connection,
+            None => return,
+        };
+        match request(connection) {
+            Ok(()) => {
+                self.last_contact = self.clock.now();
+                self.set_state(ConnectionState::Ready);
+            }
+            Err(AppError::DiscordRejected(message)) => {
+                self.last_contact = self.clock.now();
+                error!("Discord rejected the activity: {}", message);
+                self.set_state(ConnectionState::Degraded);
+            }
+            Err(e) => self.disconnect(&e),
+        }
+    }
+}
+
+impl<K: Connector, C: Clock> ActivitySink for ConnectionManager<K, C> {
+    // Remembers the activity for replays && sends it if connected, Ok even when Discord is unavailable
+    fn update_activity(&mut self, state: String, details: String, start_time: Option<i64>, assets: ActivityAssets) -> Result<(), AppError> {
+        let activity = Activity { state, details, start_time, assets };
+        self.activity = Some(activity.clone());
+        if self.connection.is_none() {
+            // A new connection replays the activity
+            self.ensure_connected();
+            return Ok(());
+        }
+        self.send(|connection| activity.send_to(connection));
+        Ok(())
+    }
+
+    fn clear_activity(&mut self) -> Result<(), AppError> {
+        self.activity = None;
+        self.send(|connection| connection.clear_activity());
+        Ok(())
+    }
+
+    // Reconnects when an attempt is due && pings quiet connections to catch a dead socket early
+    fn poll(&mut self) -> Result<(), AppError> {
+        if self.connection.is_none() {
+            self.ensure_connected();
+        } else if self.clock.now().saturating_duration_since(self.last_contact) >= PING_INTERVAL {
+            // Leaves the state alone, a pong says nothing about whether Discord took the activity
+            let result = self.connection.as_mut().map_or(Ok(()), |connection| connection.ping());
+            match result {
+                Ok(()) => self.last_contact = self.clock.now(),
+                Err(e) => self.disconnect(&e),
+            }
+        }
+        Ok(())
+    }
+}
+
+// An endpoint && the state of its connection
+pub type EndpointState = (PathBuf, ConnectionState);
+
+type ManagerFactory<K, C> = Box<dyn FnMut(&Path) -> ConnectionManager<K, C>>;
+type StatesObserver = Box<dyn FnMut(&[EndpointState])>;
+
+// One connection per available Discord IPC endpoint, each with its own state && backoff, all showing the same activity
+pub struct BroadcastManager<K: Connector, C = SystemClock> {
+    // Lists the endpoints a Discord client currently listens on
+    endpoints: Box<dyn FnMut() -> Vec<PathBuf>>,
+    // Makes the manager for a newly found endpoint
+    manager_for: ManagerFactory<K, C>,
+    clock: C,
+    managers: Vec<(PathBuf, ConnectionManager<K, C>)>,
+    scan_at: Option<Instant>,
+    activity: Option<Activity>,
+    // States as last told to the observer
+    reported: Vec<EndpointState>,
+    observer: Option<StatesObserver>,
+}
+
+impl<K: Connector> BroadcastManager<K> {
+    pub fn new<E, M>(endpoints: E, manager_for: M) -> Self
+    where
+        E: FnMut() -> Vec<PathBuf> + 'static,
+        M: FnMut(&Path) -> ConnectionManager<K> + 'static,
+    {
+        Self::with_clock(endpoints, manager_for, SystemClock)
+    }
+}
+
+impl<K: Connector, C: Clock> BroadcastManager<K, C> {
+    pub fn with_clock<E, M>(endpoints: E, manager_for: M, clock: C) -> Self
+    where
+        E: FnMut() -> Vec<PathBuf> + 'static,
+        M: FnMut(&Path) -> ConnectionManager<K, C> + 'static,
+    {
+        Self {
+            endpoints: Box::new(endpoints),
+            manager_for: Box::new(manager_for),
+            clock,
+            managers: Vec::new(),
+            scan_at: None,
+            activity: None,
+            reported: Vec::new(),
+            observer: None,
+        }
+    }
+
+    // Gets told the state of every endpoint whenever one of them changes
+    pub fn with_observer<F: FnMut(&[EndpointState]) + 'static>(mut self, observer: F) -> Self {
+        self.observer = Some(Box::new(observer));
+        self
+    }
+
+    pub fn states(&self) -> Vec<EndpointState> {
+        self.managers.iter().map(|(path, manager)| (path.clone(), manager.state())).collect()
+    }
+
+    pub fn manager(&self, path: &Path) -> Option<&ConnectionManager<K, C>> {
+        self.managers.iter().find(|(endpoint, _)| endpoint == path).map(|(_, manager)| manager)
+    }
+
+    // Adds managers for new endpoints && drops disconnected ones whose endpoint is gone
+    fn scan(&mut self) {
+        let now = self.clock.now();
+        if self.scan_at.is_some_and(|scan_at| now < scan_at) {
+            return;
+        }
+        self.scan_at = Some(now + DETECT_INTERVAL);
+
+        let endpoints = (self.endpoints)();
+        self.managers.retain(|(path, manager)| {
+            let keep = endpoints.contains(path) || manager.connection().is_some();
+            if !keep {
+                info!("Discord IPC endpoint {} went away", path.display());
+            }
+            keep
+        });
+        for path in endpoints {
+            if self.managers.iter().any(|(endpoint, _)| *endpoint == path) {
+                continue;
+            }
+            info!("Found Discord IPC endpoint {}", path.display());
+            let mut manager = (self.manager_for)(&path);
+            // A new manager connects on its first activity && keeps retrying from there
+            if let Some(activity) = self.activity.clone() {
+                let _ = activity.send_to(&mut manager);
+            }
+            self.managers.push((path, manager));
+        }
+    }
+
+    fn report(&mut self) {
+        let states = self.states();
+        if states == self.reported {
+            return;
+        }
+        if let Some(observer) = self.observer.as_mut() {
+            observer(&states);
+        }
+        self.reported = states;
+    }
+}
+
+impl<K: Connector, C: Clock> ActivitySink for BroadcastManager<K, C> {
+    // Sends the activity to every endpoint, managers log their own errors && never fail
+    fn update_activity(&mut self, state: String, details: String, start_time: Option<i64>, assets: ActivityAssets) -> Result<(), AppError> {
+        let activity = Activity { state, details, start_time, assets };
+        for (_, manager) in &mut self.managers {
+            activity.send_to(manager)?;
+        }
+        self.activity = Some(activity);
+        // Endpoints found now get the activity as their first one
+        self.scan();
+        self.report();
+        Ok(())
+    }
+
+    fn clear_activity(&mut self) -> Result<(), AppError> {
+        self.activity = None;
+        for (_, manager) in &mut self.managers {
+            manager.clear_activity()?;
+        }
+        self.report();
+        Ok(())
+    }
+
+    fn poll(&mut self) -> Result<(), AppError> {
+        self.scan();
+        for (_, manager) in &mut self.managers {
+            manager.poll()?;
+        }
+        self.report();
+        Ok(())
+    }
+}
+
+// Describes endpoint states for the tray, e.g. "discord-ipc-0 Ready, discord-ipc-1 Disconnected"
+pub fn describe_states(states: &[EndpointState]) -> String {
+    if states.is_empty() {
+        return "no client".to_string();
+    }
+    states
+        .iter()
+        .map(|(path, state)| {
+            let name = path.file_name().map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().into_owned());
+            format!("{} {}", name, state)
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
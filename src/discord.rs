@@ -0,0 +1,404 @@
+use std::env;
+use std::fmt;
+use std::io::{self, Read, Write};
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+#[cfg(windows)]
+use std::os::windows::fs::OpenOptionsExt;
+#[cfg(windows)]
+use std::os::windows::io::AsRawHandle;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+#[cfg(windows)]
+use std::{fs::File, mem, ptr};
+
+use serde_json::{json, Value};
+use thiserror::Error;
+#[cfg(windows)]
+use winapi::shared::minwindef::{BOOL, DWORD, FALSE, LPCVOID, LPVOID, TRUE};
+#[cfg(windows)]
+use winapi::shared::winerror::{ERROR_BROKEN_PIPE, ERROR_IO_PENDING, WAIT_TIMEOUT};
+#[cfg(windows)]
+use winapi::um::fileapi::{ReadFile, WriteFile};
+#[cfg(windows)]
+use winapi::um::handleapi::CloseHandle;
+#[cfg(windows)]
+use winapi::um::ioapiset::{CancelIoEx, GetOverlappedResult};
+#[cfg(windows)]
+use winapi::um::minwinbase::OVERLAPPED;
+#[cfg(windows)]
+use winapi::um::synchapi::{CreateEventW, WaitForSingleObject};
+#[cfg(windows)]
+use winapi::um::winbase::FILE_FLAG_OVERLAPPED;
+#[cfg(windows)]
+use winapi::um::winnt::HANDLE;
+
+// Discord listens on the first free of discord-ipc-0 to discord-ipc-9
+pub const SOCKET_NAME: &str = "discord-ipc-";
+pub const MAX_SOCKET_INDEX: u32 = 9;
+pub const RPC_VERSION: u32 = 1;
+
+// Sandboxed clients put their socket in a subfolder of the runtime dir
+#[cfg(unix)]
+const SANDBOX_DIRS: &[&str] = &[
+    "app/com.discordapp.Discord",
+    "app/com.discordapp.DiscordCanary",
+    "app/dev.vencord.Vesktop",
+    "app/io.github.spacingbat3.webcord",
+    "snap.discord",
+    "snap.discord-canary",
+];
+
+// Frames are a few KiB, anything bigger means we are not talking to Discord
+const MAX_FRAME_LEN: usize = 1024 * 1024;
+const IPC_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Error)]
+pub enum DiscordError {
+    #[error("Discord IPC failed: {0}")]
+    Io(#[from] io::Error),
+    #[error("Invalid Discord payload: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Unexpected Discord reply: {0}")]
+    Protocol(String),
+    #[error("Discord closed the connection ({code}): {message}")]
+    Closed { code: i64, message: String },
+    #[error("Discord rejected the command ({code}): {message}")]
+    Rpc { code: i64, message: String },
+    #[error("No Discord IPC socket found in {0} candidate path(s)")]
+    NotFound(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Opcode {
+    Handshake = 0,
+    Frame = 1,
+    Close = 2,
+    Ping = 3,
+    Pong = 4,
+}
+
+impl Opcode {
+    fn from_u32(value: u32) -> Option<Self> {
+        match value {
+            0 => Some(Opcode::Handshake),
+            1 => Some(Opcode::Frame),
+            2 => Some(Opcode::Close),
+            3 => Some(Opcode::Ping),
+            4 => Some(Opcode::Pong),
+            _ => None,
+        }
+    }
+}
+
+// Writes one frame: opcode && length as little endian u32, then the JSON payload
+pub fn write_frame<W: Write + ?Sized>(writer: &mut W, opcode: Opcode, payload: &Value) -> Result<(), DiscordError> {
+    let payload = serde_json::to_vec(payload)?;
+    let mut frame = Vec::with_capacity(8 + payload.len());
+    frame.extend_from_slice(&(opcode as u32).to_le_bytes());
+    frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    frame.extend_from_slice(&payload);
+    writer.write_all(&frame)?;
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn read_frame<R: Read + ?Sized>(reader: &mut R) -> Result<(Opcode, Value), DiscordError> {
+    let mut header = [0u8; 8];
+    reader.read_exact(&mut header)?;
+    let opcode = u32::from_le_bytes(header[..4].try_into().unwrap());
+    let opcode = Opcode::from_u32(opcode).ok_or_else(|| DiscordError::Protocol(format!("unknown opcode {}", opcode)))?;
+    let length = u32::from_le_bytes(header[4..].try_into().unwrap()) as usize;
+    if length > MAX_FRAME_LEN {
+        return Err(DiscordError::Protocol(format!("frame of {} bytes is too large", length)));
+    }
+    let mut payload = vec![0u8; length];
+    reader.read_exact(&mut payload)?;
+    Ok((opcode, serde_json::from_slice(&payload)?))
+}
+
+// Lists every path a Discord client may listen on, in the order they are tried
+#[cfg(unix)]
+pub fn socket_candidates<F>(var: F) -> Vec<PathBuf>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut bases: Vec<PathBuf> = Vec::new();
+    let vars = ["XDG_RUNTIME_DIR", "TMPDIR", "TMP", "TEMP"].iter().filter_map(|name| var(name).filter(|value| !value.is_empty()));
+    for base in vars.map(PathBuf::from).chain([PathBuf::from("/tmp")]) {
+        if !bases.contains(&base) {
+            bases.push(base);
+        }
+    }
+
+    let dirs = bases.iter().flat_map(|base| [base.clone()].into_iter().chain(SANDBOX_DIRS.iter().map(move |dir| base.join(dir))));
+    dirs.flat_map(|dir| (0..=MAX_SOCKET_INDEX).map(move |index| dir.join(format!("{}{}", SOCKET_NAME, index))))
+        .collect()
+}
+
+// Windows clients all listen on named pipes, whatever the install
+#[cfg(windows)]
+pub fn socket_candidates<F>(_var: F) -> Vec<PathBuf>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    (0..=MAX_SOCKET_INDEX).map(|index| PathBuf::from(format!(r"\\?\pipe\{}{}", SOCKET_NAME, index))).collect()
+}
+
+trait IpcStream: Read + Write + Send {}
+
+impl<T: Read + Write + Send> IpcStream for T {}
+
+#[cfg(unix)]
+fn open_stream(path: &Path) -> Result<Box<dyn IpcStream>, DiscordError> {
+    let stream = UnixStream::connect(path)?;
+    stream.set_read_timeout(Some(IPC_TIMEOUT))?;
+    stream.set_write_timeout(Some(IPC_TIMEOUT))?;
+    Ok(Box::new(stream))
+}
+
+#[cfg(windows)]
+fn open_stream(path: &Path) -> Result<Box<dyn IpcStream>, DiscordError> {
+    Ok(Box::new(PipeStream::open(path)?))
+}
+
+// Named pipe opened for overlapped I/O, so a hung client times out like a Unix socket does
+#[cfg(windows)]
+struct PipeStream {
+    file: File,
+    // Manual-reset event signalled when the pending request completes
+    event: HANDLE,
+}
+
+// The event handle is only used by the thread that owns the stream
+#[cfg(windows)]
+unsafe impl Send for PipeStream {}
+
+#[cfg(windows)]
+impl PipeStream {
+    fn open(path: &Path) -> io::Result<Self> {
+        let file = std::fs::OpenOptions::new().read(true).write(true).custom_flags(FILE_FLAG_OVERLAPPED).open(path)?;
+        let event = unsafe { CreateEventW(ptr::null_mut(), TRUE, FALSE, ptr::null()) };
+        if event.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self { file, event })
+    }
+
+    // Starts a read or write && waits up to IPC_TIMEOUT for it, cancelling it when Discord doesn't answer
+    fn transfer<F>(&mut self, start: F) -> io::Result<usize>
+    where
+        F: FnOnce(HANDLE, *mut OVERLAPPED) -> BOOL,
+    {
+        let handle = self.file.as_raw_handle() as HANDLE;
+        let mut overlapped: OVERLAPPED = unsafe { mem::zeroed() };
+        overlapped.hEvent = self.event;
+        let mut transferred: DWORD = 0;
+        unsafe {
+            if start(handle, &mut overlapped) == FALSE {
+                let error = io::Error::last_os_error();
+                if error.raw_os_error() != Some(ERROR_IO_PENDING as i32) {
+                    return Err(error);
+                }
+                if WaitForSingleObject(self.event, IPC_TIMEOUT.as_millis() as DWORD) == WAIT_TIMEOUT {
+                    CancelIoEx(handle, &mut overlapped);
+                    // The request uses the buffer && overlapped until the cancel is through
+                    GetOverlappedResult(handle, &mut overlapped, &mut transferred, TRUE);
+                    return Err(io::Error::new(io::ErrorKind::TimedOut, "Discord did not answer in time"));
+                }
+            }
+            if GetOverlappedResult(handle, &mut overlapped, &mut transferred, FALSE) == FALSE {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        Ok(transferred as usize)
+    }
+}
+
+#[cfg(windows)]
+impl Read for PipeStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = buf.len().min(DWORD::MAX as usize) as DWORD;
+        let result = self.transfer(|handle, overlapped| unsafe { ReadFile(handle, buf.as_mut_ptr() as LPVOID, len, ptr::null_mut(), overlapped) });
+        match result {
+            // The client closed its end
+            Err(e) if e.raw_os_error() == Some(ERROR_BROKEN_PIPE as i32) => Ok(0),
+            result => result,
+        }
+    }
+}
+
+#[cfg(windows)]
+impl Write for PipeStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = buf.len().min(DWORD::MAX as usize) as DWORD;
+        self.transfer(|handle, overlapped| unsafe { WriteFile(handle, buf.as_ptr() as LPCVOID, len, ptr::null_mut(), overlapped) })
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(windows)]
+impl Drop for PipeStream {
+    fn drop(&mut self) {
+        unsafe {
+            CloseHandle(self.event);
+        }
+    }
+}
+
+// The READY dispatch Discord answers the handshake with
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ready {
+    pub version: Option<u64>,
+    pub user_id: Option<String>,
+    pub username: Option<String>,
+    pub data: Value,
+}
+
+impl Ready {
+    fn parse(data: Value) -> Self {
+        Self {
+            version: data["v"].as_u64(),
+            user_id: data["user"]["id"].as_str().map(str::to_string),
+            username: data["user"]["username"].as_str().map(str::to_string),
+            data,
+        }
+    }
+}
+
+impl fmt::Display for Ready {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.username {
+            Some(username) => write!(f, "user {}", username),
+            None => write!(f, "unknown user"),
+        }
+    }
+}
+
+fn error_fields(data: &Value) -> (i64, String) {
+    (data["code"].as_i64().unwrap_or(0), data["message"].as_str().unwrap_or("no message").to_string())
+}
+
+// Connection to a Discord client's RPC socket, handshake done
+pub struct IpcConnection {
+    stream: Box<dyn IpcStream>,
+    path: PathBuf,
+    ready: Ready,
+    nonce: u64,
+}
+
+impl IpcConnection {
+    // Tries every candidate socket, the first client to accept the handshake wins
+    pub fn connect(client_id: &str) -> Result<Self, DiscordError> {
+        Self::connect_first(&socket_candidates(|name| env::var(name).ok()), client_id)
+    }
+
+    // Tries the given sockets in order, skipping missing paths && clients that refuse the handshake
+    pub fn connect_first(candidates: &[PathBuf], client_id: &str) -> Result<Self, DiscordError> {
+        let mut last_error = None;
+        for path in candidates.iter().filter(|path| cfg!(windows) || path.exists()) {
+            match Self::connect_to(path, client_id) {
+                Ok(connection) => return Ok(connection),
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(last_error.unwrap_or(DiscordError::NotFound(candidates.len())))
+    }
+
+    pub fn connect_to(path: &Path, client_id: &str) -> Result<Self, DiscordError> {
+        let mut stream = open_stream(path)?;
+        write_frame(&mut stream, Opcode::Handshake, &json!({ "v": RPC_VERSION, "client_id": client_id }))?;
+
+        let reply = read_reply(&mut stream)?;
+        match reply["evt"].as_str() {
+            Some("READY") => Ok(Self { stream, path: path.to_path_buf(), ready: Ready::parse(reply["data"].clone()), nonce: 0 }),
+            Some("ERROR") => {
+                let (code, message) = error_fields(&reply["data"]);
+                Err(DiscordError::Rpc { code, message })
+            }
+            _ => Err(DiscordError::Protocol(format!("expected READY, got {}", reply))),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn ready(&self) -> &Ready {
+        &self.ready
+    }
+
+    // Sends a command && waits for the reply carrying its nonce, returning the reply's data
+    pub fn send_command(&mut self, command: &str, args: Value) -> Result<Value, DiscordError> {
+        self.nonce += 1;
+        let nonce = self.nonce.to_string();
+        write_frame(&mut self.stream, Opcode::Frame, &json!({ "cmd": command, "args": args, "nonce": nonce }))?;
+
+        loop {
+            let reply = read_reply(&mut self.stream)?;
+            if reply["nonce"].as_str() != Some(nonce.as_str()) {
+                continue;
+            }
+            if reply["evt"].as_str() == Some("ERROR") {
+                let (code, message) = error_fields(&reply["data"]);
+                return Err(DiscordError::Rpc { code, message });
+            }
+            return Ok(reply["data"].clone());
+        }
+    }
+
+    // Shows the activity on the user's profile, None clears it
+    pub fn set_activity(&mut self, activity: Option<Value>) -> Result<Value, DiscordError> {
+        let activity = activity.unwrap_or(Value::Null);
+        self.send_command("SET_ACTIVITY", json!({ "pid": std::process::id(), "activity": activity }))
+    }
+
+    pub fn clear_activity(&mut self) -> Result<Value, DiscordError> {
+        self.set_activity(None)
+    }
+
+    // Sends a ping && waits for the matching pong, an error means the socket is gone
+    pub fn ping(&mut self) -> Result<(), DiscordError> {
+        self.nonce += 1;
+        let payload = json!({ "nonce": self.nonce.to_string() });
+        write_frame(&mut self.stream, Opcode::Ping, &payload)?;
+
+        loop {
+            match read_frame(&mut self.stream)? {
+                (Opcode::Pong, reply) if reply == payload => return Ok(()),
+                (Opcode::Ping, ping) => write_frame(&mut self.stream, Opcode::Pong, &ping)?,
+                (Opcode::Close, reply) => {
+                    let (code, message) = error_fields(&reply);
+                    return Err(DiscordError::Closed { code, message });
+                }
+                // Replies to commands given up on earlier
+                _ => continue,
+            }
+        }
+    }
+}
+
+impl Drop for IpcConnection {
+    fn drop(&mut self) {
+        let _ = write_frame(&mut self.stream, Opcode::Close, &json!({}));
+    }
+}
+
+// Reads the next dispatch, answering pings && turning CLOSE into an error
+fn read_reply<S: Read + Write + ?Sized>(stream: &mut S) -> Result<Value, DiscordError> {
+    loop {
+        match read_frame(stream)? {
+            (Opcode::Ping, payload) => write_frame(stream, Opcode::Pong, &payload)?,
+            (Opcode::Pong, _) => continue,
+            (Opcode::Close, payload) => {
+                let (code, message) = error_fields(&payload);
+                return Err(DiscordError::Closed { code, message });
+            }
+            (Opcode::Frame, payload) => return Ok(payload),
+            (Opcode::Handshake, _) => return Err(DiscordError::Protocol("unexpected handshake from Discord".to_string())),
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use std::env;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::discord::socket_candidates;
+use crate::process::{list_processes, ProcessEntry};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DiscordVariant {
+    Stable,
+    Ptb,
+    Canary,
+    // The com.discordapp.Discord Flatpak, whichever channel it runs
+    Flatpak,
+    Vesktop,
+    WebCord,
+}
+
+impl fmt::Display for DiscordVariant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            DiscordVariant::Stable => "Discord",
+            DiscordVariant::Ptb => "Discord PTB",
+            DiscordVariant::Canary => "Discord Canary",
+            DiscordVariant::Flatpak => "Discord (Flatpak)",
+            DiscordVariant::Vesktop => "Vesktop",
+            DiscordVariant::WebCord => "WebCord",
+        };
+        f.pad(name)
+    }
+}
+
+// Gets the variant an executable name belongs to, e.g. "DiscordCanary.exe" or "vesktop"
+pub fn variant_from_name(name: &str) -> Option<DiscordVariant> {
+    let name = name.to_ascii_lowercase();
+    match name.strip_suffix(".exe").unwrap_or(&name) {
+        "discord" => Some(DiscordVariant::Stable),
+        "discordptb" | "discord-ptb" => Some(DiscordVariant::Ptb),
+        "discordcanary" | "discord-canary" => Some(DiscordVariant::Canary),
+        "vesktop" => Some(DiscordVariant::Vesktop),
+        "webcord" => Some(DiscordVariant::WebCord),
+        _ => None,
+    }
+}
+
+// Names worth a closer look, a client itself or an Electron that may run one
+pub fn is_candidate_name(name: &str) -> bool {
+    variant_from_name(name).is_some() || name.to_ascii_lowercase().starts_with("electron")
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit(['/', '\\']).next().unwrap_or(path)
+}
+
+// Recognises a Discord client from its name, executable && command line
+pub fn detect_variant(process: &ProcessEntry) -> Option<DiscordVariant> {
+    let exe = process.exe.as_deref().and_then(Path::to_str);
+    let argv0 = process.args.first().map(String::as_str);
+
+    let variant = [Some(process.name.as_str()), exe.map(file_name), argv0.map(file_name)]
+        .into_iter()
+        .flatten()
+        .find_map(variant_from_name)
+        .or_else(|| {
+            // Clients run by a system-wide Electron only show up in the app path, e.g. electron /usr/lib/webcord/app.asar
+            let electron = [Some(process.name.as_str()), exe.map(file_name)].into_iter().flatten().any(|name| name.starts_with("electron"));
+            let app = process.args.iter().skip(1).find(|arg| !arg.starts_with('-'))?;
+            electron.then(|| app.split(['/', '\\']).find_map(variant_from_name)).flatten()
+        })?;
+
+    // Flatpak apps see their own files under /app
+    let sandboxed = [exe, argv0].into_iter().flatten().any(|path| path.starts_with("/app/"));
+    match variant {
+        DiscordVariant::Stable | DiscordVariant::Ptb | DiscordVariant::Canary if sandboxed => Some(DiscordVariant::Flatpak),
+        variant => Some(variant),
+    }
+}
+
+// What was found of running Discord clients
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiscordScan {
+    // Each variant once, in the order of DiscordVariant
+    pub variants: Vec<DiscordVariant>,
+    // IPC sockets that exist, a client may be running where the process list can't see it
+    pub sockets: Vec<PathBuf>,
+}
+
+impl DiscordScan {
+    pub fn from_parts(processes: &[ProcessEntry], sockets: Vec<PathBuf>) -> Self {
+        let mut variants: Vec<DiscordVariant> = processes.iter().filter_map(detect_variant).collect();
+        variants.sort();
+        variants.dedup();
+        Self { variants, sockets }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.variants.is_empty() && self.sockets.is_empty()
+    }
+}
+
+impl fmt::Display for DiscordScan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let variants: Vec<String> = self.variants.iter().map(ToString::to_string).collect();
+        match (variants.is_empty(), self.sockets.first()) {
+            (true, None) => write!(f, "no Discord client"),
+            (true, Some(socket)) => write!(f, "IPC socket {}", socket.display()),
+            (false, _) => write!(f, "{}", variants.join(", ")),
+        }
+    }
+}
+
+// Looks for Discord clients in the process list && for their IPC sockets
+pub fn scan_discord() -> DiscordScan {
+    DiscordScan::from_parts(&list_processes(is_candidate_name), ipc_endpoints())
+}
+
+// Lists the IPC sockets a Discord client listens on
+#[cfg(unix)]
+pub fn ipc_endpoints() -> Vec<PathBuf> {
+    existing_sockets(socket_candidates(|name| env::var(name).ok()))
+}
+
+// Keeps the candidates that exist, each socket once even when symlinked into several folders
+#[cfg(unix)]
+pub fn existing_sockets(candidates: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut seen = Vec::new();
+    candidates
+        .into_iter()
+        .filter(|path| match path.canonicalize() {
+            Ok(target) if !seen.contains(&target) => {
+                seen.push(target);
+                true
+            }
+            _ => false,
+        })
+        .collect()
+}
+
+// Named pipes can't be opened without taking the client's instance, but the pipe namespace can be listed
+#[cfg(windows)]
+pub fn ipc_endpoints() -> Vec<PathBuf> {
+    let pipes: Vec<String> = std::fs::read_dir(r"\\.\pipe\")
+        .map(|entries| entries.filter_map(Result::ok).filter_map(|entry| entry.file_name().into_string().ok()).collect())
+        .unwrap_or_default();
+    socket_candidates(|name| env::var(name).ok())
+        .into_iter()
+        .filter(|path| path.file_name().and_then(|name| name.to_str()).is_some_and(|name| pipes.iter().any(|pipe| pipe == name)))
+        .collect()
+}
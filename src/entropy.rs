@@ -0,0 +1,269 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, TryRecvError};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::resolver::ResolvedFile;
+
+pub const DEFAULT_BLOCK_SIZE: usize = 4096;
+// Larger files are only profiled up to this many bytes
+pub const DEFAULT_SIZE_CAP: u64 = 256 * 1024 * 1024;
+
+// Bits per byte above which a block looks compressed or encrypted
+const HIGH_ENTROPY: f64 = 7.2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntropyVerdict {
+    Empty,
+    Sparse,
+    Text,
+    Structured,
+    PartlyPacked,
+    Packed,
+}
+
+impl fmt::Display for EntropyVerdict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EntropyVerdict::Empty => write!(f, "empty"),
+            EntropyVerdict::Sparse => write!(f, "mostly padding"),
+            EntropyVerdict::Text => write!(f, "likely text"),
+            EntropyVerdict::Structured => write!(f, "likely code or structured data"),
+            EntropyVerdict::PartlyPacked => write!(f, "contains compressed/encrypted regions"),
+            EntropyVerdict::Packed => write!(f, "likely compressed/encrypted"),
+        }
+    }
+}
+
+// Whole-file && block-wise entropy in bits per byte
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntropyProfile {
+    pub entropy: f64,
+    pub blocks: Vec<f64>,
+    pub bytes_read: u64,
+    // Set when the size cap stopped the analysis early
+    pub truncated: bool,
+}
+
+impl EntropyProfile {
+    // Share of blocks that look compressed or encrypted
+    pub fn high_entropy_ratio(&self) -> f64 {
+        if self.blocks.is_empty() {
+            return 0.0;
+        }
+        self.blocks.iter().filter(|&&entropy| entropy >= HIGH_ENTROPY).count() as f64 / self.blocks.len() as f64
+    }
+
+    pub fn verdict(&self) -> EntropyVerdict {
+        let high_ratio = self.high_entropy_ratio();
+        if self.bytes_read == 0 {
+            EntropyVerdict::Empty
+        } else if self.entropy >= 7.5 || high_ratio >= 0.9 {
+            EntropyVerdict::Packed
+        } else if high_ratio >= 0.1 {
+            EntropyVerdict::PartlyPacked
+        } else if self.entropy < 1.0 {
+            EntropyVerdict::Sparse
+        } else if self.entropy < 5.0 {
+            EntropyVerdict::Text
+        } else {
+            EntropyVerdict::Structured
+        }
+    }
+}
+
+impl fmt::Display for EntropyProfile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:.2} bits/byte, {} ({:.0}% of {} blocks high entropy",
+            self.entropy,
+            self.verdict(),
+            self.high_entropy_ratio() * 100.0,
+            self.blocks.len()
+        )?;
+        if self.truncated {
+            write!(f, ", first {} bytes only", self.bytes_read)?;
+        }
+        write!(f, ")")
+    }
+}
+
+// Computes the Shannon entropy of a byte histogram in bits per byte
+pub fn shannon_entropy(counts: &[u64; 256]) -> f64 {
+    let total: u64 = counts.iter().sum();
+    if total == 0 {
+        return 0.0;
+    }
+    let total = total as f64;
+    counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f64 / total;
+            p * (1.0 / p).log2()
+        })
+        .sum()
+}
+
+// Streams a reader into an entropy profile, None if cancelled midway
+pub fn analyze<R: Read>(reader: &mut R, block_size: usize, size_cap: u64, cancel: &AtomicBool) -> io::Result<Option<EntropyProfile>> {
+    let mut totals = [0u64; 256];
+    let mut blocks = Vec::new();
+    let mut buffer = vec![0u8; block_size.max(1)];
+    let mut bytes_read = 0u64;
+    let mut reader = reader.take(size_cap);
+
+    loop {
+        if cancel.load(Ordering::Relaxed) {
+            return Ok(None);
+        }
+
+        // Fill a whole block so short reads do not skew the block entropy
+        let mut filled = 0;
+        while filled < buffer.len() {
+            match reader.read(&mut buffer[filled..]) {
+                Ok(0) => break,
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        if filled == 0 {
+            break;
+        }
+
+        let mut counts = [0u64; 256];
+        for &byte in &buffer[..filled] {
+            counts[byte as usize] += 1;
+        }
+        for (total, count) in totals.iter_mut().zip(counts.iter()) {
+            *total += count;
+        }
+        blocks.push(shannon_entropy(&counts));
+        bytes_read += filled as u64;
+    }
+
+    // Anything left past the cap means the profile only covers a prefix
+    let truncated = bytes_read == size_cap && reader.into_inner().read(&mut [0u8; 1])? > 0;
+    Ok(Some(EntropyProfile { entropy: shannon_entropy(&totals), blocks, bytes_read, truncated }))
+}
+
+struct EntropyJob {
+    key: (PathBuf, Option<SystemTime>),
+    cancel: Arc<AtomicBool>,
+    receiver: Option<Receiver<io::Result<Option<EntropyProfile>>>>,
+    profile: Option<EntropyProfile>,
+}
+
+impl Drop for EntropyJob {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+// Profiles the analyzed file on a background thread, restarting whenever the file changes
+pub struct EntropyAnalyzer {
+    block_size: usize,
+    size_cap: u64,
+    job: Option<EntropyJob>,
+}
+
+impl EntropyAnalyzer {
+    pub fn new() -> Self {
+        Self { block_size: DEFAULT_BLOCK_SIZE, size_cap: DEFAULT_SIZE_CAP, job: None }
+    }
+
+    pub fn with_block_size(mut self, block_size: usize) -> Self {
+        self.block_size = block_size;
+        self
+    }
+
+    pub fn with_size_cap(mut self, size_cap: u64) -> Self {
+        self.size_cap = size_cap;
+        self
+    }
+
+    // Follows the analyzed file, returns true when its profile finished during this call
+    pub fn update(&mut self, file: Option<&ResolvedFile>) -> bool {
+        let file = match file {
+            Some(file) => file,
+            None => {
+                self.job = None;
+                return false;
+            }
+        };
+
+        let key = (file.path.clone(), file.modified);
+        if self.job.as_ref().is_none_or(|job| job.key != key) {
+            // Dropping the previous job cancels its thread
+            self.job = Some(self.spawn(key));
+        }
+        self.poll(None)
+    }
+
+    // Blocks until the current profile is ready, returns true if it finished in time
+    pub fn wait(&mut self, timeout: Duration) -> bool {
+        self.poll(Some(timeout)) || self.profile().is_some()
+    }
+
+    pub fn profile(&self) -> Option<&EntropyProfile> {
+        self.job.as_ref().and_then(|job| job.profile.as_ref())
+    }
+
+    fn poll(&mut self, timeout: Option<Duration>) -> bool {
+        let job = match self.job.as_mut() {
+            Some(job) => job,
+            None => return false,
+        };
+        let result = match (&job.receiver, timeout) {
+            (None, _) => return false,
+            (Some(receiver), Some(timeout)) => match receiver.recv_timeout(timeout) {
+                Ok(result) => result,
+                Err(RecvTimeoutError::Timeout) => return false,
+                Err(RecvTimeoutError::Disconnected) => Ok(None),
+            },
+            (Some(receiver), None) => match receiver.try_recv() {
+                Ok(result) => result,
+                Err(TryRecvError::Empty) => return false,
+                Err(TryRecvError::Disconnected) => Ok(None),
+            },
+        };
+
+        job.receiver = None;
+        match result {
+            Ok(Some(profile)) => {
+                job.profile = Some(profile);
+                true
+            }
+            // Unreadable files && cancelled jobs simply have no profile
+            _ => false,
+        }
+    }
+
+    fn spawn(&self, key: (PathBuf, Option<SystemTime>)) -> EntropyJob {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let (sender, receiver) = mpsc::channel();
+        let path = key.0.clone();
+        let thread_cancel = Arc::clone(&cancel);
+        let (block_size, size_cap) = (self.block_size, self.size_cap);
+
+        thread::spawn(move || {
+            let result = File::open(&path).and_then(|mut file| analyze(&mut file, block_size, size_cap, &thread_cancel));
+            let _ = sender.send(result);
+        });
+
+        EntropyJob { key, cancel, receiver: Some(receiver), profile: None }
+    }
+}
+
+impl Default for EntropyAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
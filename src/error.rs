@@ -0,0 +1,27 @@
+#[derive(Debug)]
+pub enum AppError {
+    Discord(String),
+    // Discord answered but refused the request, the connection is still fine
+    DiscordRejected(String),
+    Filesystem(std::io::Error),
+    Configuration(String),
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::Discord(msg) => write!(f, "Discord error: {}", msg),
+            AppError::DiscordRejected(msg) => write!(f, "Discord rejected the request: {}", msg),
+            AppError::Filesystem(err) => write!(f, "Filesystem error: {}", err),
+            AppError::Configuration(msg) => write!(f, "Configuration error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<std::io::Error> for AppError {
+    fn from(err: std::io::Error) -> Self {
+        AppError::Filesystem(err)
+    }
+}
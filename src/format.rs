@@ -0,0 +1,323 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use crate::binary::{self, elf_machine_name, mach_o_cpu_name, pe_machine_name, BinarySummary};
+use crate::resolver::ResolvedFile;
+
+// Enough to reach the deepest signature we check, the ISO 9660 volume descriptor at 0x8001
+pub const HEADER_LEN: usize = 0x8010;
+
+// Classifications kept before the cache is flushed
+const CACHE_CAPACITY: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FormatCategory {
+    Executable,
+    Archive,
+    Image,
+    Firmware,
+    Filesystem,
+    Document,
+}
+
+impl FormatCategory {
+    // Discord asset key shown as the large image
+    pub fn image_key(&self) -> &'static str {
+        match self {
+            FormatCategory::Executable => "format_executable",
+            FormatCategory::Archive => "format_archive",
+            FormatCategory::Image => "format_image",
+            FormatCategory::Firmware => "format_firmware",
+            FormatCategory::Filesystem => "format_filesystem",
+            FormatCategory::Document => "format_document",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileFormat {
+    pub category: FormatCategory,
+    // Human readable description, e.g. "ELF x86-64 shared object"
+    pub description: String,
+    // Header metadata for ELF, PE && Mach-O executables
+    pub binary: Option<BinarySummary>,
+}
+
+impl FileFormat {
+    fn new(category: FormatCategory, description: impl Into<String>) -> Self {
+        Self { category, description: description.into(), binary: None }
+    }
+}
+
+impl fmt::Display for FileFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.description)
+    }
+}
+
+// Classifies resolved files, remembering the result until the file changes
+#[derive(Default)]
+pub struct FormatClassifier {
+    cache: HashMap<PathBuf, (Option<SystemTime>, Option<FileFormat>)>,
+}
+
+impl FormatClassifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn classify(&mut self, file: &ResolvedFile) -> Option<FileFormat> {
+        if let Some((modified, format)) = self.cache.get(&file.path) {
+            if *modified == file.modified {
+                return format.clone();
+            }
+        }
+
+        let mut format = read_header(file).and_then(|header| classify_header(&header));
+        if let Some(format) = format.as_mut().filter(|format| format.category == FormatCategory::Executable) {
+            format.binary = binary::inspect_file(&file.path).ok();
+        }
+        if self.cache.len() >= CACHE_CAPACITY {
+            self.cache.clear();
+        }
+        self.cache.insert(file.path.clone(), (file.modified, format.clone()));
+        format
+    }
+}
+
+fn read_header(file: &ResolvedFile) -> Option<Vec<u8>> {
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    File::open(&file.path).ok()?.take(HEADER_LEN as u64).read_to_end(&mut header).ok()?;
+    Some(header)
+}
+
+fn has_bytes(header: &[u8], offset: usize, magic: &[u8]) -> bool {
+    header.get(offset..offset + magic.len()) == Some(magic)
+}
+
+fn read_u16(header: &[u8], offset: usize, big_endian: bool) -> Option<u16> {
+    let bytes: [u8; 2] = header.get(offset..offset + 2)?.try_into().ok()?;
+    Some(if big_endian { u16::from_be_bytes(bytes) } else { u16::from_le_bytes(bytes) })
+}
+
+fn read_u32(header: &[u8], offset: usize, big_endian: bool) -> Option<u32> {
+    let bytes: [u8; 4] = header.get(offset..offset + 4)?.try_into().ok()?;
+    Some(if big_endian { u32::from_be_bytes(bytes) } else { u32::from_le_bytes(bytes) })
+}
+
+// Identifies a file from its leading bytes
+pub fn classify_header(header: &[u8]) -> Option<FileFormat> {
+    classify_executable(header)
+        .or_else(|| classify_archive(header))
+        .or_else(|| classify_image(header))
+        .or_else(|| classify_firmware(header))
+        .or_else(|| classify_filesystem(header))
+        .or_else(|| classify_document(header))
+}
+
+fn classify_executable(header: &[u8]) -> Option<FileFormat> {
+    use FormatCategory::Executable;
+
+    if has_bytes(header, 0, b"\x7fELF") {
+        return Some(FileFormat::new(Executable, describe_elf(header)));
+    }
+    if has_bytes(header, 0, b"MZ") {
+        return Some(FileFormat::new(Executable, describe_pe(header)));
+    }
+    if let Some(description) = describe_mach_o(header) {
+        return Some(FileFormat::new(Executable, description));
+    }
+    if has_bytes(header, 0, b"\xCA\xFE\xBA\xBE") {
+        return Some(FileFormat::new(Executable, "Java class file"));
+    }
+    if has_bytes(header, 0, b"dex\n") {
+        return Some(FileFormat::new(Executable, "Android DEX file"));
+    }
+    if has_bytes(header, 0, b"\0asm") {
+        return Some(FileFormat::new(Executable, "WebAssembly module"));
+    }
+    None
+}
+
+fn describe_elf(header: &[u8]) -> String {
+    let big_endian = header.get(5) == Some(&2);
+    let machine = match read_u16(header, 18, big_endian) {
+        Some(machine) => elf_machine_name(machine).map_or_else(|| format!("machine 0x{:X}", machine), str::to_string),
+        None => return "ELF file".to_string(),
+    };
+    let kind = match read_u16(header, 16, big_endian) {
+        Some(1) => "relocatable",
+        Some(2) => "executable",
+        Some(3) => "shared object",
+        Some(4) => "core dump",
+        _ => "file",
+    };
+    format!("ELF {} {}", machine, kind)
+}
+
+fn describe_pe(header: &[u8]) -> String {
+    let pe_offset = match read_u32(header, 0x3C, false) {
+        Some(offset) if has_bytes(header, offset as usize, b"PE\0\0") => offset as usize,
+        _ => return "MS-DOS executable".to_string(),
+    };
+    let machine = match read_u16(header, pe_offset + 4, false) {
+        Some(machine) => pe_machine_name(machine).map_or_else(|| format!("machine 0x{:X}", machine), str::to_string),
+        None => return "PE file".to_string(),
+    };
+    let format = match read_u16(header, pe_offset + 24, false) {
+        Some(0x20B) => "PE32+",
+        _ => "PE32",
+    };
+    let is_dll = read_u16(header, pe_offset + 22, false).is_some_and(|characteristics| characteristics & 0x2000 != 0);
+    format!("{} {} {}", format, machine, if is_dll { "DLL" } else { "executable" })
+}
+
+fn describe_mach_o(header: &[u8]) -> Option<String> {
+    let (bits, big_endian) = match header.get(0..4)? {
+        [0xFE, 0xED, 0xFA, 0xCE] => ("32-bit", true),
+        [0xCE, 0xFA, 0xED, 0xFE] => ("32-bit", false),
+        [0xFE, 0xED, 0xFA, 0xCF] => ("64-bit", true),
+        [0xCF, 0xFA, 0xED, 0xFE] => ("64-bit", false),
+        // Fat binaries share their magic with Java classes, which store a much larger version there
+        [0xCA, 0xFE, 0xBA, 0xBE] if read_u32(header, 4, true).is_some_and(|count| count > 0 && count < 32) => {
+            return Some("Mach-O universal binary".to_string());
+        }
+        _ => return None,
+    };
+    let cpu = read_u32(header, 4, big_endian)?;
+    let cpu = mach_o_cpu_name(cpu).map_or_else(|| format!("CPU 0x{:X}", cpu), str::to_string);
+    let kind = match read_u32(header, 12, big_endian)? {
+        1 => "object",
+        2 => "executable",
+        4 => "core dump",
+        6 => "dynamic library",
+        8 => "bundle",
+        _ => "file",
+    };
+    Some(format!("Mach-O {} {} {}", bits, cpu, kind))
+}
+
+fn classify_archive(header: &[u8]) -> Option<FileFormat> {
+    let description = if has_bytes(header, 0, b"PK\x03\x04") || has_bytes(header, 0, b"PK\x05\x06") {
+        "ZIP archive"
+    } else if has_bytes(header, 0, b"\x1F\x8B") {
+        "gzip compressed data"
+    } else if has_bytes(header, 0, b"BZh") {
+        "bzip2 compressed data"
+    } else if has_bytes(header, 0, b"\xFD7zXZ\0") {
+        "XZ compressed data"
+    } else if has_bytes(header, 0, b"\x28\xB5\x2F\xFD") {
+        "Zstandard compressed data"
+    } else if has_bytes(header, 0, b"\x04\x22\x4D\x18") {
+        "LZ4 compressed data"
+    } else if has_bytes(header, 0, b"7z\xBC\xAF\x27\x1C") {
+        "7-Zip archive"
+    } else if has_bytes(header, 0, b"Rar!\x1A\x07") {
+        "RAR archive"
+    } else if has_bytes(header, 257, b"ustar") {
+        "tar archive"
+    } else if has_bytes(header, 0, b"070701") || has_bytes(header, 0, b"070702") {
+        "cpio archive"
+    } else if has_bytes(header, 0, b"!<arch>\n") {
+        "ar archive"
+    } else {
+        return None;
+    };
+    Some(FileFormat::new(FormatCategory::Archive, description))
+}
+
+fn classify_image(header: &[u8]) -> Option<FileFormat> {
+    let description = if has_bytes(header, 0, b"\x89PNG\r\n\x1A\n") {
+        "PNG image"
+    } else if has_bytes(header, 0, b"\xFF\xD8\xFF") {
+        "JPEG image"
+    } else if has_bytes(header, 0, b"GIF87a") || has_bytes(header, 0, b"GIF89a") {
+        "GIF image"
+    } else if has_bytes(header, 0, b"RIFF") && has_bytes(header, 8, b"WEBP") {
+        "WebP image"
+    } else if has_bytes(header, 0, b"II*\0") || has_bytes(header, 0, b"MM\0*") {
+        "TIFF image"
+    } else if has_bytes(header, 0, b"BM") && read_u32(header, 14, false).is_some_and(|size| [12, 40, 56, 108, 124].contains(&size)) {
+        "BMP image"
+    } else if has_bytes(header, 0, b"\0\0\x01\0") && read_u16(header, 4, false).is_some_and(|count| count > 0) {
+        "Windows icon"
+    } else if has_bytes(header, 0, b"8BPS") {
+        "Photoshop image"
+    } else {
+        return None;
+    };
+    Some(FileFormat::new(FormatCategory::Image, description))
+}
+
+fn classify_firmware(header: &[u8]) -> Option<FileFormat> {
+    let description = if has_bytes(header, 0, b"\x27\x05\x19\x56") {
+        "U-Boot image"
+    } else if has_bytes(header, 0, b"\xD0\x0D\xFE\xED") {
+        "Device tree blob"
+    } else if has_bytes(header, 0, b"ANDROID!") {
+        "Android boot image"
+    } else if has_bytes(header, 0, b"\x3A\xFF\x26\xED") {
+        "Android sparse image"
+    } else if has_bytes(header, 0, b"HDR0") {
+        "TRX firmware image"
+    } else if has_bytes(header, 0x10, b"\x5A\xA5\xF0\x0F") {
+        "Intel flash descriptor"
+    } else if has_bytes(header, 0, b"UBI#") {
+        "UBI image"
+    } else if has_bytes(header, 0, b"\x31\x18\x10\x06") {
+        "UBIFS image"
+    } else if header.len() >= 16 && header[0] == b':' && header[1..16].iter().all(u8::is_ascii_hexdigit) {
+        "Intel HEX firmware"
+    } else {
+        return None;
+    };
+    Some(FileFormat::new(FormatCategory::Firmware, description))
+}
+
+fn classify_filesystem(header: &[u8]) -> Option<FileFormat> {
+    let description = if has_bytes(header, 0x8001, b"CD001") {
+        "ISO 9660 filesystem"
+    } else if has_bytes(header, 0, b"hsqs") || has_bytes(header, 0, b"sqsh") {
+        "SquashFS filesystem"
+    } else if has_bytes(header, 0, b"\x45\x3D\xCD\x28") || has_bytes(header, 0, b"\x28\xCD\x3D\x45") {
+        "CramFS filesystem"
+    } else if has_bytes(header, 0, b"\x85\x19") || has_bytes(header, 0, b"\x19\x85") {
+        "JFFS2 filesystem"
+    } else if has_bytes(header, 0x438, b"\x53\xEF") {
+        "ext2/3/4 filesystem"
+    } else if has_bytes(header, 3, b"NTFS    ") {
+        "NTFS filesystem"
+    } else if has_bytes(header, 0x52, b"FAT32") {
+        "FAT32 filesystem"
+    } else if has_bytes(header, 0x36, b"FAT1") {
+        "FAT filesystem"
+    } else if has_bytes(header, 0x200, b"EFI PART") {
+        "GPT partitioned disk"
+    } else if has_bytes(header, 0x1FE, b"\x55\xAA") {
+        "MBR partitioned disk"
+    } else {
+        return None;
+    };
+    Some(FileFormat::new(FormatCategory::Filesystem, description))
+}
+
+fn classify_document(header: &[u8]) -> Option<FileFormat> {
+    let description = if has_bytes(header, 0, b"%PDF-") {
+        "PDF document"
+    } else if has_bytes(header, 0, b"%!PS") {
+        "PostScript document"
+    } else if has_bytes(header, 0, b"{\\rtf") {
+        "RTF document"
+    } else if has_bytes(header, 0, b"\xD0\xCF\x11\xE0\xA1\xB1\x1A\xE1") {
+        "Microsoft Office document"
+    } else if has_bytes(header, 0, b"SQLite format 3\0") {
+        "SQLite database"
+    } else {
+        return None;
+    };
+    Some(FileFormat::new(FormatCategory::Document, description))
+}
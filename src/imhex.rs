@@ -1,25 +1,77 @@
+#[cfg(windows)]
 use std::ffi::OsString;
+#[cfg(windows)]
+use std::mem;
 use std::fs::OpenOptions;
 use std::io::Write;
+#[cfg(windows)]
 use std::os::windows::ffi::OsStringExt;
-use std::os::windows::process::CommandExt;
-use std::process::Command;
 use std::sync::Mutex;
+use std::thread;
+#[cfg(target_os = "linux")]
+use std::path::Path;
 use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
 
+use crate::imhex_config::{DiscoveryEnv, ImhexConfig, ImhexSettings};
+use crate::network::{NetworkClient, Selection};
+use crate::pattern::PatternWatcher;
+use crate::probe::ImhexProbe;
+use crate::process::{find_imhex_processes, ImhexProcess};
+use crate::project::project_file_name;
+use crate::resolver::{FileResolver, ResolvedFile};
+use crate::title::{parse_window_title, OpenedContent};
 use crate::utils::current_timestamp;
+use crate::window::{ImhexWindow, ImhexWindows};
+#[cfg(target_os = "linux")]
+use crate::wayland::{Compositor, WaylandError};
+#[cfg(target_os = "linux")]
+use crate::x11::{X11Error, X11Session, X11Watcher};
 use lazy_static::lazy_static;
 use chrono::Local;
 use dirs::home_dir;
 
+#[cfg(windows)]
 use winapi::shared::minwindef::LPARAM;
+#[cfg(windows)]
 use winapi::shared::windef::HWND;
-use winapi::um::winbase::CREATE_NO_WINDOW;
-use winapi::um::winuser::{EnumWindows, GetWindowTextW};
+#[cfg(windows)]
+use winapi::um::sysinfoapi::GetTickCount;
+#[cfg(windows)]
+use winapi::um::winuser::{
+    EnumWindows, GetForegroundWindow, GetLastInputInfo, GetWindowTextW, GetWindowThreadProcessId,
+    IsWindowVisible, LASTINPUTINFO,
+};
 
 lazy_static! {
     static ref PREVIOUS_TITLE: Mutex<Option<String>> = Mutex::new(None);
-    static ref PREVIOUS_RUNNING_STATE: Mutex<bool> = Mutex::new(false);
+    static ref PREVIOUS_RUNNING_STATE: Mutex<Option<u32>> = Mutex::new(None);
+    static ref NETWORK_CLIENT: NetworkClient = NetworkClient::default().with_preview_len(SELECTION_PREVIEW_LEN);
+    static ref NETWORK_RETRY_AT: Mutex<Option<Instant>> = Mutex::new(None);
+}
+
+const SELECTION_PREVIEW_LEN: usize = 4;
+const NETWORK_RETRY_DELAY: Duration = Duration::from_secs(5);
+// With X11 events the loop still ticks this often for the selection && idle time
+#[cfg(target_os = "linux")]
+const WATCHED_POLL_INTERVAL: Duration = Duration::from_secs(1);
+#[cfg(target_os = "linux")]
+const EVENT_SETTLE_DELAY: Duration = Duration::from_millis(10);
+#[cfg(target_os = "linux")]
+const WATCHER_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+#[cfg(target_os = "linux")]
+lazy_static! {
+    static ref X11_SESSION: Mutex<Option<X11Session>> = Mutex::new(None);
+    static ref PREVIOUS_WINDOW_ERROR: Mutex<Option<String>> = Mutex::new(None);
+    // sway or Hyprland IPC, preferred over X11 in a Wayland session
+    static ref COMPOSITOR: Option<Compositor> = {
+        let compositor = Compositor::from_env();
+        if let Some(compositor) = &compositor {
+            log_error(&format!("Listing windows through {} IPC.", compositor.name()));
+        }
+        compositor
+    };
 }
 
 // Logs an error message to a file
@@ -44,21 +96,34 @@ fn get_log_file_path() -> Option<PathBuf> {
     })
 }
 
-// Converts a string to hex
-fn string_to_hex(s: &str) -> String {
-    if s == "ImHex" {
-        "0".to_string()
-    } else {
-        s.as_bytes()
-            .iter()
-            .map(|b| format!("{:02x}", b))
-            .collect::<Vec<String>>()
-            .join("")
-    }
+// Checks if a window title belongs to ImHex
+fn is_imhex_window_title(window_title: &str) -> bool {
+    parse_window_title(window_title).is_some()
+}
+
+// A top-level window as reported by the platform
+struct RawWindow {
+    title: String,
+    pid: Option<u32>,
+    focused: bool,
+    // X11 window id, used to subscribe to its title changes
+    #[cfg(target_os = "linux")]
+    window: Option<u32>,
+}
+
+#[cfg(windows)]
+struct EnumState {
+    foreground: HWND,
+    windows: Vec<RawWindow>,
 }
 
 // Windows callback function
+#[cfg(windows)]
 unsafe extern "system" fn enum_windows_proc(hwnd: HWND, lparam: LPARAM) -> i32 {
+    if IsWindowVisible(hwnd) == 0 {
+        return 1;
+    }
+
     let mut title: [u16; 256] = [0; 256];
     let length = GetWindowTextW(hwnd, title.as_mut_ptr(), title.len() as i32);
 
@@ -67,45 +132,189 @@ unsafe extern "system" fn enum_windows_proc(hwnd: HWND, lparam: LPARAM) -> i32 {
             .to_string_lossy()
             .into_owned();
 
-        if window_title.starts_with("ImHex") || window_title.contains("imhex-gui.exe") {
-            process_window_title(window_title, lparam);
-            return 0;
+        if is_imhex_window_title(&window_title) {
+            let state = &mut *(lparam as *mut EnumState);
+            let mut pid = 0;
+            GetWindowThreadProcessId(hwnd, &mut pid);
+            state.windows.push(RawWindow {
+                title: window_title,
+                pid: (pid != 0).then_some(pid),
+                focused: hwnd == state.foreground,
+            });
         }
     }
     1
 }
 
-// Processes the window title && logs
-fn process_window_title(window_title: String, lparam: LPARAM) {
-    let mut previous_title = PREVIOUS_TITLE.lock().unwrap();
-    if let Some(index) = window_title.find(" - ") {
-        let current_opened_file = &window_title[(index + 3)..];
-        if previous_title.as_deref() != Some(current_opened_file) {
-            log_error(&format!("Currently opened file: {}", current_opened_file));
-            *previous_title = Some(current_opened_file.to_string());
+// Finds every ImHex window through EnumWindows, which walks them in Z-order
+#[cfg(windows)]
+fn find_imhex_windows() -> Vec<RawWindow> {
+    let mut state = EnumState {
+        foreground: unsafe { GetForegroundWindow() },
+        windows: Vec::new(),
+    };
+    unsafe {
+        EnumWindows(Some(enum_windows_proc), &mut state as *mut _ as LPARAM);
+    }
+    state.windows
+}
+
+// Runs a query against the shared X11 connection, reconnecting after errors
+#[cfg(target_os = "linux")]
+fn with_x11_session<T, F>(query: F) -> Option<T>
+where
+    F: FnOnce(&X11Session) -> Result<T, X11Error>,
+{
+    let mut session = X11_SESSION.lock().unwrap();
+    if session.is_none() {
+        match X11Session::connect() {
+            Ok(connected) => *session = Some(connected),
+            Err(e) => {
+                log_x11_error(&e.to_string());
+                return None;
+            }
         }
-        unsafe { *(lparam as *mut String) = current_opened_file.to_string(); }
-    } else {
-        if previous_title.as_deref() != Some(&window_title) {
-            let hex_string = string_to_hex(&window_title);
-            log_error(&format!("Currently opened file: {}", hex_string));
-            *previous_title = Some(window_title.clone());
+    }
+
+    match query(session.as_ref().unwrap()) {
+        Ok(result) => Some(result),
+        Err(e) => {
+            // Drop the connection so the next poll reconnects
+            *session = None;
+            log_x11_error(&e.to_string());
+            None
         }
-        unsafe { *(lparam as *mut String) = window_title; }
     }
 }
 
-// Checks if an ImHex window exists && returns window title
-pub fn check_if_imhex_window_exists() -> Option<String> {
-    let mut found = String::new();
+// Runs a query against the compositor's IPC socket
+#[cfg(target_os = "linux")]
+fn query_compositor<T, F>(compositor: &Compositor, query: F) -> Option<T>
+where
+    F: FnOnce(&Compositor) -> Result<T, WaylandError>,
+{
+    query(compositor).map_err(|e| log_window_error(compositor.name(), &e.to_string())).ok()
+}
+
+// Finds every ImHex window through the compositor's IPC, else the EWMH client lists
+#[cfg(target_os = "linux")]
+fn find_imhex_windows() -> Vec<RawWindow> {
+    if let Some(compositor) = COMPOSITOR.as_ref() {
+        return query_compositor(compositor, Compositor::windows)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|window| is_imhex_window_title(&window.title))
+            .map(|window| RawWindow { title: window.title, pid: window.pid, focused: window.focused, window: None })
+            .collect();
+    }
+
+    with_x11_session(|session| session.find_windows(is_imhex_window_title))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|window| RawWindow { title: window.title, pid: window.pid, focused: window.active, window: Some(window.window) })
+        .collect()
+}
+
+// Checks if the foreground window belongs to one of the processes
+#[cfg(windows)]
+fn imhex_has_focus(processes: &[ImhexProcess]) -> Option<bool> {
+    let foreground = unsafe { GetForegroundWindow() };
+    if foreground.is_null() {
+        return Some(false);
+    }
+    let mut pid = 0;
     unsafe {
-        EnumWindows(Some(enum_windows_proc), &mut found as *mut _ as LPARAM);
+        GetWindowThreadProcessId(foreground, &mut pid);
     }
-    if found.is_empty() {
-        handle_no_imhex_window();
-        None
-    } else {
-        Some(found)
+    Some(processes.iter().any(|process| process.pid == pid))
+}
+
+// Checks if the compositor's focused window or _NET_ACTIVE_WINDOW belongs to one of the processes,
+// None without an EWMH window manager
+#[cfg(target_os = "linux")]
+fn imhex_has_focus(processes: &[ImhexProcess]) -> Option<bool> {
+    let focused_pid = match COMPOSITOR.as_ref() {
+        Some(compositor) => query_compositor(compositor, Compositor::focused_pid),
+        None => with_x11_session(|session| match session.active_window()? {
+            Some(window) => Ok(Some(session.window_pid(window)?)),
+            None => Ok(None),
+        })
+        .flatten(),
+    };
+    focused_pid.map(|pid| pid.is_some_and(|pid| processes.iter().any(|process| process.pid == pid)))
+}
+
+// Gets the time since the last input from GetLastInputInfo
+#[cfg(windows)]
+fn system_idle_time() -> Option<Duration> {
+    let mut info = LASTINPUTINFO { cbSize: mem::size_of::<LASTINPUTINFO>() as u32, dwTime: 0 };
+    if unsafe { GetLastInputInfo(&mut info) } == 0 {
+        return None;
+    }
+    // The tick count wraps after 49.7 days
+    let now = unsafe { GetTickCount() };
+    Some(Duration::from_millis(u64::from(now.wrapping_sub(info.dwTime))))
+}
+
+// Gets the time since the last input from the XScreenSaver extension
+#[cfg(target_os = "linux")]
+fn system_idle_time() -> Option<Duration> {
+    with_x11_session(|session| session.idle_time())
+}
+
+// Logs an X11 error once instead of on every poll
+#[cfg(target_os = "linux")]
+fn log_x11_error(message: &str) {
+    log_window_error("X11", message);
+}
+
+// Logs a window listing error once instead of on every poll
+#[cfg(target_os = "linux")]
+fn log_window_error(source: &str, message: &str) {
+    let mut previous_error = PREVIOUS_WINDOW_ERROR.lock().unwrap();
+    if previous_error.as_deref() != Some(message) {
+        log_error(&format!("{} query failed: {}", source, message));
+        *previous_error = Some(message.to_string());
+    }
+}
+
+// Lists every ImHex window owned by one of the given processes
+pub fn list_imhex_windows(processes: &[ImhexProcess]) -> ImhexWindows {
+    collect_imhex_windows(find_imhex_windows(), processes)
+}
+
+fn collect_imhex_windows(raw_windows: Vec<RawWindow>, processes: &[ImhexProcess]) -> ImhexWindows {
+    let windows = raw_windows
+        .into_iter()
+        .filter_map(|window| ImhexWindow::from_title(&window.title, window.pid, window.focused))
+        .collect();
+    let mut windows = ImhexWindows::new(windows);
+
+    let pids: Vec<u32> = processes.iter().map(|process| process.pid).collect();
+    windows.retain_processes(&pids);
+
+    match windows.primary() {
+        Some(primary) => log_primary_window(primary, &windows),
+        None => handle_no_imhex_window(),
+    }
+    windows
+}
+
+// Logs the window the presence describes whenever it changes
+fn log_primary_window(primary: &ImhexWindow, windows: &ImhexWindows) {
+    let mut previous_title = PREVIOUS_TITLE.lock().unwrap();
+    if previous_title.as_deref() != Some(&primary.title) {
+        if windows.len() > 1 {
+            log_error(&format!(
+                "Currently opened file: {} ({} windows across {} instances)",
+                primary.state,
+                windows.len(),
+                windows.instance_count()
+            ));
+        } else {
+            log_error(&format!("Currently opened file: {}", primary.state));
+        }
+        *previous_title = Some(primary.title.clone());
     }
 }
 
@@ -118,54 +327,284 @@ fn handle_no_imhex_window() {
     }
 }
 
-// Gets bytes in ImHex
-pub fn get_selected_bytes() -> Option<String> {
-    if let Some(current_file) = check_if_imhex_window_exists() {
-        let hex_string = string_to_hex(&current_file);
-        let bytes: Vec<u8> = match hex_string.as_bytes().chunks(2).map(|chunk| {
-            u8::from_str_radix(std::str::from_utf8(chunk).unwrap_or_default(), 16).ok()
-        }).collect::<Option<Vec<u8>>>() {
-            Some(v) => v,
-            None => return None,
-        };
+// Gets the current selection through ImHex's network interface
+pub fn get_selected_bytes() -> Option<Selection> {
+    let mut retry_at = NETWORK_RETRY_AT.lock().unwrap();
+    if retry_at.is_some_and(|retry_at| Instant::now() < retry_at) {
+        return None;
+    }
 
-        if let (Some(&min), Some(&max)) = (bytes.iter().min(), bytes.iter().max()) {
-            return Some(format!("0x{:02X}-0x{:02X}", min, max));
+    match NETWORK_CLIENT.query_selection() {
+        Ok(selection) => {
+            if retry_at.take().is_some() {
+                log_error("ImHex network interface is reachable.");
+            }
+            selection
+        }
+        Err(e) => {
+            // Usually the interface is just disabled in ImHex, so back off quietly
+            if retry_at.is_none() {
+                log_error(&format!("Selection unavailable: {}", e));
+            }
+            *retry_at = Some(Instant::now() + NETWORK_RETRY_DELAY);
+            None
         }
     }
-    None
 }
 
-// Checks if ImHex is running
-pub(crate) fn is_imhex_running() -> bool {
-    let output = match Command::new("tasklist")
-        .arg("/FI")
-        .arg("IMAGENAME eq imhex-gui.exe")
-        .creation_flags(CREATE_NO_WINDOW)
-        .output() 
-    {
-        Ok(output) => output,
-        Err(e) => {
-            log_error(&format!("Failed to execute tasklist: {}", e));
+// Updates the running state
+fn update_running_state(process: Option<&ImhexProcess>) {
+    let mut previous_running_state = PREVIOUS_RUNNING_STATE.lock().unwrap();
+    let pid = process.map(|process| process.pid);
+    if pid != *previous_running_state {
+        match process {
+            Some(process) => log_error(&format!("ImHex is running (PID {}).", process.pid)),
+            None => log_error("ImHex is not running."),
+        }
+        *previous_running_state = pid;
+    }
+}
+
+// Last file name lookup, kept so the resolver only reruns when the window changes
+struct ResolvedName {
+    name: String,
+    pid: Option<u32>,
+    path: Option<PathBuf>,
+}
+
+// Wakes the loop on PropertyNotify for ImHex titles && focus, polling only while X11 events are unavailable
+#[cfg(target_os = "linux")]
+#[derive(Default)]
+struct WindowEvents {
+    watcher: Option<X11Watcher>,
+    windows: Vec<u32>,
+    retry_at: Option<Instant>,
+}
+
+#[cfg(target_os = "linux")]
+impl WindowEvents {
+    // Remembers which windows to watch for title changes
+    fn follow(&mut self, raw_windows: &[RawWindow]) {
+        self.windows = raw_windows.iter().filter_map(|window| window.window).collect();
+    }
+
+    fn wait(&mut self, timeout: Duration) -> bool {
+        // Native Wayland windows never show up in X11 events
+        if COMPOSITOR.is_some() {
+            thread::sleep(timeout);
             return false;
         }
-    };
+        if self.watcher.is_none() && self.retry_at.is_none_or(|retry_at| Instant::now() >= retry_at) {
+            match X11Watcher::connect() {
+                Ok(watcher) => {
+                    log_error("Watching ImHex windows through X11 events.");
+                    self.watcher = Some(watcher);
+                }
+                Err(e) => self.fail(&e),
+            }
+        }
+
+        let watcher = match self.watcher.as_mut() {
+            Some(watcher) => watcher,
+            None => {
+                thread::sleep(timeout);
+                return false;
+            }
+        };
+        let woken = watcher.watch_windows(&self.windows).and_then(|()| watcher.wait(timeout.max(WATCHED_POLL_INTERVAL)));
+        match woken {
+            Ok(Some(_)) => {
+                // Title && focus changes tend to arrive together, so let the burst settle into one tick
+                thread::sleep(EVENT_SETTLE_DELAY);
+                let _ = watcher.drain();
+                true
+            }
+            Ok(None) => false,
+            Err(e) => {
+                self.fail(&e);
+                false
+            }
+        }
+    }
 
-    let output_str = String::from_utf8_lossy(&output.stdout);
-    let is_running = output_str.contains("imhex-gui.exe");
-    update_running_state(is_running);
-    is_running
+    // Falls back to polling until the watcher can reconnect
+    fn fail(&mut self, error: &X11Error) {
+        log_x11_error(&error.to_string());
+        self.watcher = None;
+        self.retry_at = Some(Instant::now() + WATCHER_RETRY_DELAY);
+    }
 }
 
-// Updates the running state
-fn update_running_state(is_running: bool) {
-    let mut previous_running_state = PREVIOUS_RUNNING_STATE.lock().unwrap();
-    if is_running != *previous_running_state {
-        log_error(if is_running {
-            "ImHex is running."
-        } else {
-            "ImHex is not running."
+// ImHex config discovered for one process
+struct LoadedConfig {
+    pid: u32,
+    config: ImhexConfig,
+}
+
+// Finds the config directories of the ImHex process, including portable && AppImage installs
+#[cfg(target_os = "linux")]
+fn discovery_env(pid: u32) -> DiscoveryEnv {
+    DiscoveryEnv::from_env().with_process(Path::new("/proc"), pid)
+}
+
+#[cfg(windows)]
+fn discovery_env(pid: u32) -> DiscoveryEnv {
+    DiscoveryEnv::from_env().with_process(pid)
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+fn discovery_env(_pid: u32) -> DiscoveryEnv {
+    DiscoveryEnv::from_env()
+}
+
+// Probe backed by the live desktop
+#[derive(Default)]
+pub struct SystemProbe {
+    processes: Vec<ImhexProcess>,
+    resolver: FileResolver,
+    resolved: Option<ResolvedName>,
+    config: Option<LoadedConfig>,
+    patterns: Option<PatternWatcher>,
+    #[cfg(target_os = "linux")]
+    window_events: WindowEvents,
+}
+
+impl SystemProbe {
+    // Finds a file name on disk, reusing the last lookup while the window stays the same
+    fn resolve_name(&mut self, name: &str, pid: Option<u32>) -> Option<ResolvedFile> {
+        let cached = self.resolved.as_ref().filter(|resolved| resolved.name == name && resolved.pid == pid);
+        if let Some(resolved) = cached {
+            match &resolved.path {
+                // Refresh the metadata so size && mtime follow edits
+                Some(path) => {
+                    if let Some(file) = ResolvedFile::from_path(path) {
+                        return Some(file);
+                    }
+                }
+                None => return None,
+            }
+        }
+
+        let file = self.resolver.resolve(name, pid);
+        match &file {
+            Some(file) => log_error(&format!("Resolved {} to {} ({} bytes)", name, file.path.display(), file.size)),
+            None => log_error(&format!("Could not find {} on disk", name)),
+        }
+        self.resolved = Some(ResolvedName {
+            name: name.to_string(),
+            pid,
+            path: file.as_ref().map(|file| file.path.clone()),
         });
-        *previous_running_state = is_running;
+        file
+    }
+
+    // Loads ImHex's config once per process && rereads settings.json when ImHex saves it
+    fn update_config(&mut self, pid: Option<u32>) {
+        let pid = match pid {
+            Some(pid) => pid,
+            None => {
+                self.config = None;
+                self.patterns = None;
+                return;
+            }
+        };
+
+        if let Some(loaded) = self.config.as_mut().filter(|loaded| loaded.pid == pid) {
+            match loaded.config.refresh() {
+                Ok(true) => log_error(&format!("ImHex settings changed: {}", loaded.config)),
+                Ok(false) => {}
+                Err(e) => log_error(&e.to_string()),
+            }
+            return;
+        }
+
+        let config = match ImhexConfig::discover(&discovery_env(pid)) {
+            Ok(config) => config,
+            Err(e) => {
+                log_error(&e.to_string());
+                ImhexConfig::default()
+            }
+        };
+        log_error(&format!("ImHex config: {}", config));
+        self.resolver = FileResolver::new(config.dir_paths()).with_recent_files(config.settings.recent_files.clone());
+        self.resolved = None;
+        let patterns = PatternWatcher::for_config_dirs(&config.dir_paths());
+        if !patterns.dirs().is_empty() {
+            let method = if patterns.uses_inotify() { "inotify" } else { "rescanning" };
+            log_error(&format!("Watching {} pattern folder(s) by {}", patterns.dirs().len(), method));
+        }
+        self.patterns = Some(patterns);
+        self.config = Some(LoadedConfig { pid, config });
+    }
+}
+
+impl ImhexProbe for SystemProbe {
+    // Gets the oldest running ImHex process && remembers the rest for window ownership
+    fn imhex_process(&mut self) -> Option<ImhexProcess> {
+        self.processes = find_imhex_processes();
+        let process = self.processes.first().cloned();
+        update_running_state(process.as_ref());
+        self.update_config(process.as_ref().map(|process| process.pid));
+        process
+    }
+
+    fn windows(&mut self) -> ImhexWindows {
+        let raw_windows = find_imhex_windows();
+        #[cfg(target_os = "linux")]
+        self.window_events.follow(&raw_windows);
+        collect_imhex_windows(raw_windows, &self.processes)
+    }
+
+    fn selection(&mut self) -> Option<Selection> {
+        get_selected_bytes()
+    }
+
+    fn imhex_focused(&mut self) -> Option<bool> {
+        imhex_has_focus(&self.processes)
+    }
+
+    fn idle_time(&mut self) -> Option<Duration> {
+        system_idle_time()
+    }
+
+    fn opened_file(&mut self, window: &ImhexWindow) -> Option<ResolvedFile> {
+        match &window.state.content {
+            OpenedContent::File(name) => self.resolve_name(name, window.pid),
+            _ => None,
+        }
+    }
+
+    fn project_file(&mut self, window: &ImhexWindow) -> Option<ResolvedFile> {
+        match &window.state.content {
+            OpenedContent::Project(name) => self.resolve_name(&project_file_name(name), window.pid),
+            _ => None,
+        }
+    }
+
+    fn recent_pattern(&mut self) -> Option<PathBuf> {
+        self.patterns.as_mut()?.recent_pattern(SystemTime::now())
+    }
+
+    fn find_pattern(&mut self, source: &str) -> Option<PathBuf> {
+        self.patterns.as_ref()?.find_source(source)
+    }
+
+    fn imhex_settings(&mut self) -> Option<ImhexSettings> {
+        self.config.as_ref().filter(|loaded| !loaded.config.dirs.is_empty()).map(|loaded| loaded.config.settings.clone())
+    }
+
+    #[cfg(target_os = "linux")]
+    fn wait_for_change(&mut self, timeout: Duration) -> bool {
+        self.window_events.wait(timeout)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn wait_for_change(&mut self, timeout: Duration) -> bool {
+        thread::sleep(timeout);
+        false
+    }
+
+    fn log_event(&mut self, message: &str) {
+        log_error(message);
     }
 }
@@ -0,0 +1,288 @@
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde_json::Value;
+use thiserror::Error;
+
+#[cfg(target_os = "linux")]
+use crate::process::{read_environ_var, read_executable};
+#[cfg(windows)]
+use crate::process::process_executable;
+use crate::resolver;
+
+pub const FLATPAK_APP_ID: &str = "net.werwolv.ImHex";
+pub const SETTINGS_FILE: &str = "settings.json";
+
+// Marker file ImHex looks for next to its executable to keep everything in that folder
+const PORTABLE_MARKERS: [&str; 2] = ["PORTABLE", "portable"];
+
+#[derive(Debug, Error)]
+pub enum ImhexConfigError {
+    #[error("Failed to read ImHex settings: {0}")]
+    Io(#[from] io::Error),
+    #[error("Invalid ImHex settings: {0}")]
+    Parse(#[from] serde_json::Error),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstallKind {
+    Portable,
+    AppImage,
+    Flatpak,
+    Installed,
+}
+
+impl fmt::Display for InstallKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InstallKind::Portable => write!(f, "portable"),
+            InstallKind::AppImage => write!(f, "AppImage"),
+            InstallKind::Flatpak => write!(f, "Flatpak"),
+            InstallKind::Installed => write!(f, "installed"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigDir {
+    pub path: PathBuf,
+    pub kind: InstallKind,
+}
+
+// Where to look for ImHex's directories
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiscoveryEnv {
+    pub home: Option<PathBuf>,
+    pub config_home: Option<PathBuf>,
+    pub data_home: Option<PathBuf>,
+    pub config_dirs: Vec<PathBuf>,
+    pub data_dirs: Vec<PathBuf>,
+    // Binary of the running ImHex process
+    pub executable: Option<PathBuf>,
+    // AppImage file ImHex was started from
+    pub appimage: Option<PathBuf>,
+}
+
+impl DiscoveryEnv {
+    // Builds the search locations from the XDG environment of the current user
+    #[cfg(target_os = "linux")]
+    pub fn from_env() -> Self {
+        let home = dirs::home_dir();
+        // The XDG spec says relative paths are invalid && must be ignored
+        let path_var = |name: &str| env::var_os(name).map(PathBuf::from).filter(|dir| dir.is_absolute());
+        let path_list = |name: &str, default: &str| {
+            let value = env::var(name).ok().filter(|value| !value.is_empty()).unwrap_or_else(|| default.to_string());
+            value.split(':').map(PathBuf::from).filter(|dir| dir.is_absolute()).collect()
+        };
+
+        Self {
+            config_home: path_var("XDG_CONFIG_HOME").or_else(|| home.as_ref().map(|home| home.join(".config"))),
+            data_home: path_var("XDG_DATA_HOME").or_else(|| home.as_ref().map(|home| home.join(".local/share"))),
+            config_dirs: path_list("XDG_CONFIG_DIRS", "/etc/xdg"),
+            data_dirs: path_list("XDG_DATA_DIRS", "/usr/local/share:/usr/share"),
+            home,
+            executable: None,
+            appimage: None,
+        }
+    }
+
+    // Builds the search locations from the platform's per-user directories
+    #[cfg(not(target_os = "linux"))]
+    pub fn from_env() -> Self {
+        Self {
+            home: dirs::home_dir(),
+            config_home: dirs::config_dir(),
+            data_home: dirs::data_local_dir(),
+            config_dirs: Vec::new(),
+            data_dirs: Vec::new(),
+            executable: None,
+            appimage: env::var_os("APPIMAGE").map(PathBuf::from),
+        }
+    }
+
+    // Adds the executable && AppImage of a running ImHex process
+    #[cfg(target_os = "linux")]
+    pub fn with_process(mut self, proc_root: &Path, pid: u32) -> Self {
+        self.executable = read_executable(proc_root, pid);
+        self.appimage = read_environ_var(proc_root, pid, "APPIMAGE").map(PathBuf::from);
+        self
+    }
+
+    // Adds the executable of a running ImHex process
+    #[cfg(windows)]
+    pub fn with_process(mut self, pid: u32) -> Self {
+        self.executable = process_executable(pid);
+        self
+    }
+}
+
+// Lists the ImHex config directories that exist, the one ImHex itself would use first
+pub fn discover_config_dirs(env: &DiscoveryEnv) -> Vec<ConfigDir> {
+    let mut candidates = Vec::new();
+
+    if let Some(exe_dir) = env.executable.as_deref().and_then(Path::parent) {
+        if PORTABLE_MARKERS.iter().any(|marker| exe_dir.join(marker).is_file()) {
+            candidates.push((exe_dir.to_path_buf(), InstallKind::Portable));
+        }
+    }
+
+    // Portable AppImages keep their XDG directories next to the image
+    if let Some(appimage) = &env.appimage {
+        let sibling = |suffix: &str| PathBuf::from(format!("{}{}", appimage.display(), suffix));
+        candidates.push((sibling(".config").join("imhex"), InstallKind::AppImage));
+        candidates.push((sibling(".home").join(".config/imhex"), InstallKind::AppImage));
+        candidates.push((sibling(".home").join(".local/share/imhex"), InstallKind::AppImage));
+    }
+
+    if let Some(home) = &env.home {
+        let flatpak = home.join(".var/app").join(FLATPAK_APP_ID);
+        candidates.push((flatpak.join("config/imhex"), InstallKind::Flatpak));
+        candidates.push((flatpak.join("data/imhex"), InstallKind::Flatpak));
+    }
+
+    let installed = env
+        .config_home
+        .iter()
+        .chain(env.data_home.iter())
+        .chain(env.config_dirs.iter())
+        .chain(env.data_dirs.iter());
+    candidates.extend(installed.map(|dir| (dir.join("imhex"), InstallKind::Installed)));
+
+    let mut found: Vec<ConfigDir> = Vec::new();
+    for (path, kind) in candidates {
+        if path.is_dir() && !found.iter().any(|dir| dir.path == path) {
+            found.push(ConfigDir { path, kind });
+        }
+    }
+    found
+}
+
+// The parts of ImHex's settings.json the presence cares about
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImhexSettings {
+    pub language: Option<String>,
+    pub theme: Option<String>,
+    pub workspace: Option<String>,
+    // Only written by older ImHex versions, newer ones keep a recent folder instead
+    pub recent_files: Vec<PathBuf>,
+}
+
+impl ImhexSettings {
+    // Settings are grouped by category, e.g. "hex.builtin.setting.interface" -> "hex.builtin.setting.interface.language"
+    pub fn parse(json: &str) -> Result<Self, ImhexConfigError> {
+        let root: Value = serde_json::from_str(json)?;
+        let mut settings = Self::default();
+
+        let entries = root.as_object().into_iter().flat_map(|categories| categories.values());
+        for (key, value) in entries.filter_map(Value::as_object).flatten() {
+            let name = key.rsplit('.').next().unwrap_or(key);
+            match (name, value) {
+                ("language", Value::String(language)) => settings.language = Some(language.clone()),
+                ("color" | "theme", Value::String(theme)) => settings.theme = Some(theme.clone()),
+                ("workspace" | "current_workspace", Value::String(workspace)) => {
+                    settings.workspace = Some(workspace.clone())
+                }
+                ("recent_files", Value::Array(files)) => {
+                    settings.recent_files = files.iter().filter_map(Value::as_str).map(PathBuf::from).collect()
+                }
+                _ => {}
+            }
+        }
+        Ok(settings)
+    }
+}
+
+// ImHex's directories && settings for one installation
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImhexConfig {
+    pub dirs: Vec<ConfigDir>,
+    pub settings: ImhexSettings,
+    settings_file: Option<(PathBuf, Option<SystemTime>)>,
+}
+
+impl ImhexConfig {
+    // Reads settings.json from the first directory that has one
+    pub fn load(dirs: Vec<ConfigDir>) -> Result<Self, ImhexConfigError> {
+        let settings_path = dirs
+            .iter()
+            .flat_map(|dir| [dir.path.join(SETTINGS_FILE), dir.path.join("config").join(SETTINGS_FILE)])
+            .find(|path| path.is_file());
+
+        let mut config = Self { dirs, settings: ImhexSettings::default(), settings_file: None };
+        if let Some(path) = settings_path {
+            config.read_settings(path)?;
+        }
+        Ok(config)
+    }
+
+    pub fn discover(env: &DiscoveryEnv) -> Result<Self, ImhexConfigError> {
+        Self::load(discover_config_dirs(env))
+    }
+
+    fn read_settings(&mut self, path: PathBuf) -> Result<(), ImhexConfigError> {
+        let modified = fs::metadata(&path)?.modified().ok();
+        self.settings = ImhexSettings::parse(&fs::read_to_string(&path)?)?;
+        self.settings_file = Some((path, modified));
+        Ok(())
+    }
+
+    // Rereads settings.json when ImHex saved it since the last load, returns true if it changed
+    pub fn refresh(&mut self) -> Result<bool, ImhexConfigError> {
+        let (path, modified) = match &self.settings_file {
+            Some(settings_file) => settings_file.clone(),
+            None => return Ok(false),
+        };
+        if fs::metadata(&path)?.modified().ok() == modified {
+            return Ok(false);
+        }
+        let previous = self.settings.clone();
+        self.read_settings(path)?;
+        Ok(self.settings != previous)
+    }
+
+    pub fn install_kind(&self) -> Option<InstallKind> {
+        self.dirs.first().map(|dir| dir.kind)
+    }
+
+    pub fn settings_path(&self) -> Option<&Path> {
+        self.settings_file.as_ref().map(|(path, _)| path.as_path())
+    }
+
+    pub fn dir_paths(&self) -> Vec<PathBuf> {
+        self.dirs.iter().map(|dir| dir.path.clone()).collect()
+    }
+
+    // Gets recently opened files, newest recent entries first && the settings list after them
+    pub fn recent_files(&self) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = Vec::new();
+        let recent = self.dirs.iter().flat_map(|dir| resolver::recent_files(&dir.path));
+        for file in recent.chain(self.settings.recent_files.iter().cloned()) {
+            if !files.contains(&file) {
+                files.push(file);
+            }
+        }
+        files
+    }
+}
+
+impl fmt::Display for ImhexConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let dir = match self.dirs.first() {
+            Some(dir) => dir,
+            None => return write!(f, "no config directory found"),
+        };
+        write!(f, "{} ({})", dir.path.display(), dir.kind)?;
+        let settings = &self.settings;
+        let values = [("language", &settings.language), ("theme", &settings.theme), ("workspace", &settings.workspace)];
+        for (name, value) in values {
+            if let Some(value) = value {
+                write!(f, ", {} {}", name, value)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,138 +1,88 @@
 #![windows_subsystem = "windows"]
 
+pub mod activity;
+pub mod binary;
+pub mod client;
+pub mod connection;
+pub mod discord;
+pub mod discovery;
+pub mod entropy;
+pub mod error;
+pub mod format;
 pub mod imhex;
+pub mod imhex_config;
+pub mod network;
+pub mod pattern;
+pub mod presence;
+pub mod privacy;
+pub mod probe;
+pub mod process;
+pub mod project;
+pub mod resolver;
+pub mod scheduler;
+pub mod settings;
+pub mod template;
+pub mod title;
 pub mod tray;
 pub mod utils;
 pub mod updater;
-
+pub mod window;
+#[cfg(target_os = "linux")]
+pub mod wayland;
+#[cfg(target_os = "linux")]
+pub mod x11;
+
+#[cfg(windows)]
+use winapi::um::wincon::{AttachConsole, ATTACH_PARENT_PROCESS};
+#[cfg(windows)]
 use winapi::um::winuser::SetProcessDPIAware;
-use discord_rich_presence::{activity::{Activity, Timestamps}, DiscordIpc, DiscordIpcClient};
+use client::DiscordClient;
+use connection::{BroadcastManager, ConnectionManager};
+use error::AppError;
+use imhex::SystemProbe;
+use presence::{ActivitySink, AppState};
+use scheduler::RateLimitedSink;
+use settings::{Settings, SETTINGS_FILE_NAME};
+use template::Templates;
+use tray::TrayTooltip;
 use log::{error, info};
 use std::fs::{self, OpenOptions};
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::thread;
+use std::sync::atomic::Ordering;
 use std::time::Duration;
 use chrono::Local;
 use tokio::runtime::Runtime;
 
 const CLIENT_ID: &str = "1060827018196955177";
 const UPDATE_INTERVAL: Duration = Duration::from_millis(100);
-const RECONNECT_DELAY: Duration = Duration::from_secs(5);
-
-#[derive(Debug)]
-pub enum AppError {
-    Discord(String),
-    Filesystem(std::io::Error),
-    Configuration(String),
-}
-
-impl std::fmt::Display for AppError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            AppError::Discord(msg) => write!(f, "Discord error: {}", msg),
-            AppError::Filesystem(err) => write!(f, "Filesystem error: {}", err),
-            AppError::Configuration(msg) => write!(f, "Configuration error: {}", msg),
-        }
-    }
-}
-
-impl std::error::Error for AppError {}
-
-impl From<std::io::Error> for AppError {
-    fn from(err: std::io::Error) -> Self {
-        AppError::Filesystem(err)
-    }
-}
-
-#[derive(Debug, Eq, PartialEq)]
-struct ActivityState {
-    state: String,
-    details: String,
-}
 
 struct Config {
     client_id: String,
     log_dir: PathBuf,
     update_interval: Duration,
+    settings: Settings,
 }
 
 impl Config {
     fn new() -> Result<Self, AppError> {
         let home_dir = std::env::var("USERPROFILE")
+            .or_else(|_| std::env::var("HOME"))
             .map_err(|_| AppError::Configuration("Failed to get user profile".to_string()))?;
+        let log_dir = PathBuf::from(home_dir).join(".discord-imhex");
+        let settings = Settings::load(&log_dir.join(SETTINGS_FILE_NAME))
+            .map_err(|e| AppError::Configuration(e.to_string()))?;
         
         Ok(Config {
             client_id: CLIENT_ID.to_string(),
-            log_dir: PathBuf::from(home_dir).join(".discord-imhex"),
+            log_dir,
             update_interval: UPDATE_INTERVAL,
+            settings,
         })
     }
 }
 
-pub struct DiscordClient {
-    client: DiscordIpcClient,
-    last_activity: Option<ActivityState>,
-}
-
-impl DiscordClient {
-    pub fn new(client_id: &str) -> Result<Self, AppError> {
-        let mut client = DiscordIpcClient::new(client_id)
-            .map_err(|e| AppError::Discord(e.to_string()))?;
-        client.connect()
-            .map_err(|e| AppError::Discord(e.to_string()))?;
-        
-        Ok(Self { 
-            client,
-            last_activity: None,
-        })
-    }
-
-    pub fn update_activity(&mut self, state: String, details: String, timestamps: Timestamps) -> Result<(), AppError> {
-        let new_activity = ActivityState {
-            state: state.clone(),
-            details: details.clone(),
-        };
-
-        if Some(&new_activity) != self.last_activity.as_ref() {
-            let activity = Activity::new()
-                .state(&state)
-                .details(&details)
-                .timestamps(timestamps);
-
-            self.client.set_activity(activity)
-                .map_err(|e| AppError::Discord(e.to_string()))?;
-            self.last_activity = Some(new_activity);
-        }
-        Ok(())
-    }
-
-    pub fn clear_activity(&mut self) -> Result<(), AppError> {
-        self.client.clear_activity()
-            .map_err(|e| AppError::Discord(e.to_string()))?;
-        self.last_activity = None;
-        Ok(())
-    }
-}
-
-struct AppState {
-    running: Arc<AtomicBool>,
-    start_time: Option<i64>,
-    imhex_running: bool,
-}
-
-impl AppState {
-    fn new() -> Self {
-        Self {
-            running: Arc::new(AtomicBool::new(true)),
-            start_time: None,
-            imhex_running: false,
-        }
-    }
-}
-
 fn setup_logging(log_dir: &Path) -> Result<(), AppError> {
     if !log_dir.exists() {
         fs::create_dir(log_dir)?;
@@ -151,70 +101,55 @@ fn setup_logging(log_dir: &Path) -> Result<(), AppError> {
     Ok(())
 }
 
-fn create_timestamps(start_time: i64) -> Timestamps {
-    Timestamps::new().start(start_time)
-}
-
-fn handle_imhex_running(client: &mut DiscordClient, state: &mut AppState) -> Result<(), AppError> {
-    let current_time = utils::get_current_timestamp();
-
-    if !state.imhex_running {
-        state.start_time = Some(current_time);
-        state.imhex_running = true;
-    }
-
-    if let Some(current_opened_file) = imhex::check_if_imhex_window_exists() {
-        let timestamps = create_timestamps(state.start_time.unwrap());
-        let selected_bytes = imhex::get_selected_bytes().unwrap_or_else(|| "None".to_string());
-        let activity_state = format!("Bytes: [{}]", selected_bytes);
-        let details = if current_opened_file == "ImHex" {
-            "Idle".to_string()
-        } else {
-            format!("Analyzing: [{}]", current_opened_file)
-        };
-
-        client.update_activity(activity_state, details, timestamps)?;
-    } else {
-        client.update_activity("".to_string(), "Idle".to_string(), Timestamps::default())?;
-    }
-
+// Prints every state's lines rendered with sample values, config errors surface as usual
+fn preview_templates(settings: &Settings) -> Result<(), AppError> {
+    let templates = Templates::from_settings(settings)
+        .map_err(|(name, e)| AppError::Configuration(format!("template {}: {}", name, e)))?;
+    print!("{}", template::preview(&templates));
     Ok(())
 }
 
-fn handle_imhex_not_running(client: &mut DiscordClient, state: &mut AppState) -> Result<(), AppError> {
-    if state.imhex_running {
-        state.imhex_running = false;
-        state.start_time = None;
-        client.clear_activity()?;
+fn show_connection_state(tooltip: &TrayTooltip, text: &str) {
+    if let Err(e) = tray::show_connection_state(tooltip, text) {
+        error!("Failed to update the tray: {}", e);
     }
-    Ok(())
 }
 
-fn run_discord_loop(client: &mut DiscordClient, state: &mut AppState, config: &Config) -> Result<(), AppError> {
+// Runs the presence loop until the app exits, through the rate limiter
+fn run_presence<S: ActivitySink>(connection: S, state: &mut AppState, update_interval: Duration) {
+    let mut client = RateLimitedSink::new(connection);
     while state.running.load(Ordering::SeqCst) {
-        if imhex::is_imhex_running() {
-            handle_imhex_running(client, state)?;
-        } else {
-            handle_imhex_not_running(client, state)?;
+        if let Err(e) = presence::run_discord_loop(&mut SystemProbe::default(), &mut client, state, update_interval) {
+            error!("Error in Discord loop: {}", e);
         }
-        thread::sleep(config.update_interval);
     }
-    client.clear_activity()?;
-    Ok(())
 }
 
 fn main() -> Result<(), AppError> {
+    #[cfg(windows)]
     unsafe {
         SetProcessDPIAware();
     }
     
+    let preview = std::env::args().any(|arg| arg == "--preview-templates");
+    // The windows subsystem starts without a console, so the preview && config errors go to the one of the shell that ran us
+    #[cfg(windows)]
+    if preview {
+        unsafe {
+            AttachConsole(ATTACH_PARENT_PROCESS);
+        }
+    }
+
     let config = Config::new()?;
+    if preview {
+        return preview_templates(&config.settings);
+    }
     setup_logging(&config.log_dir)?;
     
-    let mut state = AppState::new();
+    let mut state = AppState::with_settings(config.settings.clone());
     let running_clone = Arc::clone(&state.running);
 
-    let _tray_icon = tray::create_tray_icon(&running_clone)
+    let tooltip = tray::create_tray_icon(&running_clone)
         .map_err(|e| AppError::Configuration(e.to_string()))?;
 
     let rt = Runtime::new()
@@ -223,18 +158,23 @@ fn main() -> Result<(), AppError> {
 
     info!("Application started successfully");
 
-    while state.running.load(Ordering::SeqCst) {
-        match DiscordClient::new(&config.client_id) {
-            Ok(mut client) => {
-                if let Err(e) = run_discord_loop(&mut client, &mut state, &config) {
-                    error!("Error in Discord loop: {}", e);
-                }
-            }
-            Err(e) => {
-                error!("Failed to connect to Discord client: {}", e);
-                thread::sleep(RECONNECT_DELAY);
-            }
-        }
+    let client_id = config.client_id.clone();
+    if config.settings.broadcast {
+        info!("Broadcasting the presence to every running Discord client");
+        let connection = BroadcastManager::new(discovery::ipc_endpoints, move |path| {
+            let (path, client_id) = (path.to_path_buf(), client_id.clone());
+            ConnectionManager::new(move || DiscordClient::connect_to(&path, &client_id))
+        })
+        .with_observer(move |states| show_connection_state(&tooltip, &connection::describe_states(states)));
+        run_presence(connection, &mut state, config.update_interval);
+    } else {
+        let connection = ConnectionManager::new(move || DiscordClient::new(&client_id))
+            .with_detector(|| {
+                let scan = discovery::scan_discord();
+                (!scan.is_empty()).then(|| scan.to_string())
+            })
+            .with_observer(move |connection_state| show_connection_state(&tooltip, &connection_state.to_string()));
+        run_presence(connection, &mut state, config.update_interval);
     }
 
     info!("Application shutting down");
@@ -0,0 +1,157 @@
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use thiserror::Error;
+
+// ImHex listens here once "Network Interface" is enabled in its settings
+pub const DEFAULT_ADDRESS: &str = "127.0.0.1:31337";
+pub const SELECTION_ENDPOINT: &str = "hex_editor/selection";
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_millis(250);
+const MAX_PREVIEW_LEN: usize = 16;
+
+#[derive(Debug, Error)]
+pub enum NetworkError {
+    #[error("ImHex network interface unreachable: {0}")]
+    Io(#[from] io::Error),
+    #[error("Malformed response from ImHex: {0}")]
+    Protocol(String),
+    #[error("ImHex endpoint error: {0}")]
+    Endpoint(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selection {
+    pub offset: u64,
+    pub length: u64,
+    // First few selected bytes, empty when not requested
+    pub preview: Vec<u8>,
+}
+
+impl Selection {
+    // Gets the offset of the last selected byte
+    pub fn end(&self) -> u64 {
+        self.offset + self.length.saturating_sub(1)
+    }
+}
+
+impl fmt::Display for Selection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unit = if self.length == 1 { "byte" } else { "bytes" };
+        write!(f, "0x{:X}-0x{:X} ({} {})", self.offset, self.end(), self.length, unit)?;
+        for byte in &self.preview {
+            write!(f, " {:02X}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+struct Response {
+    status: String,
+    #[serde(default)]
+    data: Value,
+}
+
+#[derive(Deserialize)]
+struct SelectionData {
+    offset: u64,
+    size: u64,
+    #[serde(default)]
+    bytes: Option<String>,
+}
+
+pub struct NetworkClient {
+    address: SocketAddr,
+    timeout: Duration,
+    preview_len: usize,
+}
+
+impl NetworkClient {
+    pub fn new(address: SocketAddr) -> Self {
+        Self { address, timeout: DEFAULT_TIMEOUT, preview_len: 0 }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    // Asks ImHex for up to `preview_len` selected bytes alongside the range
+    pub fn with_preview_len(mut self, preview_len: usize) -> Self {
+        self.preview_len = preview_len.min(MAX_PREVIEW_LEN);
+        self
+    }
+
+    // Queries the current selection, None when nothing is selected
+    pub fn query_selection(&self) -> Result<Option<Selection>, NetworkError> {
+        let data = self.call(SELECTION_ENDPOINT, json!({ "preview": self.preview_len }))?;
+        if data.is_null() || data.as_object().is_some_and(|object| object.is_empty()) {
+            return Ok(None);
+        }
+
+        let data: SelectionData =
+            serde_json::from_value(data).map_err(|e| NetworkError::Protocol(e.to_string()))?;
+        if data.size == 0 {
+            return Ok(None);
+        }
+
+        let mut preview = match data.bytes {
+            Some(bytes) => decode_hex(&bytes)?,
+            None => Vec::new(),
+        };
+        preview.truncate(self.preview_len);
+
+        Ok(Some(Selection { offset: data.offset, length: data.size, preview }))
+    }
+
+    // Sends a single null-terminated JSON request && reads the reply
+    fn call(&self, endpoint: &str, data: Value) -> Result<Value, NetworkError> {
+        let mut stream = TcpStream::connect_timeout(&self.address, self.timeout)?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+
+        let request = json!({ "endpoint": endpoint, "data": data });
+        stream.write_all(request.to_string().as_bytes())?;
+        stream.write_all(&[0])?;
+        stream.flush()?;
+
+        let mut reply = Vec::new();
+        BufReader::new(stream).read_until(0, &mut reply)?;
+        if reply.last() == Some(&0) {
+            reply.pop();
+        }
+
+        let response: Response =
+            serde_json::from_slice(&reply).map_err(|e| NetworkError::Protocol(e.to_string()))?;
+        if response.status != "success" {
+            let message = response.data["error"].as_str().unwrap_or(&response.status).to_string();
+            return Err(NetworkError::Endpoint(message));
+        }
+        Ok(response.data)
+    }
+}
+
+impl Default for NetworkClient {
+    fn default() -> Self {
+        Self::new(DEFAULT_ADDRESS.parse().expect("Invalid default ImHex address"))
+    }
+}
+
+fn decode_hex(hex: &str) -> Result<Vec<u8>, NetworkError> {
+    let hex: String = hex.chars().filter(|c| !c.is_whitespace()).collect();
+    if !hex.is_ascii() || !hex.len().is_multiple_of(2) {
+        return Err(NetworkError::Protocol(format!("invalid byte string '{}'", hex)));
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&hex[i..i + 2], 16)
+                .map_err(|_| NetworkError::Protocol(format!("invalid byte string '{}'", hex)))
+        })
+        .collect()
+}
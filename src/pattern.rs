@@ -0,0 +1,282 @@
+use std::cmp::Reverse;
+#[cfg(target_os = "linux")]
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+#[cfg(target_os = "linux")]
+use inotify::{EventMask, Inotify, WatchDescriptor, WatchMask};
+
+use crate::project::ProjectSummary;
+
+// Folder inside the ImHex config directory holding the user's patterns
+pub const PATTERNS_DIR: &str = "patterns";
+pub const PATTERN_EXTENSION: &str = "hexpat";
+// Name of the pattern source inside a .hexproj archive
+pub const PROJECT_PATTERN_NAME: &str = "pattern_source.hexpat";
+
+// A pattern saved within this long counts as being written
+pub const DEFAULT_RECENT_WINDOW: Duration = Duration::from_secs(10 * 60);
+
+// Shortest time between two rescans, a walk of the patterns directories on every update would be too costly
+pub const DEFAULT_RESCAN_INTERVAL: Duration = Duration::from_secs(5);
+
+// How deep pattern folders are searched, ImHex nests them by topic
+const MAX_SCAN_DEPTH: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatternSource {
+    // Recently saved to one of ImHex's patterns directories
+    Saved,
+    // Attached to the open project
+    Project,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActivePattern {
+    pub name: String,
+    pub path: Option<PathBuf>,
+    pub source: PatternSource,
+}
+
+impl ActivePattern {
+    pub fn saved(path: &Path) -> Self {
+        Self { name: file_name(path), path: Some(path.to_path_buf()), source: PatternSource::Saved }
+    }
+
+    // Names the project's pattern after the saved file with the same source, if there is one
+    pub fn from_project(project: &ProjectSummary, saved_as: Option<PathBuf>) -> Option<Self> {
+        project.pattern.as_ref()?;
+        let name = saved_as.as_deref().map_or_else(|| PROJECT_PATTERN_NAME.to_string(), file_name);
+        Some(Self { name, path: saved_as, source: PatternSource::Project })
+    }
+}
+
+impl fmt::Display for ActivePattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Writing pattern: {}", self.name)
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().to_string())
+}
+
+pub fn is_pattern_file(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == PATTERN_EXTENSION)
+}
+
+// Lists the .hexpat files below a directory with their modification times
+pub fn find_patterns(dir: &Path) -> Vec<(PathBuf, SystemTime)> {
+    let mut patterns = Vec::new();
+    let mut pending = vec![(dir.to_path_buf(), 0)];
+    while let Some((dir, depth)) = pending.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        for entry in entries.filter_map(Result::ok) {
+            let path = entry.path();
+            let metadata = match entry.metadata() {
+                Ok(metadata) => metadata,
+                Err(_) => continue,
+            };
+            if metadata.is_dir() && depth < MAX_SCAN_DEPTH {
+                pending.push((path, depth + 1));
+            } else if metadata.is_file() && is_pattern_file(&path) {
+                if let Ok(modified) = metadata.modified() {
+                    patterns.push((path, modified));
+                }
+            }
+        }
+    }
+    patterns
+}
+
+// Inotify watches on the patterns directories && their subfolders
+#[cfg(target_os = "linux")]
+struct PatternNotifier {
+    inotify: Inotify,
+    dirs: HashMap<WatchDescriptor, PathBuf>,
+    buffer: Vec<u8>,
+}
+
+#[cfg(target_os = "linux")]
+impl PatternNotifier {
+    fn new(dirs: &[PathBuf]) -> Option<Self> {
+        let mut notifier = Self { inotify: Inotify::init().ok()?, dirs: HashMap::new(), buffer: vec![0; 4096] };
+        for dir in dirs {
+            notifier.watch_tree(dir, 0);
+        }
+        (!notifier.dirs.is_empty()).then_some(notifier)
+    }
+
+    fn watch_tree(&mut self, dir: &Path, depth: usize) {
+        let mask = WatchMask::CLOSE_WRITE | WatchMask::MOVED_TO | WatchMask::CREATE;
+        if let Ok(wd) = self.inotify.watches().add(dir, mask) {
+            self.dirs.insert(wd, dir.to_path_buf());
+        }
+        if depth >= MAX_SCAN_DEPTH {
+            return;
+        }
+        let subdirs = fs::read_dir(dir).into_iter().flatten().filter_map(Result::ok).map(|entry| entry.path());
+        for subdir in subdirs.filter(|path| path.is_dir()).collect::<Vec<_>>() {
+            self.watch_tree(&subdir, depth + 1);
+        }
+    }
+
+    // Drains pending events, returning the patterns written since the last call
+    fn saved_patterns(&mut self) -> Vec<PathBuf> {
+        let mut saved = Vec::new();
+        let mut new_dirs = Vec::new();
+        while let Ok(events) = self.inotify.read_events(&mut self.buffer) {
+            let mut empty = true;
+            for event in events {
+                empty = false;
+                let path = match (self.dirs.get(&event.wd), event.name) {
+                    (Some(dir), Some(name)) => dir.join(name),
+                    _ => continue,
+                };
+                if event.mask.contains(EventMask::ISDIR) {
+                    new_dirs.push(path);
+                } else if !event.mask.contains(EventMask::CREATE) && is_pattern_file(&path) {
+                    saved.push(path);
+                }
+            }
+            if empty {
+                break;
+            }
+        }
+        for dir in new_dirs {
+            self.watch_tree(&dir, 1);
+        }
+        saved
+    }
+}
+
+// Follows ImHex's patterns directories for recently saved .hexpat files
+pub struct PatternWatcher {
+    dirs: Vec<PathBuf>,
+    recent_window: Duration,
+    rescan_interval: Duration,
+    last_saved: Option<(PathBuf, SystemTime)>,
+    last_scan: Option<SystemTime>,
+    #[cfg(target_os = "linux")]
+    notifier: Option<PatternNotifier>,
+}
+
+impl PatternWatcher {
+    // Watches the given patterns directories, with inotify where available && rescans otherwise
+    pub fn new(dirs: Vec<PathBuf>) -> Self {
+        let dirs: Vec<PathBuf> = dirs.into_iter().filter(|dir| dir.is_dir()).collect();
+        let mut watcher = Self {
+            #[cfg(target_os = "linux")]
+            notifier: PatternNotifier::new(&dirs),
+            dirs,
+            recent_window: DEFAULT_RECENT_WINDOW,
+            rescan_interval: DEFAULT_RESCAN_INTERVAL,
+            last_saved: None,
+            last_scan: None,
+        };
+        watcher.rescan();
+        watcher
+    }
+
+    // Gets the patterns directories of every ImHex config directory
+    pub fn for_config_dirs(config_dirs: &[PathBuf]) -> Self {
+        Self::new(config_dirs.iter().map(|dir| dir.join(PATTERNS_DIR)).collect())
+    }
+
+    pub fn with_recent_window(mut self, recent_window: Duration) -> Self {
+        self.recent_window = recent_window;
+        self
+    }
+
+    pub fn with_rescan_interval(mut self, rescan_interval: Duration) -> Self {
+        self.rescan_interval = rescan_interval;
+        self
+    }
+
+    // Rescans instead of following inotify, as where it can't be set up
+    #[cfg(target_os = "linux")]
+    pub fn without_inotify(mut self) -> Self {
+        self.notifier = None;
+        self
+    }
+
+    pub fn dirs(&self) -> &[PathBuf] {
+        &self.dirs
+    }
+
+    // Checks if saves are reported by inotify rather than found by rescanning
+    #[cfg(target_os = "linux")]
+    pub fn uses_inotify(&self) -> bool {
+        self.notifier.is_some()
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn uses_inotify(&self) -> bool {
+        false
+    }
+
+    // Gets the pattern saved most recently, if that was within the recent window
+    pub fn recent_pattern(&mut self, now: SystemTime) -> Option<PathBuf> {
+        self.poll(now);
+        let (path, saved_at) = self.last_saved.as_ref()?;
+        let age = now.duration_since(*saved_at).unwrap_or_default();
+        (age <= self.recent_window).then(|| path.clone())
+    }
+
+    // Finds the saved pattern whose source matches, e.g. the one attached to a project
+    pub fn find_source(&self, source: &str) -> Option<PathBuf> {
+        let source = source.trim();
+        let mut patterns: Vec<(PathBuf, SystemTime)> = self.dirs.iter().flat_map(|dir| find_patterns(dir)).collect();
+        patterns.sort_by_key(|(_, modified)| Reverse(*modified));
+        patterns
+            .into_iter()
+            .map(|(path, _)| path)
+            .find(|path| fs::read_to_string(path).is_ok_and(|contents| contents.trim() == source))
+    }
+
+    #[cfg(target_os = "linux")]
+    fn poll(&mut self, now: SystemTime) {
+        let saved = match self.notifier.as_mut() {
+            Some(notifier) => notifier.saved_patterns().pop(),
+            None => {
+                self.throttled_rescan(now);
+                return;
+            }
+        };
+        if let Some(path) = saved {
+            let saved_at = fs::metadata(&path).and_then(|metadata| metadata.modified()).unwrap_or(now);
+            self.last_saved = Some((path, saved_at));
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn poll(&mut self, now: SystemTime) {
+        self.throttled_rescan(now);
+    }
+
+    // Rescans unless the last scan is less than the rescan interval ago, keeping what that one found
+    fn throttled_rescan(&mut self, now: SystemTime) {
+        let interval = self.rescan_interval;
+        // A clock that went back counts as due too
+        let due = self.last_scan.is_none_or(|last_scan| now.duration_since(last_scan).map_or(true, |elapsed| elapsed >= interval));
+        if due {
+            self.rescan();
+            self.last_scan = Some(now);
+        }
+    }
+
+    fn rescan(&mut self) {
+        let newest = self.dirs.iter().flat_map(|dir| find_patterns(dir)).max_by_key(|(_, modified)| *modified);
+        if let Some(newest) = newest {
+            if self.last_saved.as_ref().is_none_or(|(_, saved_at)| newest.1 > *saved_at) {
+                self.last_saved = Some(newest);
+            }
+        }
+    }
+}
@@ -0,0 +1,366 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::activity::{ActivityDetector, UserActivity};
+use crate::entropy::EntropyAnalyzer;
+use crate::error::AppError;
+use crate::format::{FileFormat, FormatClassifier};
+use crate::imhex_config::ImhexSettings;
+use crate::pattern::{ActivePattern, PatternSource};
+use crate::privacy::PrivacyFilter;
+use crate::probe::ImhexProbe;
+use crate::process::ImhexProcess;
+use crate::project::{ProjectReader, ProjectSummary};
+use crate::resolver::ResolvedFile;
+use crate::settings::{ImageSettings, Settings};
+use crate::template::{PresenceState, Template, Templates};
+use crate::utils;
+
+// Discord asset shown as the large or small image, with its hover text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActivityImage {
+    pub key: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActivityButton {
+    pub label: String,
+    pub url: String,
+}
+
+// Images && buttons shown next to the activity text
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ActivityAssets {
+    pub large_image: Option<ActivityImage>,
+    pub small_image: Option<ActivityImage>,
+    pub buttons: Vec<ActivityButton>,
+}
+
+// Everything one update_activity call sends, kept by sinks that send it later or again
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Activity {
+    pub state: String,
+    pub details: String,
+    pub start_time: Option<i64>,
+    pub assets: ActivityAssets,
+}
+
+impl Activity {
+    pub fn send_to<S: ActivitySink + ?Sized>(&self, sink: &mut S) -> Result<(), AppError> {
+        sink.update_activity(self.state.clone(), self.details.clone(), self.start_time, self.assets.clone())
+    }
+}
+
+// Receives the activity computed by the presence loop
+pub trait ActivitySink {
+    fn update_activity(&mut self, state: String, details: String, start_time: Option<i64>, assets: ActivityAssets) -> Result<(), AppError>;
+
+    fn clear_activity(&mut self) -> Result<(), AppError>;
+
+    // Called once per loop iteration, for sinks with work of their own like retries
+    fn poll(&mut self) -> Result<(), AppError> {
+        Ok(())
+    }
+}
+
+pub struct AppState {
+    pub running: Arc<AtomicBool>,
+    pub start_time: Option<i64>,
+    pub imhex_running: bool,
+    // File behind the primary window, when it could be found on disk
+    pub opened_file: Option<ResolvedFile>,
+    pub formats: FormatClassifier,
+    // Path && mtime of the last file whose format went to the session log
+    pub logged_file: Option<(PathBuf, Option<SystemTime>)>,
+    // Background entropy profile of the opened file
+    pub entropy: EntropyAnalyzer,
+    pub settings: Settings,
+    pub activity: ActivityDetector,
+    pub projects: ProjectReader,
+    // Summary of the project behind the primary window
+    pub project: Option<ProjectSummary>,
+    // Pattern attached to the open project, looked up once per project
+    pub project_pattern: Option<ActivePattern>,
+    // Pattern being written, either recently saved or attached to the project
+    pub pattern: Option<ActivePattern>,
+    // Language, theme && workspace of the running ImHex
+    pub imhex_settings: Option<ImhexSettings>,
+    // Applied to every name before it goes to the sink
+    pub privacy: PrivacyFilter,
+    // Details && state lines, image texts && buttons
+    pub templates: Templates,
+}
+
+impl AppState {
+    pub fn new() -> Self {
+        Self::with_settings(Settings::default())
+    }
+
+    pub fn with_settings(settings: Settings) -> Self {
+        Self {
+            running: Arc::new(AtomicBool::new(true)),
+            start_time: None,
+            imhex_running: false,
+            opened_file: None,
+            formats: FormatClassifier::new(),
+            logged_file: None,
+            entropy: EntropyAnalyzer::new(),
+            activity: ActivityDetector::new(settings.away_timeout()),
+            // Settings are validated when loaded, broken rules from elsewhere hide every name
+            privacy: PrivacyFilter::new(&settings.privacy).unwrap_or_else(|_| PrivacyFilter::generic(&settings.privacy)),
+            templates: Templates::from_settings(&settings).unwrap_or_default(),
+            settings,
+            projects: ProjectReader::new(),
+            project: None,
+            project_pattern: None,
+            pattern: None,
+            imhex_settings: None,
+        }
+    }
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn handle_imhex_running<P: ImhexProbe, S: ActivitySink>(probe: &mut P, sink: &mut S, state: &mut AppState, process: &ImhexProcess) -> Result<(), AppError> {
+    if !state.imhex_running {
+        state.start_time = Some(process.start_time.unwrap_or_else(utils::get_current_timestamp));
+        state.imhex_running = true;
+    }
+    state.imhex_settings = probe.imhex_settings();
+
+    let activity = state.activity.update(probe.imhex_focused(), probe.idle_time(), Instant::now());
+    if activity != UserActivity::Active {
+        let line = if activity == UserActivity::Paused { PresenceState::Paused } else { PresenceState::Away };
+        let values = placeholders(state, &ShownNames::default(), None);
+        return send_line(sink, state, line, None, &values, state.start_time);
+    }
+
+    let windows = probe.windows();
+    if let Some(window) = windows.primary() {
+        state.opened_file = probe.opened_file(window);
+        if state.entropy.update(state.opened_file.as_ref()) {
+            if let (Some(file), Some(profile)) = (&state.opened_file, state.entropy.profile()) {
+                probe.log_event(&format!("Entropy of {}: {}", file.path.display(), profile));
+            }
+        }
+        let format = state.opened_file.as_ref().and_then(|file| state.formats.classify(file));
+        if let (Some(file), Some(format)) = (&state.opened_file, &format) {
+            log_file_format(probe, &mut state.logged_file, file, format);
+        }
+        let project_file = probe.project_file(window);
+        let project = project_file.as_ref().and_then(|file| state.projects.read(file));
+        if project != state.project {
+            state.project_pattern = project.as_ref().and_then(|project| {
+                let saved_as = project.pattern.as_ref().and_then(|source| probe.find_pattern(source));
+                ActivePattern::from_project(project, saved_as)
+            });
+            if let Some(project) = &project {
+                log_project(probe, project);
+            }
+        }
+        state.project = project;
+        update_pattern(probe, state);
+        let shown = ShownNames::new(state, window.state.display_name(), project_file.as_ref());
+        let open_count = windows.open_names().len();
+
+        let mut values = placeholders(state, &shown, format.as_ref());
+        values.extend([
+            ("open_files", if open_count > 1 { open_count.to_string() } else { String::new() }),
+            ("selection", probe.selection().map(|selection| selection.to_string()).unwrap_or_default()),
+        ]);
+        let analyzing = shown.pattern.iter().any(|pattern| pattern.source == PatternSource::Saved) || shown.project.is_some() || shown.file.is_some();
+        let line = if analyzing { PresenceState::Analyzing } else { PresenceState::Idle };
+        send_line(sink, state, line, format.as_ref(), &values, state.start_time)?;
+    } else {
+        state.opened_file = None;
+        state.project = None;
+        state.project_pattern = None;
+        state.pattern = None;
+        state.entropy.update(None);
+        let values = placeholders(state, &ShownNames::default(), None);
+        send_line(sink, state, PresenceState::Idle, None, &values, None)?;
+    }
+
+    Ok(())
+}
+
+// Renders the templates for a state && sends them with the images && buttons
+fn send_line<S: ActivitySink>(
+    sink: &mut S,
+    state: &AppState,
+    line: PresenceState,
+    format: Option<&FileFormat>,
+    values: &[(&str, String)],
+    start_time: Option<i64>,
+) -> Result<(), AppError> {
+    let (details, activity_state) = state.templates.line(line).render(values);
+    let assets = activity_assets(&state.settings, &state.templates, format, values);
+    sink.update_activity(activity_state, details, start_time, assets)
+}
+
+// File, project && pattern names as the privacy rules allow showing them
+#[derive(Default)]
+struct ShownNames {
+    file: Option<String>,
+    project: Option<ProjectSummary>,
+    pattern: Option<ActivePattern>,
+}
+
+impl ShownNames {
+    fn new(state: &AppState, file_name: Option<&str>, project_file: Option<&ResolvedFile>) -> Self {
+        let privacy = &state.privacy;
+        let file_path = state.opened_file.as_ref().map(|file| file.path.as_path());
+        let project = state.project.as_ref().map(|project| ProjectSummary {
+            name: privacy.label(&project.name, project_file.map(|file| file.path.as_path())),
+            ..project.clone()
+        });
+        let pattern = state.pattern.as_ref().map(|pattern| ActivePattern {
+            name: privacy.label(&pattern.name, pattern.path.as_deref()),
+            ..pattern.clone()
+        });
+        Self { file: file_name.map(|name| privacy.label(name, file_path)), project, pattern }
+    }
+}
+
+// "1 file", "3 files"
+fn count(count: usize, noun: &str) -> String {
+    format!("{} {}{}", count, noun, if count == 1 { "" } else { "s" })
+}
+
+// Values for the placeholders known from the state, empty when unknown
+fn placeholders(state: &AppState, shown: &ShownNames, format: Option<&FileFormat>) -> Vec<(&'static str, String)> {
+    let settings = state.imhex_settings.as_ref();
+    let setting = |value: Option<&Option<String>>| value.and_then(Option::clone).unwrap_or_default();
+    // The extension of the shown name, so a hidden name doesn't leak through it
+    let ext = shown.file.as_deref().and_then(|file| Path::new(file).extension()).map(|ext| ext.to_string_lossy().into_owned());
+    let size = state.opened_file.as_ref().filter(|_| shown.file.is_some()).map(|file| utils::format_size(file.size));
+    // Only a .hexpat saved a moment ago counts as writing, a project's attached pattern doesn't
+    let writing = shown.pattern.as_ref().filter(|pattern| pattern.source == PatternSource::Saved).map(|pattern| pattern.name.clone());
+    let elapsed = state.start_time.map(|start| utils::format_elapsed((utils::get_current_timestamp() - start).max(0) as u64));
+    // Stays empty until the background profile of the file is done
+    let entropy = state.entropy.profile().filter(|_| shown.file.is_some()).map(|profile| profile.verdict().to_string());
+    vec![
+        ("file", shown.file.clone().unwrap_or_default()),
+        ("ext", ext.unwrap_or_default()),
+        ("size", size.unwrap_or_default()),
+        ("format", format.map(|format| format.description.clone()).unwrap_or_default()),
+        ("entropy", entropy.unwrap_or_default()),
+        ("elapsed", elapsed.unwrap_or_default()),
+        ("project", shown.project.as_ref().map(|project| project.name.clone()).unwrap_or_default()),
+        ("project_files", shown.project.as_ref().map(|project| count(project.providers.len(), "file")).unwrap_or_default()),
+        ("bookmarks", shown.project.as_ref().map(|project| count(project.bookmarks, "bookmark")).unwrap_or_default()),
+        ("pattern", shown.pattern.as_ref().map(|pattern| pattern.name.clone()).unwrap_or_default()),
+        ("writing", writing.unwrap_or_default()),
+        ("language", setting(settings.map(|settings| &settings.language))),
+        ("theme", setting(settings.map(|settings| &settings.theme))),
+        ("workspace", setting(settings.map(|settings| &settings.workspace))),
+    ]
+}
+
+// Encodes everything but RFC 3986 unreserved characters
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+fn configured_image(image: &ImageSettings, text: &Template, values: &[(&str, String)]) -> Option<ActivityImage> {
+    (!image.key.is_empty()).then(|| ActivityImage { key: image.key.clone(), text: text.render(values) })
+}
+
+// Uses the format's icon as the large image when there is one, the configured images otherwise
+fn activity_assets(settings: &Settings, templates: &Templates, format: Option<&FileFormat>, values: &[(&str, String)]) -> ActivityAssets {
+    let large_image = match format {
+        Some(format) => Some(ActivityImage {
+            key: format.category.image_key().to_string(),
+            text: match settings.large_image.text.as_str() {
+                "" => format.binary.as_ref().map_or_else(|| format.description.clone(), |binary| binary.to_string()),
+                _ => templates.large_text.render(values),
+            },
+        }),
+        None => configured_image(&settings.large_image, &templates.large_text, values),
+    };
+    let buttons = templates
+        .buttons
+        .iter()
+        .map(|(label, url)| ActivityButton { label: label.render(values), url: url.render_with(values, percent_encode) })
+        .collect();
+    ActivityAssets { large_image, small_image: configured_image(&settings.small_image, &templates.small_text, values), buttons }
+}
+
+// Logs the analyzed file's format once per file version
+fn log_file_format<P: ImhexProbe>(probe: &mut P, logged_file: &mut Option<(PathBuf, Option<SystemTime>)>, file: &ResolvedFile, format: &FileFormat) {
+    let key = (file.path.clone(), file.modified);
+    if logged_file.as_ref() == Some(&key) {
+        return;
+    }
+    let message = match &format.binary {
+        Some(binary) => format!("Analyzing {}: {} ({})", file.path.display(), format, binary),
+        None => format!("Analyzing {}: {}", file.path.display(), format),
+    };
+    probe.log_event(&message);
+    *logged_file = Some(key);
+}
+
+// Prefers a recently saved pattern over the one attached to the project
+fn update_pattern<P: ImhexProbe>(probe: &mut P, state: &mut AppState) {
+    let pattern = probe.recent_pattern().map(|path| ActivePattern::saved(&path)).or_else(|| state.project_pattern.clone());
+    if let Some(pattern) = pattern.as_ref().filter(|&pattern| state.pattern.as_ref() != Some(pattern)) {
+        let location = pattern.path.as_ref().map_or_else(|| "project".to_string(), |path| path.display().to_string());
+        probe.log_event(&format!("{} ({})", pattern, location));
+    }
+    state.pattern = pattern;
+}
+
+// Logs what a newly opened project contains
+fn log_project<P: ImhexProbe>(probe: &mut P, project: &ProjectSummary) {
+    let paths: Vec<String> = project.provider_paths().iter().map(|path| path.display().to_string()).collect();
+    let pattern = if project.has_pattern() { "pattern attached" } else { "no pattern" };
+    probe.log_event(&format!("{} [{}], {}", project, paths.join(", "), pattern));
+}
+
+fn handle_imhex_not_running<S: ActivitySink>(sink: &mut S, state: &mut AppState) -> Result<(), AppError> {
+    if state.imhex_running {
+        state.imhex_running = false;
+        state.start_time = None;
+        state.opened_file = None;
+        state.project = None;
+        state.project_pattern = None;
+        state.pattern = None;
+        state.imhex_settings = None;
+        state.entropy.update(None);
+        state.activity.reset();
+        sink.clear_activity()?;
+    }
+    Ok(())
+}
+
+// Runs a single iteration of the presence loop
+pub fn tick<P: ImhexProbe, S: ActivitySink>(probe: &mut P, sink: &mut S, state: &mut AppState) -> Result<(), AppError> {
+    if let Some(process) = probe.imhex_process() {
+        handle_imhex_running(probe, sink, state, &process)
+    } else {
+        handle_imhex_not_running(sink, state)
+    }
+}
+
+pub fn run_discord_loop<P: ImhexProbe, S: ActivitySink>(probe: &mut P, sink: &mut S, state: &mut AppState, update_interval: Duration) -> Result<(), AppError> {
+    while state.running.load(Ordering::SeqCst) {
+        tick(probe, sink, state)?;
+        sink.poll()?;
+        probe.wait_for_change(update_interval);
+    }
+    sink.clear_activity()?;
+    Ok(())
+}
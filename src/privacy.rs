@@ -0,0 +1,85 @@
+use std::path::Path;
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use sha2::{Digest, Sha256};
+
+use crate::settings::{PrivacyMode, PrivacySettings};
+
+// Hex digits of the hash kept in hashed labels
+const HASH_LABEL_LEN: usize = 8;
+
+// Decides what part of a file, project or pattern name may reach Discord
+pub struct PrivacyFilter {
+    mode: PrivacyMode,
+    allow: GlobSet,
+    deny: GlobSet,
+    generic_label: String,
+    salt: String,
+}
+
+// Case-insensitive, so a deny rule for *.bin also hides FIRMWARE.BIN
+fn build_globs(globs: &[String]) -> Result<GlobSet, globset::Error> {
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        builder.add(GlobBuilder::new(glob).case_insensitive(true).build()?);
+    }
+    builder.build()
+}
+
+impl PrivacyFilter {
+    pub fn new(settings: &PrivacySettings) -> Result<Self, globset::Error> {
+        Ok(Self {
+            mode: settings.mode,
+            allow: build_globs(&settings.allow)?,
+            deny: build_globs(&settings.deny)?,
+            generic_label: settings.generic_label.clone(),
+            salt: settings.salt.clone(),
+        })
+    }
+
+    // Shows nothing but the generic label, used when the configured rules can't be built
+    pub fn generic(settings: &PrivacySettings) -> Self {
+        Self {
+            mode: PrivacyMode::Generic,
+            allow: GlobSet::empty(),
+            deny: GlobSet::empty(),
+            generic_label: settings.generic_label.clone(),
+            salt: String::new(),
+        }
+    }
+
+    // Gets the mode for a name, path is the file on disk when it is known
+    pub fn mode_for(&self, name: &str, path: Option<&Path>) -> PrivacyMode {
+        let matches = |globs: &GlobSet| globs.is_match(name) || path.is_some_and(|path| globs.is_match(path));
+        if matches(&self.deny) {
+            PrivacyMode::Generic
+        } else if matches(&self.allow) {
+            PrivacyMode::Full
+        } else {
+            self.mode
+        }
+    }
+
+    // Gets what may be shown in place of the name
+    pub fn label(&self, name: &str, path: Option<&Path>) -> String {
+        match self.mode_for(name, path) {
+            PrivacyMode::Full => name.to_string(),
+            PrivacyMode::Extension => extension_label(name).unwrap_or_else(|| self.generic_label.clone()),
+            PrivacyMode::Hashed => hashed_label(name, &self.salt),
+            PrivacyMode::Generic => self.generic_label.clone(),
+        }
+    }
+}
+
+// Gets "*.ext" for names with an extension
+pub fn extension_label(name: &str) -> Option<String> {
+    let extension = Path::new(name).extension()?.to_string_lossy();
+    Some(format!("*.{}", extension))
+}
+
+// Gets a stable label like "file-1a2b3c4d" that doesn't reveal the name
+pub fn hashed_label(name: &str, salt: &str) -> String {
+    let digest = Sha256::new().chain_update(salt.as_bytes()).chain_update(name.as_bytes()).finalize();
+    let hex: String = digest.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("file-{}", &hex[..HASH_LABEL_LEN])
+}
@@ -0,0 +1,47 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::imhex_config::ImhexSettings;
+use crate::network::Selection;
+use crate::process::ImhexProcess;
+use crate::resolver::ResolvedFile;
+use crate::window::{ImhexWindow, ImhexWindows};
+
+// Source of everything the presence loop needs to know about ImHex
+pub trait ImhexProbe {
+    // Gets the running ImHex process, if any
+    fn imhex_process(&mut self) -> Option<ImhexProcess>;
+
+    // Gets every ImHex window, most recently active first
+    fn windows(&mut self) -> ImhexWindows;
+
+    // Gets the current selection in the hex editor
+    fn selection(&mut self) -> Option<Selection>;
+
+    // Checks if ImHex owns the foreground window, None when the platform cannot tell
+    fn imhex_focused(&mut self) -> Option<bool>;
+
+    // Gets the time since the last keyboard or mouse input
+    fn idle_time(&mut self) -> Option<Duration>;
+
+    // Resolves the file shown in a window to its path on disk
+    fn opened_file(&mut self, window: &ImhexWindow) -> Option<ResolvedFile>;
+
+    // Resolves the .hexproj file behind a project window
+    fn project_file(&mut self, window: &ImhexWindow) -> Option<ResolvedFile>;
+
+    // Gets the .hexpat file the user saved recently, if any
+    fn recent_pattern(&mut self) -> Option<PathBuf>;
+
+    // Finds the saved .hexpat file with this pattern source
+    fn find_pattern(&mut self, source: &str) -> Option<PathBuf>;
+
+    // Gets ImHex's own settings, None when its config could not be found
+    fn imhex_settings(&mut self) -> Option<ImhexSettings>;
+
+    // Sleeps until the windows may have changed, returns true when a window event cut the wait short
+    fn wait_for_change(&mut self, timeout: Duration) -> bool;
+
+    // Records a message in the session log
+    fn log_event(&mut self, message: &str);
+}
@@ -0,0 +1,283 @@
+#[cfg(target_os = "linux")]
+use std::fs;
+#[cfg(target_os = "linux")]
+use std::path::Path;
+use std::path::PathBuf;
+
+#[cfg(windows)]
+use std::ffi::OsString;
+#[cfg(windows)]
+use std::mem;
+#[cfg(windows)]
+use std::os::windows::ffi::OsStringExt;
+#[cfg(windows)]
+use winapi::shared::minwindef::{FALSE, FILETIME};
+#[cfg(windows)]
+use winapi::um::handleapi::{CloseHandle, INVALID_HANDLE_VALUE};
+#[cfg(windows)]
+use winapi::um::processthreadsapi::{GetProcessTimes, OpenProcess};
+#[cfg(windows)]
+use winapi::um::winbase::QueryFullProcessImageNameW;
+#[cfg(windows)]
+use winapi::um::tlhelp32::{
+    CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W, TH32CS_SNAPPROCESS,
+};
+#[cfg(windows)]
+use winapi::um::winnt::PROCESS_QUERY_LIMITED_INFORMATION;
+
+// USER_HZ, which /proc reports start times in, is fixed at 100 on every mainstream architecture
+#[cfg(target_os = "linux")]
+const CLOCK_TICKS_PER_SECOND: i64 = 100;
+
+// Seconds between the Windows FILETIME epoch (1601) and the Unix epoch
+#[cfg(windows)]
+const FILETIME_UNIX_OFFSET: i64 = 11_644_473_600;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImhexProcess {
+    pub pid: u32,
+    // Unix timestamp in seconds, None when the process could not be queried
+    pub start_time: Option<i64>,
+}
+
+// Any running process, as far as the process list tells
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProcessEntry {
+    pub pid: u32,
+    // comm on Linux, the executable file name on Windows
+    pub name: String,
+    pub exe: Option<PathBuf>,
+    // Empty where the command line can't be read
+    pub args: Vec<String>,
+}
+
+// Checks if an executable name belongs to ImHex
+pub fn is_imhex_executable_name(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    let stem = name.strip_suffix(".exe").unwrap_or(&name);
+    stem == "imhex" || stem == "imhex-gui" || (stem.starts_with("imhex") && stem.ends_with(".appimage"))
+}
+
+// Gets every running ImHex process, oldest first
+pub fn find_imhex_processes() -> Vec<ImhexProcess> {
+    #[cfg(target_os = "linux")]
+    let mut processes = scan_proc(Path::new("/proc"));
+    #[cfg(windows)]
+    let mut processes = snapshot_processes();
+    #[cfg(not(any(target_os = "linux", windows)))]
+    let mut processes: Vec<ImhexProcess> = Vec::new();
+
+    processes.sort_by_key(|process| (process.start_time.unwrap_or(i64::MAX), process.pid));
+    processes
+}
+
+// Lists the running processes whose name or argv[0] file name passes the filter
+pub fn list_processes<F: Fn(&str) -> bool>(wanted: F) -> Vec<ProcessEntry> {
+    #[cfg(target_os = "linux")]
+    return read_processes(Path::new("/proc"), wanted);
+    // Snapshot names are the executable file names, so only the wanted processes get opened for their path
+    #[cfg(windows)]
+    return snapshot_entries()
+        .into_iter()
+        .filter(|(_, name)| wanted(name))
+        .map(|(pid, name)| ProcessEntry { pid, name, exe: process_executable(pid), args: Vec::new() })
+        .collect();
+    #[cfg(not(any(target_os = "linux", windows)))]
+    {
+        let _ = wanted;
+        Vec::new()
+    }
+}
+
+// Reads comm && cmdline of every process in a procfs tree, && the exe link of those whose comm or argv[0] file name passes the filter
+#[cfg(target_os = "linux")]
+pub fn read_processes<F: Fn(&str) -> bool>(proc_root: &Path, wanted: F) -> Vec<ProcessEntry> {
+    let entries = match fs::read_dir(proc_root) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let pid = entry.file_name().to_str()?.parse::<u32>().ok()?;
+            let process_dir = entry.path();
+            let name = fs::read_to_string(process_dir.join("comm")).ok()?.trim_end().to_string();
+            let args: Vec<String> = fs::read(process_dir.join("cmdline"))
+                .map(|cmdline| {
+                    cmdline.split(|&b| b == 0).filter(|arg| !arg.is_empty()).map(|arg| String::from_utf8_lossy(arg).into_owned()).collect()
+                })
+                .unwrap_or_default();
+            // comm is cut at 15 bytes && Electron hosts name their threads, so argv[0] gets a look too
+            let argv0 = args.first().map(|arg| arg.rsplit('/').next().unwrap_or(arg));
+            if !wanted(&name) && !argv0.is_some_and(&wanted) {
+                return None;
+            }
+            Some(ProcessEntry { pid, name, exe: fs::read_link(process_dir.join("exe")).ok(), args })
+        })
+        .collect()
+}
+
+// Reads the /proc/<pid>/exe link
+#[cfg(target_os = "linux")]
+pub fn read_executable(proc_root: &Path, pid: u32) -> Option<PathBuf> {
+    fs::read_link(proc_root.join(pid.to_string()).join("exe")).ok()
+}
+
+// Reads a variable from /proc/<pid>/environ, only readable for our own processes
+#[cfg(target_os = "linux")]
+pub fn read_environ_var(proc_root: &Path, pid: u32, name: &str) -> Option<String> {
+    let environ = fs::read(proc_root.join(pid.to_string()).join("environ")).ok()?;
+    environ.split(|&b| b == 0).find_map(|entry| {
+        let entry = String::from_utf8_lossy(entry);
+        let (key, value) = entry.split_once('=')?;
+        (key == name).then(|| value.to_string())
+    })
+}
+
+// Scans a procfs tree for ImHex processes
+#[cfg(target_os = "linux")]
+pub fn scan_proc(proc_root: &Path) -> Vec<ImhexProcess> {
+    let entries = match fs::read_dir(proc_root) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    let boot_time = read_boot_time(proc_root);
+
+    entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let pid = entry.file_name().to_str()?.parse::<u32>().ok()?;
+            let process_dir = entry.path();
+            if !is_imhex_process_dir(&process_dir) {
+                return None;
+            }
+            let start_time = boot_time
+                .zip(read_start_ticks(&process_dir))
+                .map(|(boot_time, ticks)| boot_time + ticks / CLOCK_TICKS_PER_SECOND);
+            Some(ImhexProcess { pid, start_time })
+        })
+        .collect()
+}
+
+// Checks comm, exe && argv[0] of a /proc/<pid> directory
+#[cfg(target_os = "linux")]
+fn is_imhex_process_dir(process_dir: &Path) -> bool {
+    if let Ok(comm) = fs::read_to_string(process_dir.join("comm")) {
+        if is_imhex_executable_name(comm.trim_end()) {
+            return true;
+        }
+    }
+
+    // Flatpak && AppImage launches keep a recognisable binary name even when comm differs
+    if let Ok(exe) = fs::read_link(process_dir.join("exe")) {
+        if exe.file_name().and_then(|name| name.to_str()).is_some_and(is_imhex_executable_name) {
+            return true;
+        }
+    }
+
+    if let Ok(cmdline) = fs::read(process_dir.join("cmdline")) {
+        let argv0 = cmdline.split(|&b| b == 0).next().unwrap_or_default();
+        let argv0 = String::from_utf8_lossy(argv0);
+        if let Some(name) = argv0.rsplit('/').next() {
+            return is_imhex_executable_name(name);
+        }
+    }
+
+    false
+}
+
+// Reads the boot time from /proc/stat
+#[cfg(target_os = "linux")]
+fn read_boot_time(proc_root: &Path) -> Option<i64> {
+    let stat = fs::read_to_string(proc_root.join("stat")).ok()?;
+    stat.lines()
+        .find_map(|line| line.strip_prefix("btime "))
+        .and_then(|btime| btime.trim().parse().ok())
+}
+
+// Reads the start time in clock ticks since boot from /proc/<pid>/stat
+#[cfg(target_os = "linux")]
+fn read_start_ticks(process_dir: &Path) -> Option<i64> {
+    let stat = fs::read_to_string(process_dir.join("stat")).ok()?;
+    // comm may contain spaces && parentheses, so split after the last ')'
+    let fields = &stat[stat.rfind(')')? + 1..];
+    // starttime is field 22, the 20th after pid && comm
+    fields.split_whitespace().nth(19)?.parse().ok()
+}
+
+// Walks a Toolhelp snapshot for ImHex processes
+#[cfg(windows)]
+fn snapshot_processes() -> Vec<ImhexProcess> {
+    snapshot_entries()
+        .into_iter()
+        .filter(|(_, name)| is_imhex_executable_name(name))
+        .map(|(pid, _)| ImhexProcess { pid, start_time: process_start_time(pid) })
+        .collect()
+}
+
+// Gets the pid && executable name of every process in a Toolhelp snapshot
+#[cfg(windows)]
+fn snapshot_entries() -> Vec<(u32, String)> {
+    let mut entries = Vec::new();
+    unsafe {
+        let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0);
+        if snapshot == INVALID_HANDLE_VALUE {
+            return entries;
+        }
+
+        let mut entry: PROCESSENTRY32W = mem::zeroed();
+        entry.dwSize = mem::size_of::<PROCESSENTRY32W>() as u32;
+        let mut has_entry = Process32FirstW(snapshot, &mut entry) != FALSE;
+        while has_entry {
+            let length = entry.szExeFile.iter().position(|&c| c == 0).unwrap_or(entry.szExeFile.len());
+            entries.push((entry.th32ProcessID, String::from_utf16_lossy(&entry.szExeFile[..length])));
+            has_entry = Process32NextW(snapshot, &mut entry) != FALSE;
+        }
+
+        CloseHandle(snapshot);
+    }
+    entries
+}
+
+// Gets the image path of a process through QueryFullProcessImageNameW
+#[cfg(windows)]
+pub fn process_executable(pid: u32) -> Option<PathBuf> {
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, FALSE, pid);
+        if handle.is_null() {
+            return None;
+        }
+
+        let mut buffer = [0u16; 1024];
+        let mut length = buffer.len() as u32;
+        let ok = QueryFullProcessImageNameW(handle, 0, buffer.as_mut_ptr(), &mut length) != FALSE;
+        CloseHandle(handle);
+
+        ok.then(|| PathBuf::from(OsString::from_wide(&buffer[..length as usize])))
+    }
+}
+
+// Gets the creation time of a process as a Unix timestamp
+#[cfg(windows)]
+fn process_start_time(pid: u32) -> Option<i64> {
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, FALSE, pid);
+        if handle.is_null() {
+            return None;
+        }
+
+        let mut creation: FILETIME = mem::zeroed();
+        let mut exit: FILETIME = mem::zeroed();
+        let mut kernel: FILETIME = mem::zeroed();
+        let mut user: FILETIME = mem::zeroed();
+        let ok = GetProcessTimes(handle, &mut creation, &mut exit, &mut kernel, &mut user) != FALSE;
+        CloseHandle(handle);
+
+        if !ok {
+            return None;
+        }
+        let intervals = ((creation.dwHighDateTime as i64) << 32) | creation.dwLowDateTime as i64;
+        Some(intervals / 10_000_000 - FILETIME_UNIX_OFFSET)
+    }
+}
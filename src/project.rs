@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::resolver::ResolvedFile;
+
+pub const PROJECT_EXTENSION: &str = "hexproj";
+
+const METADATA_ENTRY: &str = "IMHEX_METADATA";
+const PROVIDERS_DIR: &str = "providers/";
+const BOOKMARKS_ENTRY: &str = "bookmarks.json";
+const PATTERN_ENTRY: &str = "pattern_source.hexpat";
+
+// Entries bigger than this are skipped instead of read into memory
+const MAX_ENTRY_LEN: u64 = 16 * 1024 * 1024;
+
+// Projects kept before the cache is flushed
+const CACHE_CAPACITY: usize = 32;
+
+#[derive(Debug, Error)]
+pub enum ProjectError {
+    #[error("Failed to read project: {0}")]
+    Io(#[from] io::Error),
+    #[error("Not an ImHex project: {0}")]
+    Malformed(String),
+}
+
+// A data source stored in the project, e.g. a file on disk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectProvider {
+    pub id: u64,
+    // Provider type, e.g. "hex.builtin.provider.file"
+    pub kind: String,
+    pub path: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectSummary {
+    pub name: String,
+    // ImHex version that saved the project
+    pub version: Option<String>,
+    pub providers: Vec<ProjectProvider>,
+    pub bookmarks: usize,
+    // Pattern source attached to the first provider that has one
+    pub pattern: Option<String>,
+}
+
+impl ProjectSummary {
+    pub fn provider_paths(&self) -> Vec<&Path> {
+        self.providers.iter().filter_map(|provider| provider.path.as_deref()).collect()
+    }
+
+    pub fn has_pattern(&self) -> bool {
+        self.pattern.is_some()
+    }
+}
+
+impl fmt::Display for ProjectSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let files = self.providers.len();
+        write!(
+            f,
+            "Project: {} ({} file{}, {} bookmark{})",
+            self.name,
+            files,
+            if files == 1 { "" } else { "s" },
+            self.bookmarks,
+            if self.bookmarks == 1 { "" } else { "s" }
+        )
+    }
+}
+
+// Reads a .hexproj tar archive, named after the project file
+pub fn read_project<R: Read>(reader: R, name: &str) -> Result<ProjectSummary, ProjectError> {
+    let mut archive = tar::Archive::new(reader);
+    let mut version = None;
+    let mut is_project = false;
+    let mut providers = Vec::new();
+    let mut bookmarks = 0;
+    let mut patterns: Vec<(String, String)> = Vec::new();
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() || entry.size() > MAX_ENTRY_LEN {
+            continue;
+        }
+        let path = entry.path()?.to_string_lossy().trim_start_matches("./").to_string();
+        let mut contents = String::new();
+        if entry.read_to_string(&mut contents).is_err() {
+            continue;
+        }
+
+        if path == METADATA_ENTRY {
+            // "HEX\n<version>\n"
+            let mut lines = contents.lines();
+            is_project = lines.next() == Some("HEX");
+            version = lines.next().map(str::trim).filter(|version| !version.is_empty()).map(str::to_string);
+        } else if let Some(provider) = path.strip_prefix(PROVIDERS_DIR) {
+            let id = provider.strip_suffix(".json").and_then(|id| id.parse::<u64>().ok());
+            if let Some(id) = id {
+                providers.push(parse_provider(id, &contents)?);
+            }
+        } else if path.rsplit('/').next() == Some(BOOKMARKS_ENTRY) {
+            bookmarks += count_bookmarks(&contents)?;
+        } else if path.rsplit('/').next() == Some(PATTERN_ENTRY) && !contents.trim().is_empty() {
+            patterns.push((path, contents));
+        }
+    }
+
+    if !is_project {
+        return Err(ProjectError::Malformed(format!("{} has no ImHex metadata", name)));
+    }
+    providers.sort_by_key(|provider| provider.id);
+    // Per-provider folders are named after the provider id
+    patterns.sort_by_key(|(path, _)| path.split('/').next().and_then(|id| id.parse::<u64>().ok()));
+
+    Ok(ProjectSummary {
+        name: name.to_string(),
+        version,
+        providers,
+        bookmarks,
+        pattern: patterns.into_iter().next().map(|(_, source)| source),
+    })
+}
+
+fn parse_json(contents: &str) -> Result<Value, ProjectError> {
+    serde_json::from_str(contents).map_err(|e| ProjectError::Malformed(e.to_string()))
+}
+
+fn parse_provider(id: u64, contents: &str) -> Result<ProjectProvider, ProjectError> {
+    let json = parse_json(contents)?;
+    Ok(ProjectProvider {
+        id,
+        kind: json["type"].as_str().unwrap_or_default().to_string(),
+        path: json["settings"]["path"].as_str().filter(|path| !path.is_empty()).map(PathBuf::from),
+    })
+}
+
+fn count_bookmarks(contents: &str) -> Result<usize, ProjectError> {
+    let json = parse_json(contents)?;
+    let bookmarks = match &json {
+        Value::Array(bookmarks) => Some(bookmarks),
+        _ => json["bookmarks"].as_array(),
+    };
+    Ok(bookmarks.map_or(0, Vec::len))
+}
+
+// Reads a project file from disk, named after its file stem
+pub fn read_project_file(path: &Path) -> Result<ProjectSummary, ProjectError> {
+    let name = path.file_stem().map_or_else(String::new, |stem| stem.to_string_lossy().to_string());
+    read_project(File::open(path)?, &name)
+}
+
+// Gets the file name ImHex gave the project behind a window title
+pub fn project_file_name(name: &str) -> String {
+    if Path::new(name).extension().is_some_and(|extension| extension == PROJECT_EXTENSION) {
+        name.to_string()
+    } else {
+        format!("{}.{}", name, PROJECT_EXTENSION)
+    }
+}
+
+// Caches project summaries by path && modification time
+#[derive(Default)]
+pub struct ProjectReader {
+    cache: HashMap<PathBuf, (Option<SystemTime>, Option<ProjectSummary>)>,
+}
+
+impl ProjectReader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn read(&mut self, file: &ResolvedFile) -> Option<ProjectSummary> {
+        if let Some((modified, summary)) = self.cache.get(&file.path) {
+            if *modified == file.modified {
+                return summary.clone();
+            }
+        }
+
+        let summary = read_project_file(&file.path).ok();
+        if self.cache.len() >= CACHE_CAPACITY {
+            self.cache.clear();
+        }
+        self.cache.insert(file.path.clone(), (file.modified, summary.clone()));
+        summary
+    }
+}
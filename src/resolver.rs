@@ -0,0 +1,145 @@
+use std::cmp::Reverse;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde::Deserialize;
+
+// Folder inside the ImHex config directory holding one JSON entry per recently opened provider
+const RECENT_DIR_NAME: &str = "recent";
+
+// A file on disk that ImHex has open
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedFile {
+    pub path: PathBuf,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+}
+
+impl ResolvedFile {
+    // Reads the size && modification time, None if the path is not a regular file
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        if !metadata.is_file() {
+            return None;
+        }
+        Some(Self {
+            path: path.to_path_buf(),
+            size: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct RecentEntry {
+    path: Option<PathBuf>,
+}
+
+// Maps the file name shown in an ImHex window title back to a path on disk
+pub struct FileResolver {
+    config_dirs: Vec<PathBuf>,
+    // Extra recently opened files, e.g. from ImHex's settings
+    recent_files: Vec<PathBuf>,
+    #[cfg(target_os = "linux")]
+    proc_root: PathBuf,
+}
+
+impl FileResolver {
+    pub fn new(config_dirs: Vec<PathBuf>) -> Self {
+        Self {
+            config_dirs,
+            recent_files: Vec::new(),
+            #[cfg(target_os = "linux")]
+            proc_root: PathBuf::from("/proc"),
+        }
+    }
+
+    pub fn with_recent_files(mut self, recent_files: Vec<PathBuf>) -> Self {
+        self.recent_files = recent_files;
+        self
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn with_proc_root(mut self, proc_root: &Path) -> Self {
+        self.proc_root = proc_root.to_path_buf();
+        self
+    }
+
+    // Resolves a file name from a window title, preferring files the owning process has open
+    pub fn resolve(&self, name: &str, pid: Option<u32>) -> Option<ResolvedFile> {
+        let direct = Path::new(name);
+        if direct.is_absolute() {
+            if let Some(file) = ResolvedFile::from_path(direct) {
+                return Some(file);
+            }
+        }
+
+        #[cfg(target_os = "linux")]
+        if let Some(pid) = pid {
+            let open = open_files(&self.proc_root, pid).into_iter().find(|path| has_file_name(path, name));
+            if let Some(file) = open.as_deref().and_then(ResolvedFile::from_path) {
+                return Some(file);
+            }
+        }
+        #[cfg(not(target_os = "linux"))]
+        let _ = pid;
+
+        self.config_dirs
+            .iter()
+            .flat_map(|config_dir| recent_files(config_dir))
+            .chain(self.recent_files.iter().cloned())
+            .filter(|path| has_file_name(path, name))
+            .find_map(|path| ResolvedFile::from_path(&path))
+    }
+}
+
+// Only resolves absolute titles && open descriptors until ImHex's config directories are known
+impl Default for FileResolver {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+fn has_file_name(path: &Path, name: &str) -> bool {
+    path.file_name().is_some_and(|file_name| file_name.to_string_lossy() == name)
+}
+
+// Reads the file paths from ImHex's recent entries, most recently opened first
+pub fn recent_files(config_dir: &Path) -> Vec<PathBuf> {
+    let entries = match fs::read_dir(config_dir.join(RECENT_DIR_NAME)) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut recent: Vec<(SystemTime, PathBuf)> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let modified = entry.metadata().ok()?.modified().ok()?;
+            let contents = fs::read_to_string(entry.path()).ok()?;
+            let path = serde_json::from_str::<RecentEntry>(&contents).ok()?.path?;
+            Some((modified, path))
+        })
+        .collect();
+    recent.sort_by_key(|(modified, _)| Reverse(*modified));
+    recent.into_iter().map(|(_, path)| path).collect()
+}
+
+// Lists the regular files a process has open through its /proc fd links
+#[cfg(target_os = "linux")]
+pub fn open_files(proc_root: &Path, pid: u32) -> Vec<PathBuf> {
+    let entries = match fs::read_dir(proc_root.join(pid.to_string()).join("fd")) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    // Sockets && pipes link to pseudo paths like "socket:[1234]"
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| fs::read_link(entry.path()).ok())
+        .filter(|target| target.is_absolute() && !target.starts_with("/dev") && target.is_file())
+        .collect();
+    files.sort();
+    files.dedup();
+    files
+}
@@ -0,0 +1,184 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use log::debug;
+
+use crate::error::AppError;
+use crate::presence::{Activity, ActivityAssets, ActivitySink};
+
+// Discord accepts about 5 activity updates per 20 seconds
+pub const UPDATE_BURST: u32 = 5;
+pub const UPDATE_PERIOD: Duration = Duration::from_secs(20);
+
+// Source of the current time, so the scheduler can run on a virtual clock in tests
+pub trait Clock {
+    fn now(&self) -> Instant;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+// Clock that only moves when told to, clones share the same time
+#[derive(Debug, Clone)]
+pub struct VirtualClock {
+    now: Arc<Mutex<Instant>>,
+}
+
+impl VirtualClock {
+    pub fn new() -> Self {
+        Self { now: Arc::new(Mutex::new(Instant::now())) }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+impl Default for VirtualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for VirtualClock {
+    fn now(&self) -> Instant {
+        *self.now.lock().unwrap()
+    }
+}
+
+// Starts full && regains one token every period / capacity
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    capacity: u32,
+    tokens: u32,
+    refill_interval: Duration,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(capacity: u32, period: Duration, now: Instant) -> Self {
+        Self { capacity, tokens: capacity, refill_interval: period / capacity.max(1), last_refill: now }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        let earned = (elapsed.as_nanos() / self.refill_interval.as_nanos().max(1)).min(self.capacity as u128) as u32;
+        self.tokens = (self.tokens + earned).min(self.capacity);
+        // A full bucket doesn't bank time towards the next token
+        self.last_refill = if self.tokens == self.capacity { now } else { self.last_refill + self.refill_interval * earned };
+    }
+
+    // Takes a token if one is left
+    pub fn try_take(&mut self, now: Instant) -> bool {
+        self.refill(now);
+        if self.tokens == 0 {
+            return false;
+        }
+        self.tokens -= 1;
+        true
+    }
+
+    // Gets how long until a token is available, zero if one is left
+    pub fn wait_time(&mut self, now: Instant) -> Duration {
+        self.refill(now);
+        match self.tokens {
+            0 => (self.last_refill + self.refill_interval).saturating_duration_since(now),
+            _ => Duration::ZERO,
+        }
+    }
+}
+
+// Sits between the presence loop && Discord, sending at most UPDATE_BURST updates per UPDATE_PERIOD.
+// Updates that arrive without a token replace each other, so the latest one goes out once a token frees up.
+pub struct RateLimitedSink<S, C = SystemClock> {
+    sink: S,
+    clock: C,
+    bucket: TokenBucket,
+    // Last activity handed to the sink, None after a clear
+    sent: Option<Activity>,
+    // Latest activity still waiting for a token
+    pending: Option<Activity>,
+}
+
+impl<S: ActivitySink> RateLimitedSink<S> {
+    pub fn new(sink: S) -> Self {
+        Self::with_clock(sink, SystemClock)
+    }
+}
+
+impl<S: ActivitySink, C: Clock> RateLimitedSink<S, C> {
+    pub fn with_clock(sink: S, clock: C) -> Self {
+        let bucket = TokenBucket::new(UPDATE_BURST, UPDATE_PERIOD, clock.now());
+        Self { sink, clock, bucket, sent: None, pending: None }
+    }
+
+    pub fn sink(&self) -> &S {
+        &self.sink
+    }
+
+    // Checks if an update is waiting for a token
+    pub fn has_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    // Sends the waiting update if a token is free, returns how long until the next try otherwise
+    pub fn flush(&mut self) -> Result<Option<Duration>, AppError> {
+        if self.pending.is_none() {
+            return Ok(None);
+        }
+        let now = self.clock.now();
+        if !self.bucket.try_take(now) {
+            return Ok(Some(self.bucket.wait_time(now)));
+        }
+
+        let activity = self.pending.take().expect("checked above");
+        match activity.send_to(&mut self.sink) {
+            Ok(()) => {
+                self.sent = Some(activity);
+                Ok(None)
+            }
+            Err(e) => {
+                // Keep it for the next try, the caller decides whether the sink is gone
+                self.pending = Some(activity);
+                Err(e)
+            }
+        }
+    }
+}
+
+impl<S: ActivitySink, C: Clock> ActivitySink for RateLimitedSink<S, C> {
+    fn update_activity(&mut self, state: String, details: String, start_time: Option<i64>, assets: ActivityAssets) -> Result<(), AppError> {
+        let activity = Activity { state, details, start_time, assets };
+        if self.sent.as_ref() == Some(&activity) {
+            // Back to what Discord already shows, whatever was waiting is stale
+            self.pending = None;
+        } else {
+            if self.pending.is_some() {
+                debug!("Coalescing rate limited activity update");
+            }
+            self.pending = Some(activity);
+        }
+        self.flush().map(|_| ())
+    }
+
+    // Clears go out right away so ImHex closing is never shown late, they still use up a token
+    fn clear_activity(&mut self) -> Result<(), AppError> {
+        self.pending = None;
+        self.bucket.try_take(self.clock.now());
+        self.sink.clear_activity()?;
+        self.sent = None;
+        Ok(())
+    }
+
+    // Gives held back updates a chance to go out even when the loop has nothing new
+    fn poll(&mut self) -> Result<(), AppError> {
+        self.flush()?;
+        self.sink.poll()
+    }
+}
@@ -0,0 +1,175 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use globset::Glob;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::template::Templates;
+
+pub const SETTINGS_FILE_NAME: &str = "config.json";
+
+// Discord shows at most two buttons, with labels of up to 32 characters
+pub const MAX_BUTTONS: usize = 2;
+pub const MAX_BUTTON_LABEL_LEN: usize = 32;
+
+#[derive(Debug, Error)]
+pub enum SettingsError {
+    #[error("Failed to read settings: {0}")]
+    Io(#[from] io::Error),
+    #[error("Invalid settings: {0}")]
+    Parse(#[from] serde_json::Error),
+    #[error("Invalid settings: {0}")]
+    Invalid(String),
+}
+
+// Discord asset && hover text template for one of the presence images
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ImageSettings {
+    // Asset key uploaded to the Discord application, empty for no image
+    pub key: String,
+    // Hover text, may use placeholders like {file} or {format}
+    pub text: String,
+}
+
+// Button linking to a URL, both fields may use placeholders
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ButtonSettings {
+    pub label: String,
+    pub url: String,
+}
+
+// How file, project && pattern names are shown in the presence
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PrivacyMode {
+    // The name as ImHex shows it
+    #[default]
+    Full,
+    // Only the extension, e.g. "*.bin"
+    Extension,
+    // A label derived from the name, the same name always gets the same label
+    Hashed,
+    // The generic label, e.g. "a file"
+    Generic,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct PrivacySettings {
+    pub mode: PrivacyMode,
+    // Globs matched against the name && full path, denied names are always generic && allowed ones shown in full
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+    pub generic_label: String,
+    // Mixed into hashed labels so they can't be looked up from common names
+    pub salt: String,
+}
+
+impl Default for PrivacySettings {
+    fn default() -> Self {
+        Self {
+            mode: PrivacyMode::Full,
+            allow: Vec::new(),
+            deny: Vec::new(),
+            generic_label: "a file".to_string(),
+            salt: String::new(),
+        }
+    }
+}
+
+// Details && state line templates for one state, None keeps the default
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct LineTemplates {
+    pub details: Option<String>,
+    pub state: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct TemplateSettings {
+    // No file open, or ImHex running without a window
+    pub idle: LineTemplates,
+    pub analyzing: LineTemplates,
+    // The user has been idle for the away timeout
+    pub away: LineTemplates,
+    // ImHex has been in the background for the away timeout, defaults to the away lines
+    pub paused: LineTemplates,
+}
+
+// User settings read from config.json, every field is optional
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    // Seconds of idling or ImHex sitting in the background before showing away, 0 disables it
+    pub away_timeout_secs: u64,
+    // Shown when the file has no format-specific icon, its text replaces the format description when set
+    pub large_image: ImageSettings,
+    pub small_image: ImageSettings,
+    pub buttons: Vec<ButtonSettings>,
+    pub privacy: PrivacySettings,
+    pub templates: TemplateSettings,
+    // Shows the presence on every running Discord client instead of the first one found
+    pub broadcast: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            away_timeout_secs: 300,
+            large_image: ImageSettings::default(),
+            small_image: ImageSettings::default(),
+            buttons: Vec::new(),
+            privacy: PrivacySettings::default(),
+            templates: TemplateSettings::default(),
+            broadcast: false,
+        }
+    }
+}
+
+impl Settings {
+    // Loads settings from a file, falling back to defaults when it does not exist
+    pub fn load(path: &Path) -> Result<Self, SettingsError> {
+        match fs::read_to_string(path) {
+            Ok(contents) => {
+                let settings: Self = serde_json::from_str(&contents)?;
+                settings.validate()?;
+                Ok(settings)
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    // Rejects buttons Discord would refuse, broken privacy globs && templates, so a typo shows up at startup rather than in the presence
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        for glob in self.privacy.allow.iter().chain(&self.privacy.deny) {
+            Glob::new(glob).map_err(|e| SettingsError::Invalid(format!("privacy glob {:?}: {}", glob, e)))?;
+        }
+        // Unsalted labels can be reversed by hashing a list of common file names
+        if self.privacy.mode == PrivacyMode::Hashed && self.privacy.salt.trim().is_empty() {
+            return Err(SettingsError::Invalid("privacy mode \"hashed\" needs a salt".to_string()));
+        }
+        if self.buttons.len() > MAX_BUTTONS {
+            return Err(SettingsError::Invalid(format!("at most {} buttons are allowed, got {}", MAX_BUTTONS, self.buttons.len())));
+        }
+        for button in &self.buttons {
+            if button.label.trim().is_empty() || button.label.chars().count() > MAX_BUTTON_LABEL_LEN {
+                return Err(SettingsError::Invalid(format!("button label {:?} must be 1 to {} characters", button.label, MAX_BUTTON_LABEL_LEN)));
+            }
+            if !button.url.starts_with("https://") && !button.url.starts_with("http://") {
+                return Err(SettingsError::Invalid(format!("button URL {:?} must start with http:// or https://", button.url)));
+            }
+        }
+        Templates::from_settings(self).map_err(|(name, e)| SettingsError::Invalid(format!("template {}: {}", name, e)))?;
+        Ok(())
+    }
+
+    pub fn away_timeout(&self) -> Option<Duration> {
+        (self.away_timeout_secs > 0).then(|| Duration::from_secs(self.away_timeout_secs))
+    }
+}
@@ -0,0 +1,293 @@
+use std::fmt;
+
+use thiserror::Error;
+
+use crate::settings::{LineTemplates, Settings};
+
+// Every placeholder a template may use
+pub const PLACEHOLDERS: &[&str] = &[
+    "bookmarks", "elapsed", "entropy", "ext", "file", "format", "language", "open_files", "pattern", "project", "project_files",
+    "selection", "size", "theme", "workspace", "writing",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum TemplateError {
+    #[error("unknown placeholder {{{name}}} at {position}")]
+    UnknownPlaceholder { name: String, position: usize },
+    #[error("unclosed {{ at {0}")]
+    Unclosed(usize),
+    #[error("unmatched }} at {0}, write }}}} for a literal brace")]
+    UnmatchedBrace(usize),
+    #[error("{{/{name}}} at {position} does not close an open section")]
+    UnexpectedEnd { name: String, position: usize },
+    #[error("section {{?{0}}} is never closed with {{/{0}}}")]
+    UnclosedSection(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Text(String),
+    // {name} or {name|fallback}
+    Value { name: String, fallback: String },
+    // {?name}...{/name} when the value is set, {!name}...{/name} when it is empty
+    Section { name: String, when_set: bool, parts: Vec<Part> },
+}
+
+// A parsed template, e.g. "Analyzing{?format} {format}{/format}: [{file|a file}]"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Self, TemplateError> {
+        // Open sections with the parts collected before them
+        let mut stack: Vec<(String, bool, Vec<Part>)> = Vec::new();
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut chars = source.char_indices().peekable();
+
+        while let Some((position, c)) = chars.next() {
+            match c {
+                '{' if chars.peek().map(|&(_, next)| next) == Some('{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek().map(|&(_, next)| next) == Some('}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '}' => return Err(TemplateError::UnmatchedBrace(position)),
+                '{' => {
+                    let end = source[position..].find('}').map(|end| position + end).ok_or(TemplateError::Unclosed(position))?;
+                    let tag = &source[position + 1..end];
+                    while chars.peek().is_some_and(|&(next, _)| next <= end) {
+                        chars.next();
+                    }
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+
+                    let known = |name: &str| match PLACEHOLDERS.contains(&name) {
+                        true => Ok(name.to_string()),
+                        false => Err(TemplateError::UnknownPlaceholder { name: name.to_string(), position }),
+                    };
+                    if let Some(name) = tag.strip_prefix('?').or_else(|| tag.strip_prefix('!')) {
+                        stack.push((known(name.trim())?, tag.starts_with('?'), std::mem::take(&mut parts)));
+                    } else if let Some(name) = tag.strip_prefix('/') {
+                        match stack.pop() {
+                            Some((open, when_set, outer)) if open == name.trim() => {
+                                let section = Part::Section { name: open, when_set, parts: std::mem::replace(&mut parts, outer) };
+                                parts.push(section);
+                            }
+                            _ => return Err(TemplateError::UnexpectedEnd { name: name.to_string(), position }),
+                        }
+                    } else {
+                        let (name, fallback) = tag.split_once('|').unwrap_or((tag, ""));
+                        parts.push(Part::Value { name: known(name.trim())?, fallback: fallback.to_string() });
+                    }
+                }
+                c => text.push(c),
+            }
+        }
+
+        if let Some((name, _, _)) = stack.pop() {
+            return Err(TemplateError::UnclosedSection(name));
+        }
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+        Ok(Self { parts })
+    }
+
+    pub fn render(&self, values: &[(&str, String)]) -> String {
+        self.render_with(values, |value| value.to_string())
+    }
+
+    // Renders with every placeholder value passed through encode, e.g. for URLs
+    pub fn render_with<F>(&self, values: &[(&str, String)], encode: F) -> String
+    where
+        F: Fn(&str) -> String,
+    {
+        let mut rendered = String::new();
+        render_parts(&self.parts, values, &encode, &mut rendered);
+        rendered
+    }
+}
+
+fn render_parts<F>(parts: &[Part], values: &[(&str, String)], encode: &F, rendered: &mut String)
+where
+    F: Fn(&str) -> String,
+{
+    let value = |name: &str| values.iter().find(|(key, _)| *key == name).map_or("", |(_, value)| value.as_str());
+    for part in parts {
+        match part {
+            Part::Text(text) => rendered.push_str(text),
+            Part::Value { name, fallback } => match value(name) {
+                "" => rendered.push_str(fallback),
+                value => rendered.push_str(&encode(value)),
+            },
+            Part::Section { name, when_set, parts } => {
+                if value(name).is_empty() != *when_set {
+                    render_parts(parts, values, encode, rendered);
+                }
+            }
+        }
+    }
+}
+
+// Details && state lines for one state of the presence
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineTemplate {
+    pub details: Template,
+    pub state: Template,
+}
+
+impl LineTemplate {
+    fn parse(details: &str, state: &str) -> Result<Self, TemplateError> {
+        Ok(Self { details: Template::parse(details)?, state: Template::parse(state)? })
+    }
+
+    pub fn render(&self, values: &[(&str, String)]) -> (String, String) {
+        (self.details.render(values), self.state.render(values))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PresenceState {
+    Idle,
+    Analyzing,
+    Away,
+    Paused,
+}
+
+impl PresenceState {
+    pub const ALL: [PresenceState; 4] = [PresenceState::Idle, PresenceState::Analyzing, PresenceState::Away, PresenceState::Paused];
+}
+
+impl fmt::Display for PresenceState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            PresenceState::Idle => "idle",
+            PresenceState::Analyzing => "analyzing",
+            PresenceState::Away => "away",
+            PresenceState::Paused => "paused",
+        };
+        f.pad(name)
+    }
+}
+
+// Where a template came from, for error messages
+fn labeled<T>(label: &str, result: Result<T, TemplateError>) -> Result<T, (String, TemplateError)> {
+    result.map_err(|e| (label.to_string(), e))
+}
+
+// Every template in the settings, parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Templates {
+    pub idle: LineTemplate,
+    pub analyzing: LineTemplate,
+    pub away: LineTemplate,
+    pub paused: LineTemplate,
+    pub large_text: Template,
+    pub small_text: Template,
+    // Label && URL of each button
+    pub buttons: Vec<(Template, Template)>,
+}
+
+impl Templates {
+    // Parses the templates, naming the setting that failed
+    pub fn from_settings(settings: &Settings) -> Result<Self, (String, TemplateError)> {
+        let templates = &settings.templates;
+        let line = |name: &str, lines: &LineTemplates, default: (&str, &str)| {
+            let details = lines.details.as_deref().unwrap_or(default.0);
+            let state = lines.state.as_deref().unwrap_or(default.1);
+            labeled(&format!("templates.{}", name), LineTemplate::parse(details, state))
+        };
+
+        // Paused looks like away unless set
+        let paused_default = (
+            templates.away.details.as_deref().unwrap_or(DEFAULT_AWAY_DETAILS),
+            templates.away.state.as_deref().unwrap_or(""),
+        );
+
+        let buttons = settings
+            .buttons
+            .iter()
+            .enumerate()
+            .map(|(index, button)| {
+                let label = labeled(&format!("buttons[{}].label", index), Template::parse(&button.label))?;
+                Ok((label, labeled(&format!("buttons[{}].url", index), Template::parse(&button.url))?))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            idle: line("idle", &templates.idle, (DEFAULT_IDLE_DETAILS, ""))?,
+            analyzing: line("analyzing", &templates.analyzing, (DEFAULT_ANALYZING_DETAILS, DEFAULT_ANALYZING_STATE))?,
+            away: line("away", &templates.away, (DEFAULT_AWAY_DETAILS, ""))?,
+            paused: line("paused", &templates.paused, paused_default)?,
+            large_text: labeled("large_image.text", Template::parse(&settings.large_image.text))?,
+            small_text: labeled("small_image.text", Template::parse(&settings.small_image.text))?,
+            buttons,
+        })
+    }
+
+    pub fn line(&self, state: PresenceState) -> &LineTemplate {
+        match state {
+            PresenceState::Idle => &self.idle,
+            PresenceState::Analyzing => &self.analyzing,
+            PresenceState::Away => &self.away,
+            PresenceState::Paused => &self.paused,
+        }
+    }
+}
+
+impl Default for Templates {
+    fn default() -> Self {
+        Self::from_settings(&Settings::default()).expect("default templates are valid")
+    }
+}
+
+pub const DEFAULT_IDLE_DETAILS: &str = "Idle";
+// A pattern being written, else the project, else the file
+pub const DEFAULT_ANALYZING_DETAILS: &str = concat!(
+    "{?writing}Writing pattern: {writing}{/writing}",
+    "{!writing}{?project}Project: {project} ({project_files}, {bookmarks}){/project}",
+    "{!project}Analyzing{?format} {format}{/format}: [{file}]{/project}{/writing}",
+    "{?open_files} ({open_files} files open){/open_files}",
+);
+pub const DEFAULT_ANALYZING_STATE: &str = "Bytes: [{selection|None}]";
+pub const DEFAULT_AWAY_DETAILS: &str = "Away";
+
+// Values used to preview the templates from the command line
+pub fn sample_values() -> Vec<(&'static str, String)> {
+    vec![
+        ("bookmarks", "12 bookmarks".to_string()),
+        ("elapsed", "1h 05m".to_string()),
+        ("entropy", "likely compressed/encrypted".to_string()),
+        ("ext", "bin".to_string()),
+        ("file", "firmware.bin".to_string()),
+        ("format", "ELF x86-64 executable".to_string()),
+        ("language", "en-US".to_string()),
+        ("open_files", "2".to_string()),
+        ("pattern", "elf.hexpat".to_string()),
+        ("project", "router".to_string()),
+        ("project_files", "3 files".to_string()),
+        ("selection", "0x10-0x2F (32 bytes)".to_string()),
+        ("size", "1.5 MiB".to_string()),
+        ("theme", "Dark".to_string()),
+        ("workspace", "Default".to_string()),
+        ("writing", String::new()),
+    ]
+}
+
+// Renders every state's lines with the sample values
+pub fn preview(templates: &Templates) -> String {
+    let values = sample_values();
+    let mut preview = String::new();
+    for state in PresenceState::ALL {
+        let (details, activity_state) = templates.line(state).render(&values);
+        preview.push_str(&format!("{:<10} details: {:?}\n{:<10} state:   {:?}\n", state, details, "", activity_state));
+    }
+    preview
+}
@@ -0,0 +1,171 @@
+use std::fmt;
+
+const UNSAVED_MARKERS: [&str; 2] = [" (*)", "*"];
+const READ_ONLY_MARKERS: [&str; 2] = [" (Read Only)", " [Read Only]"];
+const DEBUG_BUILD_MARKER: &str = "[Debug Build]";
+const PROJECT_PREFIXES: [&str; 2] = ["Project: ", "Project "];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProviderKind {
+    ProcessMemory,
+    Disk,
+    Gdb,
+    Memory,
+    View,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OpenedContent {
+    // Welcome screen, nothing opened
+    Welcome,
+    File(String),
+    Project(String),
+    // Data source that is not a regular file
+    Provider { kind: ProviderKind, name: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImhexWindowState {
+    pub content: OpenedContent,
+    pub unsaved: bool,
+    pub read_only: bool,
+    // Version or channel shown next to "ImHex", e.g. "1.35.4" or "Nightly"
+    pub version: Option<String>,
+    pub debug_build: bool,
+}
+
+impl ImhexWindowState {
+    pub fn welcome() -> Self {
+        Self {
+            content: OpenedContent::Welcome,
+            unsaved: false,
+            read_only: false,
+            version: None,
+            debug_build: false,
+        }
+    }
+
+    pub fn is_welcome(&self) -> bool {
+        self.content == OpenedContent::Welcome
+    }
+
+    // Gets the name of whatever is opened
+    pub fn display_name(&self) -> Option<&str> {
+        match &self.content {
+            OpenedContent::Welcome => None,
+            OpenedContent::File(name) | OpenedContent::Project(name) => Some(name),
+            OpenedContent::Provider { name, .. } => Some(name),
+        }
+    }
+}
+
+impl fmt::Display for ImhexWindowState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.content {
+            OpenedContent::Welcome => write!(f, "Welcome screen")?,
+            OpenedContent::File(name) => write!(f, "{}", name)?,
+            OpenedContent::Project(name) => write!(f, "Project {}", name)?,
+            OpenedContent::Provider { name, .. } => write!(f, "{}", name)?,
+        }
+        if self.unsaved {
+            write!(f, " (unsaved)")?;
+        }
+        if self.read_only {
+            write!(f, " (read only)")?;
+        }
+        Ok(())
+    }
+}
+
+// Parses an ImHex window title, None if the window does not belong to ImHex
+pub fn parse_window_title(title: &str) -> Option<ImhexWindowState> {
+    let title = title.trim_start();
+    let rest = match title.strip_prefix("ImHex") {
+        Some(rest) if rest.is_empty() || rest.starts_with(' ') => rest,
+        // Console host window of imhex-gui.exe on Windows
+        _ if title.to_ascii_lowercase().ends_with("imhex-gui.exe") => return Some(ImhexWindowState::welcome()),
+        _ => return None,
+    };
+
+    let (header, body) = match rest.find(" - ") {
+        Some(index) => (&rest[..index], Some(&rest[index + 3..])),
+        None => (rest, None),
+    };
+
+    let mut state = ImhexWindowState::welcome();
+    parse_header(header, &mut state);
+
+    if let Some(body) = body {
+        let name = strip_markers(body, &mut state);
+        if !name.is_empty() {
+            state.content = parse_content(name);
+        }
+    }
+    Some(state)
+}
+
+// Parses the version && build suffix between "ImHex" && the first " - "
+fn parse_header(header: &str, state: &mut ImhexWindowState) {
+    let mut header = header.trim().to_string();
+    if header.contains(DEBUG_BUILD_MARKER) {
+        state.debug_build = true;
+        header = header.replace(DEBUG_BUILD_MARKER, "").trim().to_string();
+    }
+
+    let version = header.strip_prefix('v').filter(|v| v.starts_with(|c: char| c.is_ascii_digit()));
+    let version = version.unwrap_or(&header);
+    if !version.is_empty() {
+        state.version = Some(version.to_string());
+    }
+}
+
+// Strips the unsaved && read-only markers ImHex appends to the title
+fn strip_markers<'a>(body: &'a str, state: &mut ImhexWindowState) -> &'a str {
+    let mut body = body.trim_end();
+    loop {
+        if let Some(stripped) = UNSAVED_MARKERS.iter().find_map(|marker| body.strip_suffix(marker)) {
+            state.unsaved = true;
+            body = stripped.trim_end();
+        } else if let Some(stripped) = READ_ONLY_MARKERS.iter().find_map(|marker| body.strip_suffix(marker)) {
+            state.read_only = true;
+            body = stripped.trim_end();
+        } else {
+            return body;
+        }
+    }
+}
+
+fn parse_content(name: &str) -> OpenedContent {
+    if let Some(project) = PROJECT_PREFIXES.iter().find_map(|prefix| name.strip_prefix(prefix)) {
+        return OpenedContent::Project(project.trim().to_string());
+    }
+
+    match provider_kind(name) {
+        Some(kind) => OpenedContent::Provider { kind, name: name.to_string() },
+        None => OpenedContent::File(name.to_string()),
+    }
+}
+
+// Recognises the names ImHex gives to its non-file providers
+fn provider_kind(name: &str) -> Option<ProviderKind> {
+    let lower = name.to_ascii_lowercase();
+    if lower.starts_with("process memory") {
+        Some(ProviderKind::ProcessMemory)
+    } else if lower.starts_with(r"\\.\physicaldrive") || lower.starts_with("/dev/") || is_drive_root(name) {
+        Some(ProviderKind::Disk)
+    } else if lower.starts_with("gdb server") {
+        Some(ProviderKind::Gdb)
+    } else if lower == "memory" || lower == "new file" || lower.starts_with("memory (") {
+        Some(ProviderKind::Memory)
+    } else if lower.starts_with("view (") || lower == "view" {
+        Some(ProviderKind::View)
+    } else {
+        None
+    }
+}
+
+// Checks for raw volume names such as "\\.\C:"
+fn is_drive_root(name: &str) -> bool {
+    let bytes = name.as_bytes();
+    name.starts_with(r"\\.\") && bytes.len() == 6 && bytes[4].is_ascii_alphabetic() && bytes[5] == b':'
+}
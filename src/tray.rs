@@ -3,36 +3,128 @@ use std::error::Error;
 use std::fs::File;
 use std::io::Write;
 use std::path::PathBuf;
-use std::sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}};
+use std::sync::{Arc, atomic::{AtomicBool, Ordering}};
 use std::thread;
+#[cfg(windows)]
+use std::{mem, ptr};
 
 use systray::{Application, Error as SystrayError};
+#[cfg(windows)]
+use winapi::shared::minwindef::FALSE;
+#[cfg(windows)]
+use winapi::shared::windef::HWND;
+#[cfg(windows)]
+use winapi::um::processthreadsapi::GetCurrentProcessId;
+#[cfg(windows)]
+use winapi::um::shellapi::{Shell_NotifyIconW, NIF_TIP, NIM_MODIFY, NOTIFYICONDATAW};
+#[cfg(windows)]
+use winapi::um::winuser::{FindWindowExW, GetWindowThreadProcessId};
 
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 pub const ICON: &[u8] = include_bytes!("data/icon.ico");
 
-pub fn create_tray_icon(running: &Arc<AtomicBool>) -> Result<Arc<Mutex<Application>>, Box<dyn Error>> {
-    let app = initialize_application()?;
-    set_icon_and_tooltip(&app)?;
-
-    add_menu_items(&app, running)?;
-    spawn_message_listener(app.clone());
+// Class of the hidden window systray registers its icon (id 1) with
+#[cfg(windows)]
+const TRAY_WINDOW_CLASS: &str = "my_window";
+#[cfg(windows)]
+const TRAY_ICON_ID: u32 = 1;
+// szTip holds 127 UTF-16 units && the terminator
+#[cfg(windows)]
+const TOOLTIP_LEN: usize = 127;
+
+// Updates the tooltip without the Application, which the message listener keeps borrowed for as long as the app runs
+#[derive(Clone)]
+pub struct TrayTooltip {
+    #[cfg(windows)]
+    window: Option<HWND>,
+}
 
-    Ok(app)
+// Shell_NotifyIconW takes the window handle from any thread
+#[cfg(windows)]
+unsafe impl Send for TrayTooltip {}
+#[cfg(windows)]
+unsafe impl Sync for TrayTooltip {}
+
+impl TrayTooltip {
+    #[cfg(windows)]
+    fn find() -> Self {
+        Self { window: find_tray_window() }
+    }
+
+    #[cfg(not(windows))]
+    fn find() -> Self {
+        Self {}
+    }
+
+    #[cfg(windows)]
+    pub fn set(&self, text: &str) -> Result<(), SystrayError> {
+        let window = self.window.ok_or_else(|| SystrayError::OsError("Tray window not found".into()))?;
+        let mut data: NOTIFYICONDATAW = unsafe { mem::zeroed() };
+        data.cbSize = mem::size_of::<NOTIFYICONDATAW>() as u32;
+        data.hWnd = window;
+        data.uID = TRAY_ICON_ID;
+        data.uFlags = NIF_TIP;
+        let mut len = 0;
+        for c in text.chars() {
+            if len + c.len_utf16() > TOOLTIP_LEN {
+                break;
+            }
+            len += c.encode_utf16(&mut data.szTip[len..]).len();
+        }
+        if unsafe { Shell_NotifyIconW(NIM_MODIFY, &mut data) } == FALSE {
+            return Err(SystrayError::OsError("Failed to set the tooltip".into()));
+        }
+        Ok(())
+    }
+
+    // systray only has tooltips on Windows
+    #[cfg(not(windows))]
+    pub fn set(&self, _text: &str) -> Result<(), SystrayError> {
+        Ok(())
+    }
 }
 
-fn initialize_application() -> Result<Arc<Mutex<Application>>, SystrayError> {
-    Ok(Arc::new(Mutex::new(Application::new()?)))
+pub fn create_tray_icon(running: &Arc<AtomicBool>) -> Result<TrayTooltip, Box<dyn Error>> {
+    let mut app = Application::new()?;
+    set_icon_and_tooltip(&app)?;
+
+    add_menu_items(&mut app, running)?;
+    let tooltip = TrayTooltip::find();
+    spawn_message_listener(app);
+
+    Ok(tooltip)
 }
 
-fn set_icon_and_tooltip(app: &Arc<Mutex<Application>>) -> Result<(), Box<dyn Error>> {
+fn set_icon_and_tooltip(app: &Application) -> Result<(), Box<dyn Error>> {
     let temp_icon_path = create_temp_icon_file()?;
-    let app = app.lock().unwrap();
     app.set_icon_from_file(temp_icon_path.to_str().unwrap())?;
     app.set_tooltip(&format!("discord-imhex v{}", VERSION))?;
     Ok(())
 }
 
+// Shows the Discord connection state next to the version in the tooltip
+pub fn show_connection_state(tooltip: &TrayTooltip, state: &str) -> Result<(), SystrayError> {
+    tooltip.set(&format!("discord-imhex v{} (Discord: {})", VERSION, state))
+}
+
+// Finds systray's window among the ones of this process
+#[cfg(windows)]
+fn find_tray_window() -> Option<HWND> {
+    let class: Vec<u16> = TRAY_WINDOW_CLASS.encode_utf16().chain(Some(0)).collect();
+    let mut window: HWND = ptr::null_mut();
+    loop {
+        window = unsafe { FindWindowExW(ptr::null_mut(), window, class.as_ptr(), ptr::null()) };
+        if window.is_null() {
+            return None;
+        }
+        let mut pid = 0;
+        unsafe { GetWindowThreadProcessId(window, &mut pid) };
+        if pid == unsafe { GetCurrentProcessId() } {
+            return Some(window);
+        }
+    }
+}
+
 fn create_temp_icon_file() -> Result<PathBuf, Box<dyn Error>> {
     let mut temp_icon_path = env::temp_dir();
     temp_icon_path.push("icon.ico");
@@ -43,8 +135,7 @@ fn create_temp_icon_file() -> Result<PathBuf, Box<dyn Error>> {
     Ok(temp_icon_path)
 }
 
-fn add_menu_items(app: &Arc<Mutex<Application>>, running: &Arc<AtomicBool>) -> Result<(), Box<dyn Error>> {
-    let mut app = app.lock().unwrap();
+fn add_menu_items(app: &mut Application, running: &Arc<AtomicBool>) -> Result<(), Box<dyn Error>> {
     app.add_menu_item(&format!("discord-imhex v{}", VERSION), |_| {
         open::that("https://github.com/0xSolanaceae/discord-imhex").map_err(|_| SystrayError::OsError("Failed to open URL".into()))
     })?;
@@ -67,9 +158,10 @@ fn add_menu_items(app: &Arc<Mutex<Application>>, running: &Arc<AtomicBool>) -> R
     Ok(())
 }
 
-fn spawn_message_listener(app: Arc<Mutex<Application>>) {
+// wait_for_message never returns, so the listener keeps the Application to itself
+fn spawn_message_listener(mut app: Application) {
     thread::spawn(move || {
-        app.lock().unwrap().wait_for_message().expect("Failed to wait for message");
+        app.wait_for_message().expect("Failed to wait for message");
     });
 }
 
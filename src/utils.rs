@@ -11,4 +11,28 @@ pub fn get_current_timestamp() -> i64 {
 pub fn current_timestamp() -> String {
     let now: DateTime<Local> = Local::now();
     now.format("%Y-%m-%d %H:%M:%S").to_string()
-}
\ No newline at end of file
+}
+
+// Formats a byte count like "512 B" or "1.5 MiB"
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+// Formats whole minutes like "5m" or "1h 05m", so the text only changes once a minute
+pub fn format_elapsed(secs: u64) -> String {
+    let minutes = secs / 60;
+    match minutes / 60 {
+        0 => format!("{}m", minutes),
+        hours => format!("{}h {:02}m", hours, minutes % 60),
+    }
+}
@@ -0,0 +1,215 @@
+use std::env;
+use std::io::{self, Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde_json::Value;
+use thiserror::Error;
+
+// Header every i3/sway IPC message starts with
+const I3_IPC_MAGIC: &[u8; 6] = b"i3-ipc";
+const I3_IPC_GET_TREE: u32 = 4;
+
+// Replies are small, anything bigger means we are not talking to a compositor
+const MAX_REPLY_LEN: usize = 64 * 1024 * 1024;
+const IPC_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Error)]
+pub enum WaylandError {
+    #[error("Compositor IPC failed: {0}")]
+    Io(#[from] io::Error),
+    #[error("Invalid compositor reply: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Unexpected compositor reply: {0}")]
+    Protocol(String),
+}
+
+// A toplevel window as the compositor reports it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompositorWindow {
+    pub title: String,
+    pub pid: Option<u32>,
+    pub focused: bool,
+}
+
+fn connect(socket_path: &Path) -> Result<UnixStream, WaylandError> {
+    let stream = UnixStream::connect(socket_path)?;
+    stream.set_read_timeout(Some(IPC_TIMEOUT))?;
+    stream.set_write_timeout(Some(IPC_TIMEOUT))?;
+    Ok(stream)
+}
+
+fn json_pid(value: &Value) -> Option<u32> {
+    value.as_u64().and_then(|pid| u32::try_from(pid).ok()).filter(|&pid| pid > 0)
+}
+
+// sway's i3-compatible IPC socket, named by $SWAYSOCK
+pub struct SwayIpc {
+    socket_path: PathBuf,
+}
+
+impl SwayIpc {
+    pub fn new(socket_path: &Path) -> Self {
+        Self { socket_path: socket_path.to_path_buf() }
+    }
+
+    // Sends one message && reads the matching reply payload
+    fn request(&self, message_type: u32, payload: &[u8]) -> Result<Vec<u8>, WaylandError> {
+        let mut stream = connect(&self.socket_path)?;
+        let mut message = Vec::with_capacity(14 + payload.len());
+        message.extend_from_slice(I3_IPC_MAGIC);
+        message.extend_from_slice(&(payload.len() as u32).to_ne_bytes());
+        message.extend_from_slice(&message_type.to_ne_bytes());
+        message.extend_from_slice(payload);
+        stream.write_all(&message)?;
+
+        let mut header = [0u8; 14];
+        stream.read_exact(&mut header)?;
+        if &header[..6] != I3_IPC_MAGIC {
+            return Err(WaylandError::Protocol("missing i3-ipc magic".to_string()));
+        }
+        let length = u32::from_ne_bytes(header[6..10].try_into().unwrap()) as usize;
+        let reply_type = u32::from_ne_bytes(header[10..14].try_into().unwrap());
+        if reply_type != message_type {
+            return Err(WaylandError::Protocol(format!("expected reply type {}, got {}", message_type, reply_type)));
+        }
+        if length > MAX_REPLY_LEN {
+            return Err(WaylandError::Protocol(format!("reply of {} bytes is too large", length)));
+        }
+        let mut reply = vec![0u8; length];
+        stream.read_exact(&mut reply)?;
+        Ok(reply)
+    }
+
+    // Gets every window from the layout tree, most recently focused first
+    pub fn windows(&self) -> Result<Vec<CompositorWindow>, WaylandError> {
+        let tree: Value = serde_json::from_slice(&self.request(I3_IPC_GET_TREE, b"")?)?;
+        let mut windows = Vec::new();
+        collect_sway_windows(&tree, &mut windows);
+        Ok(windows)
+    }
+}
+
+// Walks the tree in each container's focus order, so recently focused windows come first
+fn collect_sway_windows(node: &Value, windows: &mut Vec<CompositorWindow>) {
+    let mut children: Vec<&Value> = ["nodes", "floating_nodes"]
+        .iter()
+        .filter_map(|key| node[key].as_array())
+        .flatten()
+        .collect();
+
+    if children.is_empty() {
+        // Leaves with a pid or app id are windows, empty workspaces are not
+        let is_window = !node["pid"].is_null() || !node["app_id"].is_null() || !node["window"].is_null();
+        if let (true, Some(title)) = (is_window, node["name"].as_str()) {
+            windows.push(CompositorWindow {
+                title: title.to_string(),
+                pid: json_pid(&node["pid"]),
+                focused: node["focused"].as_bool().unwrap_or(false),
+            });
+        }
+        return;
+    }
+
+    let focus: Vec<u64> = node["focus"].as_array().map_or_else(Vec::new, |ids| ids.iter().filter_map(Value::as_u64).collect());
+    children.sort_by_key(|child| child["id"].as_u64().and_then(|id| focus.iter().position(|&focused| focused == id)).unwrap_or(usize::MAX));
+    for child in children {
+        collect_sway_windows(child, windows);
+    }
+}
+
+// Hyprland's request socket, one command per connection
+pub struct HyprlandIpc {
+    socket_path: PathBuf,
+}
+
+impl HyprlandIpc {
+    pub fn new(socket_path: &Path) -> Self {
+        Self { socket_path: socket_path.to_path_buf() }
+    }
+
+    // Finds the socket of the instance named by $HYPRLAND_INSTANCE_SIGNATURE, newer versions keep it under $XDG_RUNTIME_DIR
+    pub fn socket_path(signature: &str, runtime_dir: Option<&Path>) -> PathBuf {
+        let candidates = runtime_dir.map(|dir| dir.join("hypr")).into_iter().chain([PathBuf::from("/tmp/hypr")]);
+        let candidates: Vec<PathBuf> = candidates.map(|dir| dir.join(signature).join(".socket.sock")).collect();
+        candidates.iter().find(|path| path.exists()).unwrap_or(&candidates[0]).clone()
+    }
+
+    fn request(&self, command: &str) -> Result<Value, WaylandError> {
+        let mut stream = connect(&self.socket_path)?;
+        stream.write_all(command.as_bytes())?;
+        let mut reply = Vec::new();
+        stream.take(MAX_REPLY_LEN as u64).read_to_end(&mut reply)?;
+        Ok(serde_json::from_slice(&reply)?)
+    }
+
+    // Gets every mapped client, most recently focused first
+    pub fn windows(&self) -> Result<Vec<CompositorWindow>, WaylandError> {
+        let clients = self.request("j/clients")?;
+        let clients = clients.as_array().ok_or_else(|| WaylandError::Protocol("clients is not a list".to_string()))?;
+
+        let mut windows: Vec<(i64, CompositorWindow)> = clients
+            .iter()
+            .filter(|client| client["mapped"].as_bool().unwrap_or(true))
+            .filter_map(|client| {
+                // focusHistoryID counts up from 0 for the focused window, -1 when never focused
+                let history = client["focusHistoryID"].as_i64().filter(|&id| id >= 0).unwrap_or(i64::MAX);
+                let window = CompositorWindow {
+                    title: client["title"].as_str()?.to_string(),
+                    pid: json_pid(&client["pid"]),
+                    focused: history == 0,
+                };
+                Some((history, window))
+            })
+            .collect();
+        windows.sort_by_key(|(history, _)| *history);
+        Ok(windows.into_iter().map(|(_, window)| window).collect())
+    }
+}
+
+// Wayland compositor whose IPC lists windows
+pub enum Compositor {
+    Sway(SwayIpc),
+    Hyprland(HyprlandIpc),
+}
+
+impl Compositor {
+    // Picks the compositor from the session's environment
+    pub fn from_env() -> Option<Self> {
+        Self::detect(|name| env::var(name).ok())
+    }
+
+    // Picks the compositor from environment variables looked up through var
+    pub fn detect<F>(var: F) -> Option<Self>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let var = |name: &str| var(name).filter(|value| !value.is_empty());
+        if let Some(signature) = var("HYPRLAND_INSTANCE_SIGNATURE") {
+            let runtime_dir = var("XDG_RUNTIME_DIR").map(PathBuf::from);
+            return Some(Compositor::Hyprland(HyprlandIpc::new(&HyprlandIpc::socket_path(&signature, runtime_dir.as_deref()))));
+        }
+        // i3 on X11 sets I3SOCK too, only sway's own variable means a Wayland session
+        var("SWAYSOCK").map(|socket| Compositor::Sway(SwayIpc::new(Path::new(&socket))))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Compositor::Sway(_) => "sway",
+            Compositor::Hyprland(_) => "Hyprland",
+        }
+    }
+
+    pub fn windows(&self) -> Result<Vec<CompositorWindow>, WaylandError> {
+        match self {
+            Compositor::Sway(sway) => sway.windows(),
+            Compositor::Hyprland(hyprland) => hyprland.windows(),
+        }
+    }
+
+    // Gets the owning process of the focused window, None when nothing is focused
+    pub fn focused_pid(&self) -> Result<Option<u32>, WaylandError> {
+        Ok(self.windows()?.into_iter().find(|window| window.focused).and_then(|window| window.pid))
+    }
+}
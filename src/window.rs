@@ -0,0 +1,71 @@
+use crate::title::{parse_window_title, ImhexWindowState};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImhexWindow {
+    // Owning process, None when the platform does not report it
+    pub pid: Option<u32>,
+    pub title: String,
+    pub state: ImhexWindowState,
+    pub focused: bool,
+}
+
+impl ImhexWindow {
+    // Builds a window from its raw title, None if it does not belong to ImHex
+    pub fn from_title(title: &str, pid: Option<u32>, focused: bool) -> Option<Self> {
+        let state = parse_window_title(title)?;
+        Some(Self { pid, title: title.to_string(), state, focused })
+    }
+}
+
+// Every ImHex window on the desktop, most recently active first
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImhexWindows {
+    windows: Vec<ImhexWindow>,
+}
+
+impl ImhexWindows {
+    pub fn new(windows: Vec<ImhexWindow>) -> Self {
+        Self { windows }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.windows.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.windows.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ImhexWindow> {
+        self.windows.iter()
+    }
+
+    // Gets the window the presence should describe: the focused one, else the most recent
+    pub fn primary(&self) -> Option<&ImhexWindow> {
+        self.windows.iter().find(|window| window.focused).or_else(|| self.windows.first())
+    }
+
+    // Gets the distinct names of everything opened across all windows
+    pub fn open_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = Vec::new();
+        for name in self.windows.iter().filter_map(|window| window.state.display_name()) {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        names
+    }
+
+    // Gets the number of distinct ImHex processes owning a window
+    pub fn instance_count(&self) -> usize {
+        let mut pids: Vec<u32> = self.windows.iter().filter_map(|window| window.pid).collect();
+        pids.sort_unstable();
+        pids.dedup();
+        pids.len().max(usize::from(!self.windows.is_empty()))
+    }
+
+    // Drops windows owned by a process that is not ImHex, e.g. a browser tab titled "ImHex - ..."
+    pub fn retain_processes(&mut self, pids: &[u32]) {
+        self.windows.retain(|window| window.pid.is_none_or(|pid| pids.contains(&pid)));
+    }
+}
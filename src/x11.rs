@@ -0,0 +1,318 @@
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use thiserror::Error;
+
+use x11rb::connection::Connection;
+use x11rb::errors::{ConnectError, ConnectionError, ReplyError, ReplyOrIdError};
+use x11rb::protocol::screensaver::ConnectionExt as _;
+use x11rb::protocol::xproto::{
+    Atom, AtomEnum, ChangeWindowAttributesAux, ClientMessageEvent, ConnectionExt, CreateWindowAux, EventMask,
+    Window, WindowClass,
+};
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+
+#[derive(Debug, Error)]
+pub enum X11Error {
+    #[error("Failed to connect to X server: {0}")]
+    Connect(#[from] ConnectError),
+    #[error("X11 connection error: {0}")]
+    Connection(#[from] ConnectionError),
+    #[error("X11 request failed: {0}")]
+    Reply(#[from] ReplyError),
+    #[error("X11 request failed: {0}")]
+    ReplyOrId(#[from] ReplyOrIdError),
+}
+
+struct Atoms {
+    net_client_list: Atom,
+    net_client_list_stacking: Atom,
+    net_active_window: Atom,
+    net_wm_name: Atom,
+    net_wm_pid: Atom,
+    utf8_string: Atom,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientWindow {
+    pub window: Window,
+    pub title: String,
+    pub pid: Option<u32>,
+    pub active: bool,
+}
+
+pub struct X11Session {
+    conn: RustConnection,
+    root: Window,
+    atoms: Atoms,
+}
+
+impl X11Session {
+    // Connects to the display named by $DISPLAY
+    pub fn connect() -> Result<Self, X11Error> {
+        let (conn, screen_num) = x11rb::connect(None)?;
+        let root = conn.setup().roots[screen_num].root;
+        let atoms = Atoms {
+            net_client_list: intern_atom(&conn, b"_NET_CLIENT_LIST")?,
+            net_client_list_stacking: intern_atom(&conn, b"_NET_CLIENT_LIST_STACKING")?,
+            net_active_window: intern_atom(&conn, b"_NET_ACTIVE_WINDOW")?,
+            net_wm_name: intern_atom(&conn, b"_NET_WM_NAME")?,
+            net_wm_pid: intern_atom(&conn, b"_NET_WM_PID")?,
+            utf8_string: intern_atom(&conn, b"UTF8_STRING")?,
+        };
+        Ok(Self { conn, root, atoms })
+    }
+
+    // Gets the top-level client windows, most recently active first
+    pub fn client_windows(&self) -> Result<Vec<Window>, X11Error> {
+        // The stacking list runs bottom to top, so reverse it
+        let mut windows = self.root_windows(self.atoms.net_client_list_stacking)?;
+        if !windows.is_empty() {
+            windows.reverse();
+            return Ok(windows);
+        }
+
+        let windows = self.root_windows(self.atoms.net_client_list)?;
+        if !windows.is_empty() {
+            return Ok(windows);
+        }
+
+        // No EWMH window manager (e.g. bare Xvfb), walk the root children instead
+        let mut children = self.conn.query_tree(self.root)?.reply()?.children;
+        children.reverse();
+        Ok(children)
+    }
+
+    // Gets the focused window from _NET_ACTIVE_WINDOW
+    pub fn active_window(&self) -> Result<Option<Window>, X11Error> {
+        Ok(self.root_windows(self.atoms.net_active_window)?.into_iter().find(|&window| window != 0))
+    }
+
+    // Gets the time since the last keyboard or mouse input from the XScreenSaver extension
+    pub fn idle_time(&self) -> Result<Duration, X11Error> {
+        let info = self.conn.screensaver_query_info(self.root)?.reply()?;
+        Ok(Duration::from_millis(u64::from(info.ms_since_user_input)))
+    }
+
+    // Gets the owning process of a window from _NET_WM_PID
+    pub fn window_pid(&self, window: Window) -> Result<Option<u32>, X11Error> {
+        let reply = self
+            .conn
+            .get_property(false, window, self.atoms.net_wm_pid, AtomEnum::CARDINAL, 0, 1)?
+            .reply()?;
+        Ok(reply.value32().and_then(|mut values| values.next()))
+    }
+
+    fn root_windows(&self, property: Atom) -> Result<Vec<Window>, X11Error> {
+        let reply = self
+            .conn
+            .get_property(false, self.root, property, AtomEnum::WINDOW, 0, u32::MAX)?
+            .reply()?;
+        Ok(reply.value32().map(|windows| windows.collect()).unwrap_or_default())
+    }
+
+    // Reads a window title from _NET_WM_NAME, falling back to WM_NAME
+    pub fn window_title(&self, window: Window) -> Result<Option<String>, X11Error> {
+        let net_wm_name = self
+            .conn
+            .get_property(false, window, self.atoms.net_wm_name, self.atoms.utf8_string, 0, 1024)?
+            .reply()?;
+        if !net_wm_name.value.is_empty() {
+            return Ok(Some(String::from_utf8_lossy(&net_wm_name.value).into_owned()));
+        }
+
+        let wm_name = self
+            .conn
+            .get_property(false, window, AtomEnum::WM_NAME, AtomEnum::ANY, 0, 1024)?
+            .reply()?;
+        if !wm_name.value.is_empty() {
+            // WM_NAME is usually Latin-1 encoded STRING
+            return Ok(Some(wm_name.value.iter().map(|&b| b as char).collect()));
+        }
+
+        Ok(None)
+    }
+
+    // Gets every client window whose title matches the filter, most recently active first
+    pub fn find_windows<F>(&self, filter: F) -> Result<Vec<ClientWindow>, X11Error>
+    where
+        F: Fn(&str) -> bool,
+    {
+        let active = self.active_window()?;
+        let mut found = Vec::new();
+        for window in self.client_windows()? {
+            // Windows can disappear between listing and querying them
+            let title = match self.window_title(window) {
+                Ok(Some(title)) if filter(&title) => title,
+                Ok(_) | Err(X11Error::Reply(ReplyError::X11Error(_))) => continue,
+                Err(e) => return Err(e),
+            };
+            let pid = match self.window_pid(window) {
+                Ok(pid) => pid,
+                Err(X11Error::Reply(ReplyError::X11Error(_))) => continue,
+                Err(e) => return Err(e),
+            };
+            found.push(ClientWindow { window, title, pid, active: active == Some(window) });
+        }
+        Ok(found)
+    }
+}
+
+fn intern_atom(conn: &RustConnection, name: &[u8]) -> Result<Atom, X11Error> {
+    Ok(conn.intern_atom(false, name)?.reply()?.atom)
+}
+
+// Property that changed on the display, which can alter the presence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowEvent {
+    Title(Window),
+    ActiveWindow,
+    ClientList,
+}
+
+// Properties whose PropertyNotify events the watcher reports
+#[derive(Clone, Copy)]
+struct WatchedAtoms {
+    net_active_window: Atom,
+    net_client_list: Atom,
+    net_client_list_stacking: Atom,
+    net_wm_name: Atom,
+}
+
+impl WatchedAtoms {
+    fn classify(&self, root: Window, window: Window, atom: Atom) -> Option<WindowEvent> {
+        if window == root {
+            if atom == self.net_active_window {
+                Some(WindowEvent::ActiveWindow)
+            } else if atom == self.net_client_list || atom == self.net_client_list_stacking {
+                Some(WindowEvent::ClientList)
+            } else {
+                None
+            }
+        } else if atom == self.net_wm_name || atom == u32::from(AtomEnum::WM_NAME) {
+            Some(WindowEvent::Title(window))
+        } else {
+            None
+        }
+    }
+}
+
+// Subscribes to PropertyNotify on the root && the watched windows, reading events on a background thread
+pub struct X11Watcher {
+    conn: Arc<RustConnection>,
+    // Unmapped window of our own, used to wake the event thread on drop
+    wake_window: Window,
+    watched: HashSet<Window>,
+    stop: Arc<AtomicBool>,
+    events: Receiver<Result<WindowEvent, ConnectionError>>,
+}
+
+impl X11Watcher {
+    // Connects to the display named by $DISPLAY && starts listening for focus && client list changes
+    pub fn connect() -> Result<Self, X11Error> {
+        let (conn, screen_num) = x11rb::connect(None)?;
+        let root = conn.setup().roots[screen_num].root;
+        let atoms = WatchedAtoms {
+            net_active_window: intern_atom(&conn, b"_NET_ACTIVE_WINDOW")?,
+            net_client_list: intern_atom(&conn, b"_NET_CLIENT_LIST")?,
+            net_client_list_stacking: intern_atom(&conn, b"_NET_CLIENT_LIST_STACKING")?,
+            net_wm_name: intern_atom(&conn, b"_NET_WM_NAME")?,
+        };
+
+        let property_changes = ChangeWindowAttributesAux::new().event_mask(EventMask::PROPERTY_CHANGE);
+        conn.change_window_attributes(root, &property_changes)?.check()?;
+        let wake_window = conn.generate_id()?;
+        conn.create_window(0, wake_window, root, 0, 0, 1, 1, 0, WindowClass::INPUT_ONLY, 0, &CreateWindowAux::new())?
+            .check()?;
+
+        let conn = Arc::new(conn);
+        let stop = Arc::new(AtomicBool::new(false));
+        let (sender, events) = mpsc::channel();
+        let (thread_conn, thread_stop) = (Arc::clone(&conn), Arc::clone(&stop));
+        thread::spawn(move || {
+            while !thread_stop.load(Ordering::Relaxed) {
+                let event = match thread_conn.wait_for_event() {
+                    Ok(Event::PropertyNotify(event)) => atoms.classify(root, event.window, event.atom),
+                    Ok(_) => None,
+                    Err(e) => {
+                        let _ = sender.send(Err(e));
+                        return;
+                    }
+                };
+                if let Some(event) = event {
+                    if sender.send(Ok(event)).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+
+        Ok(Self { conn, wake_window, watched: HashSet::new(), stop, events })
+    }
+
+    // Subscribes to title changes of these windows && unsubscribes from the rest
+    pub fn watch_windows(&mut self, windows: &[Window]) -> Result<(), X11Error> {
+        let subscribe = ChangeWindowAttributesAux::new().event_mask(EventMask::PROPERTY_CHANGE);
+        let new_windows: Vec<Window> = windows.iter().copied().filter(|window| !self.watched.contains(window)).collect();
+        for window in new_windows {
+            match self.conn.change_window_attributes(window, &subscribe)?.check() {
+                Ok(()) => {
+                    self.watched.insert(window);
+                }
+                // The window closed before we could subscribe
+                Err(ReplyError::X11Error(_)) => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        let unsubscribe = ChangeWindowAttributesAux::new().event_mask(EventMask::NO_EVENT);
+        let gone: Vec<Window> = self.watched.iter().copied().filter(|window| !windows.contains(window)).collect();
+        for window in gone {
+            self.watched.remove(&window);
+            // Fails harmlessly when the window no longer exists
+            self.conn.change_window_attributes(window, &unsubscribe)?;
+        }
+        self.conn.flush()?;
+        Ok(())
+    }
+
+    pub fn is_watching(&self, window: Window) -> bool {
+        self.watched.contains(&window)
+    }
+
+    // Waits for the next change, None if the timeout passed first
+    pub fn wait(&self, timeout: Duration) -> Result<Option<WindowEvent>, X11Error> {
+        match self.events.recv_timeout(timeout) {
+            Ok(Ok(event)) => Ok(Some(event)),
+            Ok(Err(e)) => Err(e.into()),
+            Err(RecvTimeoutError::Timeout) => Ok(None),
+            Err(RecvTimeoutError::Disconnected) => Err(ConnectionError::UnknownError.into()),
+        }
+    }
+
+    // Discards events that are already queued, so a burst of changes only wakes the loop once
+    pub fn drain(&self) -> Result<usize, X11Error> {
+        let mut drained = 0;
+        while let Ok(event) = self.events.try_recv() {
+            event?;
+            drained += 1;
+        }
+        Ok(drained)
+    }
+}
+
+impl Drop for X11Watcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        // Client messages to our own window reach only us, which unblocks wait_for_event
+        let wake = ClientMessageEvent::new(32, self.wake_window, AtomEnum::NONE, [0u32; 5]);
+        let _ = self.conn.send_event(false, self.wake_window, EventMask::NO_EVENT, wake);
+        let _ = self.conn.destroy_window(self.wake_window);
+        let _ = self.conn.flush();
+    }
+}
@@ -0,0 +1,80 @@
+#[path = "../src/activity.rs"]
+mod activity;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use activity::{ActivityDetector, UserActivity};
+    use std::time::{Duration, Instant};
+
+    const TIMEOUT: Duration = Duration::from_secs(300);
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn test_idle_time_reaches_timeout() {
+        let mut detector = ActivityDetector::new(Some(TIMEOUT));
+        let now = Instant::now();
+
+        assert_eq!(detector.update(Some(true), Some(secs(299)), now), UserActivity::Active);
+        assert_eq!(detector.update(Some(true), Some(secs(300)), now), UserActivity::Away);
+        assert_eq!(detector.update(Some(true), Some(secs(1)), now), UserActivity::Active);
+    }
+
+    #[test]
+    fn test_background_time_reaches_timeout() {
+        let mut detector = ActivityDetector::new(Some(TIMEOUT));
+        let start = Instant::now();
+
+        assert_eq!(detector.update(Some(true), Some(secs(0)), start), UserActivity::Active);
+        assert_eq!(detector.update(Some(false), Some(secs(0)), start + secs(120)), UserActivity::Active);
+        assert_eq!(detector.update(Some(false), Some(secs(0)), start + secs(300)), UserActivity::Paused);
+
+        // Switching back to ImHex restarts the clock
+        assert_eq!(detector.update(Some(true), Some(secs(0)), start + secs(310)), UserActivity::Active);
+        assert_eq!(detector.update(Some(false), Some(secs(0)), start + secs(400)), UserActivity::Active);
+    }
+
+    #[test]
+    fn test_idle_time_wins_over_background_time() {
+        let mut detector = ActivityDetector::new(Some(TIMEOUT));
+        let start = Instant::now();
+
+        assert_eq!(detector.update(Some(true), Some(secs(0)), start), UserActivity::Active);
+        assert_eq!(detector.update(Some(false), Some(secs(299)), start + secs(300)), UserActivity::Paused);
+        assert_eq!(detector.update(Some(false), Some(secs(300)), start + secs(300)), UserActivity::Away);
+    }
+
+    #[test]
+    fn test_never_focused_counts_from_first_update() {
+        let mut detector = ActivityDetector::new(Some(TIMEOUT));
+        let start = Instant::now();
+
+        assert_eq!(detector.update(Some(false), None, start), UserActivity::Active);
+        assert_eq!(detector.update(Some(false), None, start + secs(300)), UserActivity::Paused);
+
+        detector.reset();
+        assert_eq!(detector.update(Some(false), None, start + secs(301)), UserActivity::Active);
+    }
+
+    #[test]
+    fn test_unknown_focus_only_uses_idle_time() {
+        let mut detector = ActivityDetector::new(Some(TIMEOUT));
+        let start = Instant::now();
+
+        assert_eq!(detector.update(None, Some(secs(5)), start), UserActivity::Active);
+        assert_eq!(detector.update(None, Some(secs(5)), start + secs(3600)), UserActivity::Active);
+        assert_eq!(detector.update(None, Some(secs(3600)), start + secs(3600)), UserActivity::Away);
+    }
+
+    #[test]
+    fn test_disabled_timeout_is_always_active() {
+        let mut detector = ActivityDetector::new(None);
+        let start = Instant::now();
+
+        assert_eq!(detector.update(Some(false), Some(secs(86_400)), start), UserActivity::Active);
+        assert_eq!(detector.update(Some(false), Some(secs(86_400)), start + secs(86_400)), UserActivity::Active);
+    }
+}
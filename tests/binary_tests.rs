@@ -0,0 +1,299 @@
+#[path = "../src/binary.rs"]
+mod binary;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use binary::{inspect, inspect_file, BinaryError, BinaryFormat, BinarySummary, Endianness};
+    use std::fs;
+    use std::io::Cursor;
+    use tempfile::tempdir;
+
+    // Builds a little fixture binary by patching fields into a zeroed buffer
+    struct Fixture {
+        bytes: Vec<u8>,
+        big_endian: bool,
+    }
+
+    impl Fixture {
+        fn new(len: usize, big_endian: bool) -> Self {
+            Self { bytes: vec![0; len], big_endian }
+        }
+
+        fn raw(mut self, offset: usize, bytes: &[u8]) -> Self {
+            self.bytes[offset..offset + bytes.len()].copy_from_slice(bytes);
+            self
+        }
+
+        fn u16(self, offset: usize, value: u16) -> Self {
+            let bytes = if self.big_endian { value.to_be_bytes() } else { value.to_le_bytes() };
+            self.raw(offset, &bytes)
+        }
+
+        fn u32(self, offset: usize, value: u32) -> Self {
+            let bytes = if self.big_endian { value.to_be_bytes() } else { value.to_le_bytes() };
+            self.raw(offset, &bytes)
+        }
+
+        fn u64(self, offset: usize, value: u64) -> Self {
+            let bytes = if self.big_endian { value.to_be_bytes() } else { value.to_le_bytes() };
+            self.raw(offset, &bytes)
+        }
+
+        fn inspect(&self) -> Result<BinarySummary, BinaryError> {
+            inspect(&mut Cursor::new(&self.bytes))
+        }
+    }
+
+    // x86-64 executable with a null, .text && .symtab section
+    fn elf64_with_symbols() -> Fixture {
+        Fixture::new(64 + 3 * 64, false)
+            .raw(0, b"\x7fELF\x02\x01\x01")
+            .u16(16, 2)
+            .u16(18, 0x3E)
+            .u64(24, 0x401000)
+            .u64(0x28, 64)
+            .u16(0x3A, 64)
+            .u16(0x3C, 3)
+            .u32(64 + 64 + 4, 1)
+            .u32(64 + 128 + 4, 2)
+    }
+
+    fn pe(is_64: bool) -> Fixture {
+        let optional = 0x58;
+        let fixture = Fixture::new(0x200, false)
+            .raw(0, b"MZ")
+            .u32(0x3C, 0x40)
+            .raw(0x40, b"PE\0\0")
+            .u16(0x44, if is_64 { 0x8664 } else { 0x14C })
+            .u16(0x46, 4);
+        if is_64 {
+            fixture.u16(optional, 0x20B).u32(optional + 16, 0x1400).u64(optional + 24, 0x1_4000_0000).u32(optional + 108, 16)
+        } else {
+            fixture.u16(optional, 0x10B).u32(optional + 16, 0x1000).u32(optional + 28, 0x40_0000).u32(optional + 92, 16)
+        }
+    }
+
+    fn mach_o_64() -> Fixture {
+        let segment = 32;
+        let symtab = segment + 72 + 2 * 80;
+        let main = symtab + 24;
+        Fixture::new(main + 24, false)
+            .raw(0, b"\xCF\xFA\xED\xFE")
+            .u32(4, 0x0100_000C)
+            .u32(12, 2)
+            .u32(16, 3)
+            .u32(20, (main + 24 - 32) as u32)
+            .u32(segment, 0x19)
+            .u32(segment + 4, 72 + 2 * 80)
+            .u32(segment + 64, 2)
+            .u32(symtab, 0x2)
+            .u32(symtab + 4, 24)
+            .u32(symtab + 12, 10)
+            .u32(main, 0x8000_0028)
+            .u32(main + 4, 24)
+            .u64(main + 8, 0x3F40)
+    }
+
+    #[test]
+    fn test_elf64_executable() {
+        let summary = elf64_with_symbols().inspect().unwrap();
+        assert_eq!(
+            summary,
+            BinarySummary {
+                format: BinaryFormat::Elf,
+                architecture: "x86-64".to_string(),
+                bits: 64,
+                endianness: Endianness::Little,
+                entry_point: Some(0x401000),
+                stripped: false,
+                section_count: 3,
+            }
+        );
+        assert_eq!(summary.to_string(), "x86-64, 64-bit little-endian, entry 0x401000, not stripped, 3 sections");
+    }
+
+    #[test]
+    fn test_elf32_big_endian_stripped() {
+        let summary = Fixture::new(52 + 2 * 40, true)
+            .raw(0, b"\x7fELF\x01\x02\x01")
+            .u16(16, 2)
+            .u16(18, 0x08)
+            .u32(24, 0x8000_1000)
+            .u32(0x20, 52)
+            .u16(0x2E, 40)
+            .u16(0x30, 2)
+            .u32(52 + 40 + 4, 1)
+            .inspect()
+            .unwrap();
+
+        assert_eq!(summary.architecture, "MIPS");
+        assert_eq!(summary.bits, 32);
+        assert_eq!(summary.endianness, Endianness::Big);
+        assert_eq!(summary.entry_point, Some(0x8000_1000));
+        assert!(summary.stripped);
+        assert_eq!(summary.section_count, 2);
+    }
+
+    #[test]
+    fn test_elf_without_sections() {
+        let summary = Fixture::new(64, false).raw(0, b"\x7fELF\x02\x01\x01").u16(16, 1).u16(18, 0x1234).inspect().unwrap();
+        assert_eq!(summary.architecture, "machine 0x1234");
+        assert_eq!(summary.entry_point, None);
+        assert!(summary.stripped);
+        assert_eq!(summary.to_string(), "machine 0x1234, 64-bit little-endian, stripped, 0 sections");
+    }
+
+    #[test]
+    fn test_elf_extended_section_count() {
+        let summary = Fixture::new(64 + 2 * 64, false)
+            .raw(0, b"\x7fELF\x02\x01\x01")
+            .u16(18, 0xB7)
+            .u64(0x28, 64)
+            .u16(0x3A, 64)
+            .u64(64 + 32, 2)
+            .u32(64 + 64 + 4, 2)
+            .inspect()
+            .unwrap();
+        assert_eq!(summary.section_count, 2);
+        assert!(!summary.stripped);
+    }
+
+    #[test]
+    fn test_elf_huge_section_count_is_malformed() {
+        let elf = |count: u64| {
+            Fixture::new(64 + 64, false).raw(0, b"\x7fELF\x02\x01\x01").u16(18, 0x3E).u64(0x28, 64).u16(0x3A, 64).u64(64 + 32, count).inspect()
+        };
+        // Used to overflow the table size
+        assert!(matches!(elf(u64::MAX), Err(BinaryError::Malformed(_))));
+        assert!(matches!(elf(1 << 20), Err(BinaryError::Malformed(_))));
+    }
+
+    #[test]
+    fn test_elf_section_offset_overflow_is_malformed() {
+        // With no section count the size field of section 0 lies past e_shoff
+        let result = Fixture::new(64, false).raw(0, b"\x7fELF\x02\x01\x01").u16(18, 0x3E).u64(0x28, u64::MAX - 8).u16(0x3A, 64).inspect();
+        assert!(matches!(result, Err(BinaryError::Malformed(_))));
+    }
+
+    #[test]
+    fn test_pe32_plus() {
+        let summary = pe(true).inspect().unwrap();
+        assert_eq!(
+            summary,
+            BinarySummary {
+                format: BinaryFormat::Pe,
+                architecture: "x86-64".to_string(),
+                bits: 64,
+                endianness: Endianness::Little,
+                entry_point: Some(0x1_4000_1400),
+                stripped: true,
+                section_count: 4,
+            }
+        );
+
+        // A debug directory entry points at symbols
+        let with_debug = pe(true).u32(0x58 + 112 + 6 * 8 + 4, 0x1C).inspect().unwrap();
+        assert!(!with_debug.stripped);
+    }
+
+    #[test]
+    fn test_pe32_with_coff_symbols() {
+        let summary = pe(false).u32(0x4C, 0x800).u32(0x50, 12).inspect().unwrap();
+        assert_eq!(summary.architecture, "Intel 80386");
+        assert_eq!(summary.bits, 32);
+        assert_eq!(summary.entry_point, Some(0x40_1000));
+        assert!(!summary.stripped);
+    }
+
+    #[test]
+    fn test_pe_without_signature() {
+        let result = Fixture::new(0x80, false).raw(0, b"MZ").u32(0x3C, 0x40).inspect();
+        assert!(matches!(result, Err(BinaryError::Malformed(_))));
+    }
+
+    #[test]
+    fn test_mach_o_64() {
+        let summary = mach_o_64().inspect().unwrap();
+        assert_eq!(
+            summary,
+            BinarySummary {
+                format: BinaryFormat::MachO,
+                architecture: "ARM64".to_string(),
+                bits: 64,
+                endianness: Endianness::Little,
+                entry_point: Some(0x3F40),
+                stripped: false,
+                section_count: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_mach_o_32_big_endian_stripped() {
+        let summary = Fixture::new(28 + 56, true)
+            .raw(0, b"\xFE\xED\xFA\xCE")
+            .u32(4, 18)
+            .u32(12, 2)
+            .u32(16, 1)
+            .u32(20, 56)
+            .u32(28, 0x1)
+            .u32(28 + 4, 56)
+            .u32(28 + 48, 3)
+            .inspect()
+            .unwrap();
+        assert_eq!(summary.architecture, "PowerPC");
+        assert_eq!(summary.bits, 32);
+        assert_eq!(summary.endianness, Endianness::Big);
+        assert_eq!(summary.entry_point, None);
+        assert!(summary.stripped);
+        assert_eq!(summary.section_count, 3);
+    }
+
+    #[test]
+    fn test_universal_binary_uses_first_slice() {
+        let slice = mach_o_64().bytes;
+        let fat = Fixture::new(0x1000 + slice.len(), true)
+            .raw(0, b"\xCA\xFE\xBA\xBE")
+            .u32(4, 1)
+            .u32(8, 0x0100_000C)
+            .u32(16, 0x1000)
+            .u32(20, slice.len() as u32)
+            .raw(0x1000, &slice);
+        assert_eq!(fat.inspect().unwrap(), mach_o_64().inspect().unwrap());
+    }
+
+    #[test]
+    fn test_mach_o_bad_load_command() {
+        let result = Fixture::new(32 + 8, false).raw(0, b"\xCF\xFA\xED\xFE").u32(16, 1).u32(20, 8).u32(32 + 4, 4).inspect();
+        assert!(matches!(result, Err(BinaryError::Malformed(_))));
+    }
+
+    #[test]
+    fn test_mach_o_symbol_count_overflow_is_malformed() {
+        let symtab = |fixture: Fixture, offset: usize, count: u32| fixture.u32(offset, 0x2).u32(offset + 4, 24).u32(offset + 12, count);
+        let fixture = Fixture::new(32 + 2 * 24, false).raw(0, b"\xCF\xFA\xED\xFE").u32(16, 2).u32(20, 2 * 24);
+        let result = symtab(symtab(fixture, 32, u32::MAX), 32 + 24, 1).inspect();
+        assert!(matches!(result, Err(BinaryError::Malformed(_))));
+    }
+
+    #[test]
+    fn test_rejected_inputs() {
+        assert!(matches!(inspect(&mut Cursor::new(b"hello world")), Err(BinaryError::UnknownFormat)));
+        assert!(matches!(inspect(&mut Cursor::new(b"\x7fELF\x02\x01")), Err(BinaryError::Truncated)));
+        assert!(matches!(inspect(&mut Cursor::new(b"")), Err(BinaryError::Truncated)));
+        // Java class file, major version 52
+        let class = Fixture::new(16, true).raw(0, b"\xCA\xFE\xBA\xBE").u32(4, 52);
+        assert!(matches!(class.inspect(), Err(BinaryError::UnknownFormat)));
+    }
+
+    #[test]
+    fn test_inspect_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("a.out");
+        fs::write(&path, &elf64_with_symbols().bytes).unwrap();
+
+        assert_eq!(inspect_file(&path).unwrap().section_count, 3);
+        assert!(matches!(inspect_file(&dir.path().join("missing")), Err(BinaryError::Io(_))));
+    }
+}
@@ -0,0 +1,271 @@
+#[path = "../src/activity.rs"]
+#[allow(dead_code)]
+mod activity;
+#[path = "../src/binary.rs"]
+#[allow(dead_code)]
+mod binary;
+#[path = "../src/client.rs"]
+#[allow(dead_code)]
+mod client;
+#[path = "../src/connection.rs"]
+#[allow(dead_code)]
+mod connection;
+#[path = "../src/discord.rs"]
+#[allow(dead_code)]
+mod discord;
+#[path = "../src/entropy.rs"]
+#[allow(dead_code)]
+mod entropy;
+#[path = "../src/error.rs"]
+#[allow(dead_code)]
+mod error;
+#[path = "../src/format.rs"]
+#[allow(dead_code)]
+mod format;
+#[path = "../src/imhex_config.rs"]
+#[allow(dead_code)]
+mod imhex_config;
+#[path = "../src/network.rs"]
+#[allow(dead_code)]
+mod network;
+#[path = "../src/pattern.rs"]
+#[allow(dead_code)]
+mod pattern;
+#[path = "../src/presence.rs"]
+#[allow(dead_code)]
+mod presence;
+#[path = "../src/privacy.rs"]
+#[allow(dead_code)]
+mod privacy;
+#[path = "../src/probe.rs"]
+#[allow(dead_code)]
+mod probe;
+#[path = "../src/process.rs"]
+#[allow(dead_code)]
+mod process;
+#[path = "../src/project.rs"]
+#[allow(dead_code)]
+mod project;
+#[path = "../src/resolver.rs"]
+#[allow(dead_code)]
+mod resolver;
+#[path = "../src/scheduler.rs"]
+#[allow(dead_code)]
+mod scheduler;
+#[path = "../src/settings.rs"]
+#[allow(dead_code)]
+mod settings;
+#[path = "../src/template.rs"]
+#[allow(dead_code)]
+mod template;
+#[path = "../src/title.rs"]
+#[allow(dead_code)]
+mod title;
+#[path = "../src/utils.rs"]
+#[allow(dead_code)]
+mod utils;
+#[path = "../src/window.rs"]
+#[allow(dead_code)]
+mod window;
+#[cfg(unix)]
+#[path = "support/fake_discord.rs"]
+mod fake_discord;
+#[path = "support/scripted_probe.rs"]
+#[allow(dead_code)]
+mod scripted_probe;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use client::{create_activity, DiscordClient};
+    use presence::{ActivityAssets, ActivityButton, ActivityImage};
+    use serde_json::json;
+
+    const START: i64 = 1_700_000_000;
+
+    #[test]
+    fn test_create_activity() {
+        // Lines shorter than two characters are left out, Discord would refuse them
+        assert_eq!(create_activity("", "Idle", None, &ActivityAssets::default()), json!({ "details": "Idle" }));
+        assert_eq!(create_activity("x", "", None, &ActivityAssets::default()), json!({}));
+
+        let assets = ActivityAssets {
+            large_image: Some(image("format_image", "PNG image")),
+            small_image: Some(image("imhex", "Dark theme")),
+            buttons: vec![button("Source", "https://github.com/0xSolanaceae/discord-imhex")],
+        };
+        assert_eq!(
+            create_activity("Bytes: [None]", "Analyzing: [a.png]", Some(START), &assets),
+            json!({
+                "state": "Bytes: [None]",
+                "details": "Analyzing: [a.png]",
+                "timestamps": { "start": START },
+                "assets": { "large_image": "format_image", "large_text": "PNG image", "small_image": "imhex", "small_text": "Dark theme" },
+                "buttons": [{ "label": "Source", "url": "https://github.com/0xSolanaceae/discord-imhex" }],
+            })
+        );
+    }
+
+    fn image(key: &str, text: &str) -> ActivityImage {
+        ActivityImage { key: key.to_string(), text: text.to_string() }
+    }
+
+    fn button(label: &str, url: &str) -> ActivityButton {
+        ActivityButton { label: label.to_string(), url: url.to_string() }
+    }
+
+    #[test]
+    fn test_create_activity_respects_discord_limits() {
+        let assets = ActivityAssets {
+            large_image: Some(image("imhex", &"x".repeat(200))),
+            small_image: Some(image("theme", "x")),
+            buttons: vec![
+                button("", "https://a.example"),
+                button(&"b".repeat(40), "https://b.example"),
+                button("c", "https://c.example"),
+                button("d", "https://d.example"),
+            ],
+        };
+        let activity = create_activity("", "Idle", None, &assets);
+
+        assert_eq!(activity["assets"]["large_text"].as_str().unwrap().len(), 128);
+        assert_eq!(activity.get("state"), None);
+        // Texts shorter than two characters are left out, the image itself stays
+        assert_eq!(activity["assets"]["small_image"], "theme");
+        assert!(activity["assets"].get("small_text").is_none());
+        assert_eq!(activity["buttons"], json!([{ "label": "b".repeat(32), "url": "https://b.example" }, { "label": "c", "url": "https://c.example" }]));
+    }
+
+    #[test]
+    fn test_long_lines_are_truncated() {
+        let details = "é".repeat(200);
+        let activity = create_activity(&"s".repeat(129), &details, None, &ActivityAssets::default());
+        assert_eq!(activity["details"], "é".repeat(128));
+        assert_eq!(activity["state"], "s".repeat(128));
+    }
+
+    #[cfg(unix)]
+    mod end_to_end {
+        use super::*;
+        use error::AppError;
+        use discord::IpcConnection;
+        use fake_discord::{FakeDiscord, FAKE_USERNAME, FAKE_USER_ID};
+        use presence::{run_discord_loop, ActivitySink, AppState};
+        use scripted_probe::{ProbeFrame, ScriptedProbe};
+        use serde_json::Value;
+        use std::time::Duration;
+        use tempfile::tempdir;
+
+        const CLIENT_ID: &str = "1060827018196955177";
+
+        fn activity(state: &str, details: &str) -> Option<Value> {
+            Some(json!({ "state": state, "details": details, "timestamps": { "start": START } }))
+        }
+
+        // Runs the presence loop over the frames against the fake server, until the script is used up
+        fn run_session(discord: &FakeDiscord, frames: Vec<ProbeFrame>) {
+            let mut client = DiscordClient::connect_to(discord.path(), CLIENT_ID).unwrap();
+            let mut state = AppState::new();
+            let frames = frames.into_iter().map(ProbeFrame::with_window_event);
+            let mut probe = ScriptedProbe::new(frames).stop_when_exhausted(state.running.clone());
+            run_discord_loop(&mut probe, &mut client, &mut state, Duration::from_millis(1)).unwrap();
+        }
+
+        #[test]
+        fn test_handshake() {
+            let dir = tempdir().unwrap();
+            let discord = FakeDiscord::start(dir.path());
+            let connection = IpcConnection::connect_first(&[dir.path().join("discord-ipc-0")], CLIENT_ID).unwrap();
+            assert_eq!(connection.ready().username.as_deref(), Some(FAKE_USERNAME));
+            assert_eq!(connection.ready().user_id.as_deref(), Some(FAKE_USER_ID));
+            drop(connection);
+
+            assert_eq!(discord.handshakes(), vec![json!({ "v": 1, "client_id": CLIENT_ID })]);
+            assert!(discord.commands().is_empty());
+        }
+
+        #[test]
+        fn test_session_sequence() {
+            let dir = tempdir().unwrap();
+            let discord = FakeDiscord::start(dir.path());
+            run_session(
+                &discord,
+                vec![
+                    ProbeFrame::stopped(),
+                    ProbeFrame::running(42, START).with_window_title("ImHex"),
+                    ProbeFrame::running(42, START).with_window_title("ImHex - a.bin"),
+                    ProbeFrame::running(42, START).with_window_title("ImHex - a.bin"),
+                    ProbeFrame::running(42, START).with_window_title("ImHex - a.bin").with_selection(0x10, 32),
+                    ProbeFrame::stopped(),
+                ],
+            );
+
+            assert_eq!(
+                discord.activities(),
+                vec![
+                    // The empty state line is left out
+                    Some(json!({ "details": "Idle", "timestamps": { "start": START } })),
+                    activity("Bytes: [None]", "Analyzing: [a.bin]"),
+                    activity("Bytes: [0x10-0x2F (32 bytes)]", "Analyzing: [a.bin]"),
+                    None,
+                    None,
+                ]
+            );
+            let nonces: Vec<Value> = discord.commands().iter().map(|command| command["nonce"].clone()).collect();
+            assert_eq!(nonces, ["1", "2", "3", "4", "5"]);
+            assert!(discord.commands().iter().all(|command| command["args"]["pid"] == std::process::id()));
+        }
+
+        #[test]
+        fn test_file_format_sends_large_image() {
+            let dir = tempdir().unwrap();
+            let discord = FakeDiscord::start(dir.path());
+            let path = dir.path().join("logo.png");
+            std::fs::write(&path, b"\x89PNG\r\n\x1A\n\0\0\0\rIHDR").unwrap();
+
+            run_session(&discord, vec![ProbeFrame::running(42, START).with_window_title("ImHex - logo.png").with_file(path.to_str().unwrap(), 16)]);
+
+            let mut expected = activity("Bytes: [None]", "Analyzing PNG image: [logo.png]").unwrap();
+            expected["assets"] = json!({ "large_image": "format_image", "large_text": "PNG image" });
+            assert_eq!(discord.activities(), vec![Some(expected), None]);
+        }
+
+        #[test]
+        fn test_reconnect_starts_a_new_session() {
+            let dir = tempdir().unwrap();
+            let discord = FakeDiscord::start(dir.path());
+            let frames = || vec![ProbeFrame::running(42, START).with_window_title("ImHex - a.bin")];
+            run_session(&discord, frames());
+            // A fresh client does not know what the previous one sent, so the activity is sent again
+            run_session(&discord, frames());
+
+            let analyzing = activity("Bytes: [None]", "Analyzing: [a.bin]");
+            assert_eq!(discord.handshakes().len(), 2);
+            assert_eq!(discord.activities(), vec![analyzing.clone(), None, analyzing, None]);
+        }
+
+        #[test]
+        fn test_asset_changes_are_sent() {
+            let dir = tempdir().unwrap();
+            let discord = FakeDiscord::start(dir.path());
+            let mut client = DiscordClient::connect_to(discord.path(), CLIENT_ID).unwrap();
+            let dark = ActivityAssets { small_image: Some(image("theme", "Dark theme")), ..ActivityAssets::default() };
+            let light = ActivityAssets { small_image: Some(image("theme", "Light theme")), ..ActivityAssets::default() };
+
+            for assets in [dark.clone(), dark, light.clone(), light] {
+                client.update_activity("Bytes: [None]".to_string(), "Idle".to_string(), None, assets).unwrap();
+            }
+            drop(client);
+
+            let texts: Vec<Value> = discord.activities().into_iter().map(|activity| activity.unwrap()["assets"]["small_text"].clone()).collect();
+            assert_eq!(texts, ["Dark theme", "Light theme"]);
+        }
+
+        #[test]
+        fn test_missing_socket_is_a_discord_error() {
+            let dir = tempdir().unwrap();
+            let client = DiscordClient::connect_to(&dir.path().join("discord-ipc-0"), CLIENT_ID);
+            assert!(matches!(client, Err(AppError::Discord(_))));
+        }
+    }
+}
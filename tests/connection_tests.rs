@@ -0,0 +1,515 @@
+#[path = "../src/activity.rs"]
+#[allow(dead_code)]
+mod activity;
+#[path = "../src/binary.rs"]
+#[allow(dead_code)]
+mod binary;
+#[path = "../src/client.rs"]
+#[allow(dead_code)]
+mod client;
+#[path = "../src/connection.rs"]
+#[allow(dead_code)]
+mod connection;
+#[path = "../src/discord.rs"]
+#[allow(dead_code)]
+mod discord;
+#[path = "../src/entropy.rs"]
+#[allow(dead_code)]
+mod entropy;
+#[path = "../src/error.rs"]
+#[allow(dead_code)]
+mod error;
+#[path = "../src/format.rs"]
+#[allow(dead_code)]
+mod format;
+#[path = "../src/imhex_config.rs"]
+#[allow(dead_code)]
+mod imhex_config;
+#[path = "../src/network.rs"]
+#[allow(dead_code)]
+mod network;
+#[path = "../src/pattern.rs"]
+#[allow(dead_code)]
+mod pattern;
+#[path = "../src/presence.rs"]
+#[allow(dead_code)]
+mod presence;
+#[path = "../src/privacy.rs"]
+#[allow(dead_code)]
+mod privacy;
+#[path = "../src/probe.rs"]
+#[allow(dead_code)]
+mod probe;
+#[path = "../src/process.rs"]
+#[allow(dead_code)]
+mod process;
+#[path = "../src/project.rs"]
+#[allow(dead_code)]
+mod project;
+#[path = "../src/resolver.rs"]
+#[allow(dead_code)]
+mod resolver;
+#[path = "../src/scheduler.rs"]
+#[allow(dead_code)]
+mod scheduler;
+#[path = "../src/settings.rs"]
+#[allow(dead_code)]
+mod settings;
+#[path = "../src/template.rs"]
+#[allow(dead_code)]
+mod template;
+#[path = "../src/title.rs"]
+#[allow(dead_code)]
+mod title;
+#[path = "../src/utils.rs"]
+#[allow(dead_code)]
+mod utils;
+#[path = "../src/window.rs"]
+#[allow(dead_code)]
+mod window;
+#[cfg(unix)]
+#[path = "support/fake_discord.rs"]
+#[allow(dead_code)]
+mod fake_discord;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use connection::{describe_states, Backoff, BroadcastManager, Connection, ConnectionManager, ConnectionState, DETECT_INTERVAL, PING_INTERVAL};
+    use error::AppError;
+    use presence::{ActivityAssets, ActivitySink};
+    use scheduler::{Clock, VirtualClock};
+    use std::cell::RefCell;
+    use std::collections::{HashMap, VecDeque};
+    use std::path::{Path, PathBuf};
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    enum Call {
+        Connect,
+        Set(String),
+        Clear,
+        Ping,
+    }
+
+    // What the next call on a connection does
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Outcome {
+        Ok,
+        Rejected,
+        Dead,
+    }
+
+    // Connections && the connector share one script of outcomes && one call log
+    #[derive(Default)]
+    struct Script {
+        connects: VecDeque<bool>,
+        outcomes: VecDeque<Outcome>,
+        calls: Vec<Call>,
+    }
+
+    type Shared = Rc<RefCell<Script>>;
+
+    struct FakeConnection(Shared);
+
+    impl FakeConnection {
+        fn call(&mut self, call: Call) -> Result<(), AppError> {
+            let mut script = self.0.borrow_mut();
+            script.calls.push(call);
+            match script.outcomes.pop_front().unwrap_or(Outcome::Ok) {
+                Outcome::Ok => Ok(()),
+                Outcome::Rejected => Err(AppError::DiscordRejected("invalid activity".to_string())),
+                Outcome::Dead => Err(AppError::Discord("broken pipe".to_string())),
+            }
+        }
+    }
+
+    impl ActivitySink for FakeConnection {
+        fn update_activity(&mut self, _state: String, details: String, _start_time: Option<i64>, _assets: ActivityAssets) -> Result<(), AppError> {
+            self.call(Call::Set(details))
+        }
+
+        fn clear_activity(&mut self) -> Result<(), AppError> {
+            self.call(Call::Clear)
+        }
+    }
+
+    impl Connection for FakeConnection {
+        fn ping(&mut self) -> Result<(), AppError> {
+            self.call(Call::Ping)
+        }
+    }
+
+    struct Harness {
+        manager: ConnectionManager<Box<dyn FnMut() -> Result<FakeConnection, AppError>>, VirtualClock>,
+        clock: VirtualClock,
+        script: Shared,
+        states: Rc<RefCell<Vec<ConnectionState>>>,
+    }
+
+    impl Harness {
+        // Connection attempts succeed or fail in the given order, then succeed
+        fn new(connects: &[bool]) -> Self {
+            let script: Shared = Rc::new(RefCell::new(Script { connects: connects.iter().copied().collect(), ..Script::default() }));
+            let connector: Box<dyn FnMut() -> Result<FakeConnection, AppError>> = {
+                let script = script.clone();
+                Box::new(move || {
+                    script.borrow_mut().calls.push(Call::Connect);
+                    match script.borrow_mut().connects.pop_front().unwrap_or(true) {
+                        true => Ok(FakeConnection(script.clone())),
+                        false => Err(AppError::Discord("no Discord client found".to_string())),
+                    }
+                })
+            };
+            let clock = VirtualClock::new();
+            let states = Rc::new(RefCell::new(Vec::new()));
+            let manager = {
+                let states = states.clone();
+                ConnectionManager::with_clock(connector, clock.clone())
+                    .with_backoff(Backoff::new(Duration::from_secs(1), Duration::from_secs(60)).with_seed(7))
+                    .with_observer(move |state| states.borrow_mut().push(state))
+            };
+            Self { manager, clock, script, states }
+        }
+
+        // Lets the detector find a Discord client only while the flag is set, counting the scans
+        fn with_detector(mut self, running: Rc<RefCell<bool>>, scans: Rc<RefCell<u32>>) -> Self {
+            self.manager = self.manager.with_detector(move || {
+                *scans.borrow_mut() += 1;
+                running.borrow().then(|| "Discord Canary".to_string())
+            });
+            self
+        }
+
+        fn update(&mut self, details: &str) {
+            self.manager.update_activity(String::new(), details.to_string(), None, ActivityAssets::default()).unwrap();
+        }
+
+        fn fail_next(&self, outcome: Outcome) {
+            self.script.borrow_mut().outcomes.push_back(outcome);
+        }
+
+        fn calls(&self) -> Vec<Call> {
+            std::mem::take(&mut self.script.borrow_mut().calls)
+        }
+
+        fn states(&self) -> Vec<ConnectionState> {
+            std::mem::take(&mut self.states.borrow_mut())
+        }
+
+        // Moves the clock to the next connection attempt
+        fn advance_to_retry(&self) {
+            let retry_at = self.manager.retry_at().expect("a retry is scheduled");
+            self.clock.advance(retry_at.saturating_duration_since(self.clock.now()));
+        }
+    }
+
+    fn set(details: &str) -> Call {
+        Call::Set(details.to_string())
+    }
+
+    #[test]
+    fn test_backoff() {
+        let (first, max) = (Duration::from_secs(1), Duration::from_secs(60));
+        let mut backoff = Backoff::new(first, max).with_seed(42);
+        for attempt in 0..10 {
+            let ceiling = (first * 2u32.pow(attempt)).min(max);
+            let delay = backoff.next_delay();
+            assert!(delay >= ceiling / 2 && delay <= ceiling, "attempt {}: {:?}", attempt, delay);
+        }
+        assert_eq!(backoff.attempts(), 10);
+
+        backoff.reset();
+        assert!(backoff.next_delay() <= first);
+
+        // The same seed gives the same delays, different seeds spread them out
+        let delays = |seed| {
+            let mut backoff = Backoff::new(first, max).with_seed(seed);
+            (0..5).map(|_| backoff.next_delay()).collect::<Vec<_>>()
+        };
+        assert_eq!(delays(1), delays(1));
+        assert_ne!(delays(1), delays(2));
+    }
+
+    #[test]
+    fn test_connects_on_first_update() {
+        let mut harness = Harness::new(&[]);
+        assert_eq!(harness.manager.state(), ConnectionState::Disconnected);
+
+        harness.update("Analyzing: [a.bin]");
+        assert_eq!(harness.calls(), vec![Call::Connect, set("Analyzing: [a.bin]")]);
+        assert_eq!(harness.states(), vec![ConnectionState::Connecting, ConnectionState::Ready]);
+
+        harness.update("Analyzing: [b.bin]");
+        assert_eq!(harness.calls(), vec![set("Analyzing: [b.bin]")]);
+        assert!(harness.states().is_empty());
+    }
+
+    #[test]
+    fn test_retries_with_backoff_and_replays_the_latest_activity() {
+        let mut harness = Harness::new(&[false, false]);
+        harness.update("a.bin");
+        assert_eq!(harness.manager.state(), ConnectionState::Disconnected);
+        let first_retry = harness.manager.retry_at().unwrap().saturating_duration_since(harness.clock.now());
+        assert!(first_retry >= Duration::from_millis(500) && first_retry <= Duration::from_secs(1));
+
+        // Nothing is attempted before the retry is due
+        harness.update("b.bin");
+        harness.manager.poll().unwrap();
+        assert_eq!(harness.calls(), vec![Call::Connect]);
+
+        harness.advance_to_retry();
+        harness.manager.poll().unwrap();
+        let second_retry = harness.manager.retry_at().unwrap().saturating_duration_since(harness.clock.now());
+        assert!(second_retry >= Duration::from_secs(1) && second_retry <= Duration::from_secs(2));
+
+        harness.update("c.bin");
+        harness.advance_to_retry();
+        harness.manager.poll().unwrap();
+        assert_eq!(harness.calls(), vec![Call::Connect, Call::Connect, set("c.bin")]);
+        assert_eq!(harness.manager.state(), ConnectionState::Ready);
+        assert_eq!(harness.manager.retry_at(), None);
+        assert_eq!(
+            harness.states(),
+            vec![
+                ConnectionState::Connecting,
+                ConnectionState::Disconnected,
+                ConnectionState::Connecting,
+                ConnectionState::Disconnected,
+                ConnectionState::Connecting,
+                ConnectionState::Ready,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dead_socket_reconnects() {
+        let mut harness = Harness::new(&[]);
+        harness.update("a.bin");
+        harness.calls();
+
+        harness.fail_next(Outcome::Dead);
+        harness.update("b.bin");
+        assert_eq!(harness.manager.state(), ConnectionState::Disconnected);
+        assert!(harness.manager.connection().is_none());
+
+        harness.advance_to_retry();
+        harness.manager.poll().unwrap();
+        assert_eq!(harness.calls(), vec![set("b.bin"), Call::Connect, set("b.bin")]);
+        assert_eq!(harness.manager.state(), ConnectionState::Ready);
+    }
+
+    #[test]
+    fn test_rejected_activity_is_degraded() {
+        let mut harness = Harness::new(&[]);
+        harness.fail_next(Outcome::Rejected);
+        harness.update("bad");
+        assert_eq!(harness.manager.state(), ConnectionState::Degraded);
+        assert!(harness.manager.connection().is_some());
+
+        harness.update("good");
+        assert_eq!(harness.manager.state(), ConnectionState::Ready);
+        assert_eq!(harness.calls(), vec![Call::Connect, set("bad"), set("good")]);
+    }
+
+    #[test]
+    fn test_quiet_connection_is_pinged() {
+        let mut harness = Harness::new(&[]);
+        harness.update("a.bin");
+        harness.calls();
+
+        harness.clock.advance(PING_INTERVAL - Duration::from_secs(1));
+        harness.manager.poll().unwrap();
+        assert!(harness.calls().is_empty());
+
+        harness.clock.advance(Duration::from_secs(1));
+        harness.manager.poll().unwrap();
+        harness.manager.poll().unwrap();
+        assert_eq!(harness.calls(), vec![Call::Ping]);
+
+        // A ping that fails finds the dead socket without waiting for the next update
+        harness.clock.advance(PING_INTERVAL);
+        harness.fail_next(Outcome::Dead);
+        harness.manager.poll().unwrap();
+        assert_eq!(harness.manager.state(), ConnectionState::Disconnected);
+
+        harness.advance_to_retry();
+        harness.manager.poll().unwrap();
+        assert_eq!(harness.calls(), vec![Call::Ping, Call::Connect, set("a.bin")]);
+    }
+
+    #[test]
+    fn test_cleared_activity_is_not_replayed() {
+        let mut harness = Harness::new(&[false]);
+        harness.update("a.bin");
+        harness.manager.clear_activity().unwrap();
+
+        harness.advance_to_retry();
+        harness.manager.poll().unwrap();
+        assert_eq!(harness.calls(), vec![Call::Connect, Call::Connect]);
+        assert_eq!(harness.manager.state(), ConnectionState::Ready);
+    }
+
+    #[test]
+    fn test_parks_until_discord_runs() {
+        let running = Rc::new(RefCell::new(false));
+        let detections = Rc::new(RefCell::new(0));
+        let mut harness = Harness::new(&[]).with_detector(running.clone(), detections.clone());
+
+        harness.update("a.bin");
+        assert!(harness.manager.is_parked());
+        assert_eq!(harness.manager.state(), ConnectionState::Disconnected);
+        // Parked, the loop neither connects nor looks again before the detect interval
+        for _ in 0..10 {
+            harness.manager.poll().unwrap();
+        }
+        assert_eq!(*detections.borrow(), 1);
+        harness.clock.advance(DETECT_INTERVAL);
+        harness.manager.poll().unwrap();
+        assert_eq!(*detections.borrow(), 2);
+        assert!(harness.calls().is_empty());
+        assert!(harness.states().is_empty());
+
+        *running.borrow_mut() = true;
+        harness.update("b.bin");
+        harness.clock.advance(DETECT_INTERVAL);
+        harness.manager.poll().unwrap();
+        assert!(!harness.manager.is_parked());
+        assert_eq!(harness.calls(), vec![Call::Connect, set("b.bin")]);
+        assert_eq!(harness.manager.state(), ConnectionState::Ready);
+    }
+
+    type FakeConnector = Box<dyn FnMut() -> Result<FakeConnection, AppError>>;
+
+    // Endpoints come && go as the test says, each with its own script
+    struct Endpoints {
+        available: Rc<RefCell<Vec<PathBuf>>>,
+        scripts: Rc<RefCell<HashMap<PathBuf, Shared>>>,
+    }
+
+    impl Endpoints {
+        fn broadcast(&self, clock: &VirtualClock) -> BroadcastManager<FakeConnector, VirtualClock> {
+            let available = self.available.clone();
+            let (scripts, clock_clone) = (self.scripts.clone(), clock.clone());
+            BroadcastManager::with_clock(
+                move || available.borrow().clone(),
+                move |path: &Path| {
+                    let script = scripts.borrow_mut().entry(path.to_path_buf()).or_default().clone();
+                    let connector: FakeConnector = Box::new(move || {
+                        script.borrow_mut().calls.push(Call::Connect);
+                        Ok(FakeConnection(script.clone()))
+                    });
+                    ConnectionManager::with_clock(connector, clock_clone.clone()).with_backoff(Backoff::new(Duration::from_secs(1), Duration::from_secs(60)).with_seed(7))
+                },
+                clock.clone(),
+            )
+        }
+
+        fn calls(&self, path: &str) -> Vec<Call> {
+            self.scripts.borrow().get(Path::new(path)).map_or_else(Vec::new, |script| std::mem::take(&mut script.borrow_mut().calls))
+        }
+
+        fn fail_next(&self, path: &str, outcome: Outcome) {
+            self.scripts.borrow()[Path::new(path)].borrow_mut().outcomes.push_back(outcome);
+        }
+    }
+
+    #[test]
+    fn test_broadcast_fans_out_to_every_endpoint() {
+        let endpoints = Endpoints { available: Rc::new(RefCell::new(vec![PathBuf::from("ipc-0"), PathBuf::from("ipc-1")])), scripts: Rc::default() };
+        let clock = VirtualClock::new();
+        let reports = Rc::new(RefCell::new(Vec::new()));
+        let mut broadcast = {
+            let reports = reports.clone();
+            endpoints.broadcast(&clock).with_observer(move |states| reports.borrow_mut().push(describe_states(states)))
+        };
+
+        broadcast.update_activity(String::new(), "a.bin".to_string(), None, ActivityAssets::default()).unwrap();
+        assert_eq!(endpoints.calls("ipc-0"), vec![Call::Connect, set("a.bin")]);
+        assert_eq!(endpoints.calls("ipc-1"), vec![Call::Connect, set("a.bin")]);
+        assert_eq!(std::mem::take(&mut *reports.borrow_mut()), ["ipc-0 Ready, ipc-1 Ready"]);
+
+        // One client dying leaves the other alone
+        endpoints.fail_next("ipc-1", Outcome::Dead);
+        broadcast.update_activity(String::new(), "b.bin".to_string(), None, ActivityAssets::default()).unwrap();
+        assert_eq!(endpoints.calls("ipc-0"), vec![set("b.bin")]);
+        assert_eq!(endpoints.calls("ipc-1"), vec![set("b.bin")]);
+        assert_eq!(std::mem::take(&mut *reports.borrow_mut()), ["ipc-0 Ready, ipc-1 Disconnected"]);
+
+        // A new endpoint is picked up on the next scan && gets the current activity, a vanished one is dropped
+        endpoints.available.replace(vec![PathBuf::from("ipc-0"), PathBuf::from("ipc-2")]);
+        broadcast.poll().unwrap();
+        assert!(endpoints.calls("ipc-2").is_empty());
+        clock.advance(DETECT_INTERVAL);
+        broadcast.poll().unwrap();
+        assert_eq!(endpoints.calls("ipc-2"), vec![Call::Connect, set("b.bin")]);
+        assert!(endpoints.calls("ipc-1").is_empty());
+        assert_eq!(broadcast.states(), vec![(PathBuf::from("ipc-0"), ConnectionState::Ready), (PathBuf::from("ipc-2"), ConnectionState::Ready)]);
+        assert!(broadcast.manager(Path::new("ipc-1")).is_none());
+
+        broadcast.clear_activity().unwrap();
+        assert_eq!(endpoints.calls("ipc-0"), vec![Call::Clear]);
+        assert_eq!(endpoints.calls("ipc-2"), vec![Call::Clear]);
+    }
+
+    #[test]
+    fn test_describe_states() {
+        assert_eq!(describe_states(&[]), "no client");
+        let states = [(PathBuf::from("/run/user/1000/discord-ipc-0"), ConnectionState::Ready), (PathBuf::from("/tmp/discord-ipc-1"), ConnectionState::Degraded)];
+        assert_eq!(describe_states(&states), "discord-ipc-0 Ready, discord-ipc-1 Degraded");
+    }
+
+    #[cfg(unix)]
+    mod end_to_end {
+        use super::*;
+        use client::DiscordClient;
+        use fake_discord::FakeDiscord;
+        use serde_json::json;
+        use tempfile::tempdir;
+
+        #[test]
+        fn test_reconnects_once_discord_starts() {
+            let dir = tempdir().unwrap();
+            let socket = dir.path().join("discord-ipc-0");
+            let clock = VirtualClock::new();
+            let connector = {
+                let socket = socket.clone();
+                move || DiscordClient::connect_to(&socket, "1060827018196955177")
+            };
+            let mut manager = ConnectionManager::with_clock(connector, clock.clone());
+
+            manager.update_activity("Bytes: [None]".to_string(), "Analyzing: [a.bin]".to_string(), None, ActivityAssets::default()).unwrap();
+            assert_eq!(manager.state(), ConnectionState::Disconnected);
+
+            let discord = FakeDiscord::start(dir.path());
+            clock.advance(Duration::from_secs(60));
+            manager.poll().unwrap();
+            assert_eq!(manager.state(), ConnectionState::Ready);
+            // The fake records a command before replying, so it's there once the replay returns
+            assert_eq!(discord.activities(), vec![Some(json!({ "state": "Bytes: [None]", "details": "Analyzing: [a.bin]" }))]);
+
+            // The fake serves one session at a time, hang up before stopping it
+            drop(manager);
+        }
+
+        #[test]
+        fn test_broadcast_reaches_both_clients() {
+            let (stable_dir, canary_dir) = (tempdir().unwrap(), tempdir().unwrap());
+            let (stable, canary) = (FakeDiscord::start(stable_dir.path()), FakeDiscord::start(canary_dir.path()));
+            let sockets = vec![stable.path().to_path_buf(), canary.path().to_path_buf()];
+            let mut broadcast = BroadcastManager::new(move || sockets.clone(), |path: &Path| {
+                let path = path.to_path_buf();
+                ConnectionManager::new(move || DiscordClient::connect_to(&path, "1060827018196955177"))
+            });
+
+            broadcast.update_activity("Bytes: [None]".to_string(), "Analyzing: [a.bin]".to_string(), None, ActivityAssets::default()).unwrap();
+            assert!(broadcast.states().iter().all(|(_, state)| *state == ConnectionState::Ready));
+            let expected = vec![Some(json!({ "state": "Bytes: [None]", "details": "Analyzing: [a.bin]" }))];
+            assert_eq!(stable.activities(), expected);
+            assert_eq!(canary.activities(), expected);
+
+            drop(broadcast);
+        }
+    }
+}
@@ -0,0 +1,234 @@
+#[path = "../src/discord.rs"]
+mod discord;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use discord::{read_frame, write_frame, DiscordError, Opcode};
+    use serde_json::{json, Value};
+    use std::io::Cursor;
+
+    #[test]
+    fn test_frame_round_trip() {
+        let mut buffer = Vec::new();
+        write_frame(&mut buffer, Opcode::Frame, &json!({ "cmd": "SET_ACTIVITY" })).unwrap();
+        assert_eq!(&buffer[..4], &1u32.to_le_bytes());
+        assert_eq!(&buffer[4..8], &(buffer.len() as u32 - 8).to_le_bytes());
+
+        let (opcode, payload) = read_frame(&mut Cursor::new(buffer)).unwrap();
+        assert_eq!(opcode, Opcode::Frame);
+        assert_eq!(payload, json!({ "cmd": "SET_ACTIVITY" }));
+    }
+
+    #[test]
+    fn test_rejects_bad_frames() {
+        let mut unknown = 7u32.to_le_bytes().to_vec();
+        unknown.extend_from_slice(&2u32.to_le_bytes());
+        unknown.extend_from_slice(b"{}");
+        assert!(matches!(read_frame(&mut Cursor::new(unknown)), Err(DiscordError::Protocol(_))));
+
+        let mut huge = 1u32.to_le_bytes().to_vec();
+        huge.extend_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(read_frame(&mut Cursor::new(huge)), Err(DiscordError::Protocol(_))));
+
+        let truncated = [1u8, 0, 0];
+        assert!(matches!(read_frame(&mut Cursor::new(truncated)), Err(DiscordError::Io(_))));
+
+        let mut garbage = 1u32.to_le_bytes().to_vec();
+        garbage.extend_from_slice(&3u32.to_le_bytes());
+        garbage.extend_from_slice(b"{{{");
+        assert!(matches!(read_frame(&mut Cursor::new(garbage)), Err(DiscordError::Json(_))));
+    }
+
+    #[cfg(unix)]
+    mod socket {
+        use super::*;
+        use discord::{socket_candidates, IpcConnection, MAX_SOCKET_INDEX};
+        use std::collections::HashMap;
+        use std::os::unix::net::{UnixListener, UnixStream};
+        use std::path::{Path, PathBuf};
+        use std::thread::{self, JoinHandle};
+        use tempfile::tempdir;
+
+        const READY: &str = r#"{"cmd": "DISPATCH", "evt": "READY", "data": {"v": 1, "user": {"id": "42", "username": "reverser"}}}"#;
+
+        // Plays one Discord client session: checks the handshake, answers with the given frame, then echoes commands
+        fn serve(socket: &Path, greeting: (Opcode, &'static str), command_reply: Option<&'static str>) -> JoinHandle<Vec<Value>> {
+            let listener = UnixListener::bind(socket).unwrap();
+            thread::spawn(move || {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut received = Vec::new();
+                let (opcode, handshake) = read_frame(&mut stream).unwrap();
+                assert_eq!(opcode, Opcode::Handshake);
+                received.push(handshake);
+                write_frame(&mut stream, greeting.0, &serde_json::from_str(greeting.1).unwrap()).unwrap();
+
+                while let Ok((opcode, frame)) = read_frame(&mut stream) {
+                    if opcode == Opcode::Close {
+                        received.push(json!({ "close": frame }));
+                        break;
+                    }
+                    let nonce = frame["nonce"].clone();
+                    // A ping && an unrelated event come first, the client has to skip both
+                    write_frame(&mut stream, Opcode::Ping, &json!({ "n": 1 })).unwrap();
+                    let (pong, _) = read_frame(&mut stream).unwrap();
+                    assert_eq!(pong, Opcode::Pong);
+                    write_frame(&mut stream, Opcode::Frame, &json!({ "evt": "ACTIVITY_JOIN", "nonce": null })).unwrap();
+
+                    let reply = match command_reply {
+                        Some(reply) => {
+                            let mut reply: Value = serde_json::from_str(reply).unwrap();
+                            reply["nonce"] = nonce;
+                            reply
+                        }
+                        None => json!({ "cmd": frame["cmd"], "data": frame["args"]["activity"], "evt": null, "nonce": nonce }),
+                    };
+                    write_frame(&mut stream, Opcode::Frame, &reply).unwrap();
+                    received.push(frame);
+                }
+                received
+            })
+        }
+
+        #[test]
+        fn test_handshake_and_set_activity() {
+            let dir = tempdir().unwrap();
+            let socket = dir.path().join("discord-ipc-0");
+            let server = serve(&socket, (Opcode::Frame, READY), None);
+
+            let mut connection = IpcConnection::connect_to(&socket, "1060827018196955177").unwrap();
+            assert_eq!(connection.path(), socket);
+            assert_eq!(connection.ready().username.as_deref(), Some("reverser"));
+            assert_eq!(connection.ready().user_id.as_deref(), Some("42"));
+            assert_eq!(connection.ready().version, Some(1));
+            assert_eq!(connection.ready().data["user"]["id"], "42");
+            assert_eq!(connection.ready().to_string(), "user reverser");
+
+            let activity = json!({ "state": "Editing", "details": "firmware.bin" });
+            assert_eq!(connection.set_activity(Some(activity.clone())).unwrap(), activity);
+            assert_eq!(connection.clear_activity().unwrap(), Value::Null);
+            drop(connection);
+
+            let received = server.join().unwrap();
+            assert_eq!(received[0], json!({ "v": 1, "client_id": "1060827018196955177" }));
+            assert_eq!(received[1]["cmd"], "SET_ACTIVITY");
+            assert_eq!(received[1]["args"]["pid"], std::process::id());
+            assert_eq!(received[1]["args"]["activity"], activity);
+            assert_eq!(received[1]["nonce"], "1");
+            assert_eq!(received[2]["args"]["activity"], Value::Null);
+            assert_eq!(received[2]["nonce"], "2");
+            assert_eq!(received[3], json!({ "close": {} }));
+        }
+
+        #[test]
+        fn test_handshake_errors() {
+            let dir = tempdir().unwrap();
+            let closed = dir.path().join("closed");
+            let server = serve(&closed, (Opcode::Close, r#"{"code": 4000, "message": "Invalid Client ID"}"#), None);
+            match IpcConnection::connect_to(&closed, "0") {
+                Err(DiscordError::Closed { code, message }) => assert_eq!((code, message.as_str()), (4000, "Invalid Client ID")),
+                other => panic!("expected Closed, got {:?}", other.err()),
+            }
+            server.join().unwrap();
+
+            let error = dir.path().join("error");
+            let server = serve(&error, (Opcode::Frame, r#"{"evt": "ERROR", "data": {"code": 4007, "message": "Not authenticated"}}"#), None);
+            assert!(matches!(IpcConnection::connect_to(&error, "0"), Err(DiscordError::Rpc { code: 4007, .. })));
+            server.join().unwrap();
+
+            let odd = dir.path().join("odd");
+            let server = serve(&odd, (Opcode::Frame, r#"{"evt": "GUILD_CREATE"}"#), None);
+            assert!(matches!(IpcConnection::connect_to(&odd, "0"), Err(DiscordError::Protocol(_))));
+            server.join().unwrap();
+        }
+
+        #[test]
+        fn test_command_error_reply() {
+            let dir = tempdir().unwrap();
+            let socket = dir.path().join("discord-ipc-0");
+            let reply = r#"{"cmd": "SET_ACTIVITY", "evt": "ERROR", "data": {"code": 4000, "message": "child \"activity\" fails"}}"#;
+            let server = serve(&socket, (Opcode::Frame, READY), Some(reply));
+
+            let mut connection = IpcConnection::connect_to(&socket, "0").unwrap();
+            match connection.set_activity(Some(json!({ "state": "" }))) {
+                Err(DiscordError::Rpc { code, message }) => assert_eq!((code, message.as_str()), (4000, "child \"activity\" fails")),
+                other => panic!("expected Rpc error, got {:?}", other),
+            }
+            drop(connection);
+            server.join().unwrap();
+        }
+
+        #[test]
+        fn test_ping() {
+            let dir = tempdir().unwrap();
+            let socket = dir.path().join("discord-ipc-0");
+            let listener = UnixListener::bind(&socket).unwrap();
+            let server = thread::spawn(move || {
+                let (mut stream, _) = listener.accept().unwrap();
+                read_frame(&mut stream).unwrap();
+                write_frame(&mut stream, Opcode::Frame, &serde_json::from_str(READY).unwrap()).unwrap();
+
+                // A late reply && a pong for another ping come first
+                let (opcode, ping) = read_frame(&mut stream).unwrap();
+                assert_eq!(opcode, Opcode::Ping);
+                write_frame(&mut stream, Opcode::Frame, &json!({ "evt": null, "nonce": "0" })).unwrap();
+                write_frame(&mut stream, Opcode::Pong, &json!({ "nonce": "old" })).unwrap();
+                write_frame(&mut stream, Opcode::Pong, &ping).unwrap();
+                // Then Discord goes away
+            });
+
+            let mut connection = IpcConnection::connect_to(&socket, "0").unwrap();
+            connection.ping().unwrap();
+            server.join().unwrap();
+            assert!(connection.ping().is_err());
+        }
+
+        #[test]
+        fn test_connect_first_skips_dead_sockets() {
+            let dir = tempdir().unwrap();
+            let missing = dir.path().join("discord-ipc-0");
+            // A socket file nobody listens on, like the one a crashed client leaves behind
+            let stale = dir.path().join("discord-ipc-1");
+            drop(UnixListener::bind(&stale).unwrap());
+            let live = dir.path().join("discord-ipc-2");
+            let server = serve(&live, (Opcode::Frame, READY), None);
+
+            let connection = IpcConnection::connect_first(&[missing.clone(), stale.clone(), live.clone()], "0").unwrap();
+            assert_eq!(connection.path(), live);
+            drop(connection);
+            server.join().unwrap();
+
+            assert!(matches!(IpcConnection::connect_first(std::slice::from_ref(&missing), "0"), Err(DiscordError::NotFound(1))));
+            assert!(matches!(IpcConnection::connect_first(&[missing, stale], "0"), Err(DiscordError::Io(_))));
+            assert!(UnixStream::connect(&live).is_err());
+        }
+
+        #[test]
+        fn test_socket_candidates() {
+            let vars = HashMap::from([("XDG_RUNTIME_DIR", "/run/user/1000"), ("TMPDIR", "/tmp"), ("TEMP", "")]);
+            let candidates = socket_candidates(|name| vars.get(name).map(|value| value.to_string()));
+
+            let per_dir = MAX_SOCKET_INDEX as usize + 1;
+            assert_eq!(candidates.len(), 2 * 7 * per_dir);
+            assert_eq!(candidates[0], PathBuf::from("/run/user/1000/discord-ipc-0"));
+            assert_eq!(candidates[per_dir - 1], PathBuf::from("/run/user/1000/discord-ipc-9"));
+            assert!(candidates.contains(&PathBuf::from("/run/user/1000/app/com.discordapp.Discord/discord-ipc-0")));
+            assert!(candidates.contains(&PathBuf::from("/run/user/1000/snap.discord/discord-ipc-3")));
+            assert!(candidates.contains(&PathBuf::from("/run/user/1000/app/io.github.spacingbat3.webcord/discord-ipc-0")));
+            assert!(candidates.contains(&PathBuf::from("/tmp/discord-ipc-0")));
+            let flatpak = candidates.iter().position(|path| path.starts_with("/run/user/1000/app")).unwrap();
+            let tmp = candidates.iter().position(|path| path.starts_with("/tmp")).unwrap();
+            assert!(flatpak < tmp);
+
+            assert_eq!(socket_candidates(|_| None).len(), 7 * per_dir);
+        }
+
+        #[test]
+        fn test_connect_without_discord() {
+            if socket_candidates(|name| std::env::var(name).ok()).iter().any(|path| path.exists()) {
+                return;
+            }
+            assert!(matches!(IpcConnection::connect("0"), Err(DiscordError::NotFound(_))));
+        }
+    }
+}
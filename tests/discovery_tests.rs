@@ -0,0 +1,121 @@
+#[path = "../src/discord.rs"]
+#[allow(dead_code)]
+mod discord;
+#[path = "../src/discovery.rs"]
+mod discovery;
+#[path = "../src/process.rs"]
+#[allow(dead_code)]
+mod process;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use discovery::{detect_variant, is_candidate_name, scan_discord, variant_from_name, DiscordScan, DiscordVariant};
+    use process::ProcessEntry;
+    use std::path::PathBuf;
+
+    fn process(name: &str, exe: &str, args: &[&str]) -> ProcessEntry {
+        ProcessEntry {
+            pid: 1,
+            name: name.to_string(),
+            exe: (!exe.is_empty()).then(|| PathBuf::from(exe)),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_variant_names() {
+        assert_eq!(variant_from_name("Discord.exe"), Some(DiscordVariant::Stable));
+        assert_eq!(variant_from_name("discord"), Some(DiscordVariant::Stable));
+        assert_eq!(variant_from_name("DiscordPTB.exe"), Some(DiscordVariant::Ptb));
+        assert_eq!(variant_from_name("discord-ptb"), Some(DiscordVariant::Ptb));
+        assert_eq!(variant_from_name("DiscordCanary"), Some(DiscordVariant::Canary));
+        assert_eq!(variant_from_name("Vesktop.exe"), Some(DiscordVariant::Vesktop));
+        assert_eq!(variant_from_name("webcord"), Some(DiscordVariant::WebCord));
+        // Including ourselves
+        assert_eq!(variant_from_name("discord-imhex"), None);
+        assert_eq!(variant_from_name("Update.exe"), None);
+    }
+
+    #[test]
+    fn test_candidate_names() {
+        assert!(is_candidate_name("DiscordPTB.exe"));
+        assert!(is_candidate_name("electron32"));
+        assert!(!is_candidate_name("discord-imhex"));
+        assert!(!is_candidate_name("svchost.exe"));
+    }
+
+    #[test]
+    fn test_detect_native_clients() {
+        let stable = process("Discord", "/opt/discord/Discord", &["/opt/discord/Discord", "--type=renderer"]);
+        assert_eq!(detect_variant(&stable), Some(DiscordVariant::Stable));
+        let canary = process("DiscordCanary.exe", r"C:\Users\user\AppData\Local\DiscordCanary\app-1.0.1\DiscordCanary.exe", &[]);
+        assert_eq!(detect_variant(&canary), Some(DiscordVariant::Canary));
+        // comm is cut to 15 characters, the executable still has the full name
+        let ptb = process("DiscordPTB", "/usr/share/discord-ptb/DiscordPTB", &["/usr/share/discord-ptb/DiscordPTB"]);
+        assert_eq!(detect_variant(&ptb), Some(DiscordVariant::Ptb));
+        let vesktop = process("vesktop", "/usr/lib/vesktop/vesktop", &["/usr/lib/vesktop/vesktop"]);
+        assert_eq!(detect_variant(&vesktop), Some(DiscordVariant::Vesktop));
+
+        assert_eq!(detect_variant(&process("discord-imhex", "/usr/bin/discord-imhex", &["discord-imhex"])), None);
+        assert_eq!(detect_variant(&process("bash", "/usr/bin/bash", &["bash", "discord"])), None);
+    }
+
+    #[test]
+    fn test_detect_sandboxed_and_electron_clients() {
+        let flatpak = process("Discord", "/app/discord/Discord", &["/app/discord/Discord"]);
+        assert_eq!(detect_variant(&flatpak), Some(DiscordVariant::Flatpak));
+        // Vesktop stays Vesktop when it runs as a Flatpak
+        let vesktop = process("vesktop", "/app/bin/vesktop", &["/app/bin/vesktop"]);
+        assert_eq!(detect_variant(&vesktop), Some(DiscordVariant::Vesktop));
+
+        let webcord = process("electron", "/usr/lib/electron32/electron", &["/usr/lib/electron32/electron", "--no-sandbox", "/usr/lib/webcord/app.asar"]);
+        assert_eq!(detect_variant(&webcord), Some(DiscordVariant::WebCord));
+        let other = process("electron", "/usr/lib/electron32/electron", &["/usr/lib/electron32/electron", "/usr/lib/code/out"]);
+        assert_eq!(detect_variant(&other), None);
+    }
+
+    #[test]
+    fn test_scan() {
+        let processes = [
+            process("Discord", "/opt/discord/Discord", &[]),
+            process("Discord", "/opt/discord/Discord", &["--type=gpu-process"]),
+            process("vesktop", "/usr/bin/vesktop", &[]),
+            process("imhex", "/usr/bin/imhex", &[]),
+        ];
+        let scan = DiscordScan::from_parts(&processes, Vec::new());
+        assert_eq!(scan.variants, vec![DiscordVariant::Stable, DiscordVariant::Vesktop]);
+        assert_eq!(scan.to_string(), "Discord, Vesktop");
+        assert!(!scan.is_empty());
+
+        let socket_only = DiscordScan::from_parts(&[], vec![PathBuf::from("/run/user/1000/discord-ipc-0")]);
+        assert_eq!(socket_only.to_string(), "IPC socket /run/user/1000/discord-ipc-0");
+        assert!(!socket_only.is_empty());
+
+        let nothing = DiscordScan::from_parts(&processes[3..], Vec::new());
+        assert!(nothing.is_empty());
+        assert_eq!(nothing.to_string(), "no Discord client");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_existing_sockets() {
+        let dir = tempfile::tempdir().unwrap();
+        let flatpak = dir.path().join("app/com.discordapp.Discord");
+        std::fs::create_dir_all(&flatpak).unwrap();
+        std::fs::write(flatpak.join("discord-ipc-0"), b"").unwrap();
+        std::fs::write(dir.path().join("discord-ipc-1"), b"").unwrap();
+        // Flatpak Discord links its socket into the runtime folder for clients outside the sandbox
+        std::os::unix::fs::symlink(flatpak.join("discord-ipc-0"), dir.path().join("discord-ipc-0")).unwrap();
+
+        let candidates = (0..3).map(|index| dir.path().join(format!("discord-ipc-{}", index))).chain([flatpak.join("discord-ipc-0")]).collect();
+        assert_eq!(discovery::existing_sockets(candidates), vec![dir.path().join("discord-ipc-0"), dir.path().join("discord-ipc-1")]);
+    }
+
+    #[test]
+    fn test_scan_discord_never_finds_itself() {
+        // The test runner is not a Discord client, whatever else runs on this machine
+        let scan = scan_discord();
+        assert!(scan.sockets.iter().all(|socket| socket.exists()));
+    }
+}
@@ -0,0 +1,158 @@
+#[path = "../src/entropy.rs"]
+mod entropy;
+#[path = "../src/resolver.rs"]
+#[allow(dead_code)]
+mod resolver;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use entropy::{analyze, shannon_entropy, EntropyAnalyzer, EntropyProfile, EntropyVerdict};
+    use resolver::ResolvedFile;
+    use std::fs::{self, File};
+    use std::io::Cursor;
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::AtomicBool;
+    use std::time::{Duration, SystemTime};
+    use tempfile::tempdir;
+
+    const WAIT: Duration = Duration::from_secs(10);
+
+    // xorshift output stands in for compressed or encrypted data
+    fn noise(len: usize) -> Vec<u8> {
+        let mut state = 0x2545_F491_4F6C_DD1Du64;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                (state >> 24) as u8
+            })
+            .collect()
+    }
+
+    fn profile_of(data: &[u8]) -> EntropyProfile {
+        analyze(&mut Cursor::new(data), 1024, u64::MAX, &AtomicBool::new(false)).unwrap().unwrap()
+    }
+
+    fn write_file(path: &Path, data: &[u8], modified: SystemTime) -> ResolvedFile {
+        fs::write(path, data).unwrap();
+        File::options().write(true).open(path).unwrap().set_modified(modified).unwrap();
+        ResolvedFile::from_path(path).unwrap()
+    }
+
+    #[test]
+    fn test_shannon_entropy_bounds() {
+        assert_eq!(shannon_entropy(&[0; 256]), 0.0);
+
+        let mut constant = [0u64; 256];
+        constant[0x41] = 100;
+        assert_eq!(shannon_entropy(&constant), 0.0);
+
+        assert!((shannon_entropy(&[1; 256]) - 8.0).abs() < 1e-9);
+
+        let mut two_symbols = [0u64; 256];
+        two_symbols[0] = 50;
+        two_symbols[1] = 50;
+        assert!((shannon_entropy(&two_symbols) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_verdicts() {
+        assert_eq!(profile_of(&[]).verdict(), EntropyVerdict::Empty);
+        assert_eq!(profile_of(&[0u8; 8192]).verdict(), EntropyVerdict::Sparse);
+        assert_eq!(profile_of(&b"The quick brown fox jumps over the lazy dog. ".repeat(100)).verdict(), EntropyVerdict::Text);
+        assert_eq!(profile_of(&noise(16384)).verdict(), EntropyVerdict::Packed);
+
+        let mut code: Vec<u8> = (0..8192u32).map(|i| (i % 64) as u8 * 3).collect();
+        assert_eq!(profile_of(&code).verdict(), EntropyVerdict::Structured);
+        code.extend(noise(2048));
+        assert_eq!(profile_of(&code).verdict(), EntropyVerdict::PartlyPacked);
+    }
+
+    #[test]
+    fn test_block_profile() {
+        let mut data = vec![0u8; 1024];
+        data.extend(noise(2048));
+        data.extend([7u8; 100]);
+
+        let profile = profile_of(&data);
+        assert_eq!(profile.bytes_read, 3172);
+        assert_eq!(profile.blocks.len(), 4);
+        assert_eq!(profile.blocks[0], 0.0);
+        assert!(profile.blocks[1] > 7.5);
+        assert_eq!(profile.blocks[3], 0.0);
+        assert!((profile.high_entropy_ratio() - 0.5).abs() < 1e-9);
+        assert!(!profile.truncated);
+    }
+
+    #[test]
+    fn test_size_cap() {
+        let data = noise(4096);
+        let profile = analyze(&mut Cursor::new(&data), 256, 1000, &AtomicBool::new(false)).unwrap().unwrap();
+        assert_eq!(profile.bytes_read, 1000);
+        assert!(profile.truncated);
+        assert!(profile.to_string().ends_with(", first 1000 bytes only)"));
+
+        let exact = analyze(&mut Cursor::new(&data), 256, 4096, &AtomicBool::new(false)).unwrap().unwrap();
+        assert!(!exact.truncated);
+    }
+
+    #[test]
+    fn test_cancelled_analysis() {
+        let result = analyze(&mut Cursor::new(noise(1024)), 256, u64::MAX, &AtomicBool::new(true)).unwrap();
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_display() {
+        let profile = EntropyProfile { entropy: 7.987, blocks: vec![7.9, 7.95, 6.0, 7.99], bytes_read: 16384, truncated: false };
+        assert_eq!(profile.to_string(), "7.99 bits/byte, likely compressed/encrypted (75% of 4 blocks high entropy)");
+    }
+
+    #[test]
+    fn test_analyzer_runs_in_background() {
+        let dir = tempdir().unwrap();
+        let file = write_file(&dir.path().join("blob.bin"), &noise(64 * 1024), SystemTime::now());
+
+        let mut analyzer = EntropyAnalyzer::new().with_block_size(1024);
+        analyzer.update(Some(&file));
+        assert!(analyzer.wait(WAIT));
+        assert_eq!(analyzer.profile().unwrap().verdict(), EntropyVerdict::Packed);
+        assert_eq!(analyzer.profile().unwrap().blocks.len(), 64);
+
+        // The same file version is not analyzed again
+        assert!(!analyzer.update(Some(&file)));
+        assert!(analyzer.profile().is_some());
+
+        analyzer.update(None);
+        assert!(analyzer.profile().is_none());
+    }
+
+    #[test]
+    fn test_analyzer_restarts_when_file_changes() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("blob.bin");
+        let modified = SystemTime::now() - Duration::from_secs(60);
+        let packed = write_file(&path, &noise(8192), modified);
+
+        let mut analyzer = EntropyAnalyzer::new().with_size_cap(4096);
+        analyzer.update(Some(&packed));
+        assert!(analyzer.wait(WAIT));
+        assert!(analyzer.profile().unwrap().truncated);
+
+        let sparse = write_file(&path, &[0u8; 8192], modified + Duration::from_secs(30));
+        analyzer.update(Some(&sparse));
+        assert!(analyzer.wait(WAIT));
+        assert_eq!(analyzer.profile().unwrap().verdict(), EntropyVerdict::Sparse);
+    }
+
+    #[test]
+    fn test_analyzer_missing_file() {
+        let missing = ResolvedFile { path: PathBuf::from("/nonexistent/blob.bin"), size: 0, modified: None };
+        let mut analyzer = EntropyAnalyzer::default();
+        analyzer.update(Some(&missing));
+        assert!(!analyzer.wait(WAIT));
+        assert!(analyzer.profile().is_none());
+    }
+}
@@ -0,0 +1,195 @@
+#[path = "../src/binary.rs"]
+#[allow(dead_code)]
+mod binary;
+#[path = "../src/format.rs"]
+mod format;
+#[path = "../src/resolver.rs"]
+#[allow(dead_code)]
+mod resolver;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use format::{classify_header, FormatCategory, FormatClassifier, HEADER_LEN};
+    use resolver::ResolvedFile;
+    use std::fs::{self, File};
+    use std::time::{Duration, SystemTime};
+    use tempfile::tempdir;
+
+    fn header_with(len: usize, patches: &[(usize, &[u8])]) -> Vec<u8> {
+        let mut header = vec![0u8; len];
+        for (offset, bytes) in patches {
+            header[*offset..*offset + bytes.len()].copy_from_slice(bytes);
+        }
+        header
+    }
+
+    fn elf(class: u8, data: u8, kind: u16, machine: u16) -> Vec<u8> {
+        let (kind, machine) = if data == 2 {
+            (kind.to_be_bytes(), machine.to_be_bytes())
+        } else {
+            (kind.to_le_bytes(), machine.to_le_bytes())
+        };
+        header_with(64, &[(0, b"\x7fELF"), (4, &[class, data, 1]), (16, &kind), (18, &machine)])
+    }
+
+    fn pe(machine: u16, optional_magic: u16, characteristics: u16) -> Vec<u8> {
+        header_with(
+            0x200,
+            &[
+                (0, b"MZ"),
+                (0x3C, &0x80u32.to_le_bytes()),
+                (0x80, b"PE\0\0"),
+                (0x84, &machine.to_le_bytes()),
+                (0x96, &characteristics.to_le_bytes()),
+                (0x98, &optional_magic.to_le_bytes()),
+            ],
+        )
+    }
+
+    fn describe(header: &[u8]) -> Option<(FormatCategory, String)> {
+        classify_header(header).map(|format| (format.category, format.to_string()))
+    }
+
+    fn executable(description: &str) -> Option<(FormatCategory, String)> {
+        Some((FormatCategory::Executable, description.to_string()))
+    }
+
+    #[test]
+    fn test_elf_headers() {
+        assert_eq!(describe(&elf(2, 1, 3, 0x3E)), executable("ELF x86-64 shared object"));
+        assert_eq!(describe(&elf(2, 1, 2, 0xB7)), executable("ELF AArch64 executable"));
+        assert_eq!(describe(&elf(1, 2, 1, 0x08)), executable("ELF MIPS relocatable"));
+        assert_eq!(describe(&elf(1, 1, 4, 0x1234)), executable("ELF machine 0x1234 core dump"));
+        assert_eq!(describe(b"\x7fELF"), executable("ELF file"));
+    }
+
+    #[test]
+    fn test_pe_headers() {
+        assert_eq!(describe(&pe(0x8664, 0x20B, 0x0022)), executable("PE32+ x86-64 executable"));
+        assert_eq!(describe(&pe(0x14C, 0x10B, 0x2102)), executable("PE32 Intel 80386 DLL"));
+        assert_eq!(describe(&header_with(0x40, &[(0, b"MZ")])), executable("MS-DOS executable"));
+    }
+
+    #[test]
+    fn test_mach_o_and_bytecode_headers() {
+        let mach_o = header_with(32, &[(0, b"\xCF\xFA\xED\xFE"), (4, &0x0100_000Cu32.to_le_bytes()), (12, &2u32.to_le_bytes())]);
+        assert_eq!(describe(&mach_o), executable("Mach-O 64-bit ARM64 executable"));
+
+        let dylib = header_with(32, &[(0, b"\xFE\xED\xFA\xCE"), (4, &7u32.to_be_bytes()), (12, &6u32.to_be_bytes())]);
+        assert_eq!(describe(&dylib), executable("Mach-O 32-bit x86 dynamic library"));
+
+        let fat = header_with(32, &[(0, b"\xCA\xFE\xBA\xBE"), (4, &2u32.to_be_bytes())]);
+        assert_eq!(describe(&fat), executable("Mach-O universal binary"));
+
+        // Java 8 class: minor 0, major 52
+        let class = header_with(32, &[(0, b"\xCA\xFE\xBA\xBE\x00\x00\x00\x34")]);
+        assert_eq!(describe(&class), executable("Java class file"));
+        assert_eq!(describe(b"\0asm\x01\0\0\0"), executable("WebAssembly module"));
+    }
+
+    #[test]
+    fn test_signature_table() {
+        let cases: Vec<(Vec<u8>, FormatCategory, &str)> = vec![
+            (b"PK\x03\x04\x14\0".to_vec(), FormatCategory::Archive, "ZIP archive"),
+            (b"\x1F\x8B\x08\0".to_vec(), FormatCategory::Archive, "gzip compressed data"),
+            (b"\xFD7zXZ\0\0".to_vec(), FormatCategory::Archive, "XZ compressed data"),
+            (b"\x28\xB5\x2F\xFD\0".to_vec(), FormatCategory::Archive, "Zstandard compressed data"),
+            (header_with(512, &[(257, b"ustar\x0000")]), FormatCategory::Archive, "tar archive"),
+            (b"\x89PNG\r\n\x1A\n".to_vec(), FormatCategory::Image, "PNG image"),
+            (b"\xFF\xD8\xFF\xE0".to_vec(), FormatCategory::Image, "JPEG image"),
+            (b"GIF89a".to_vec(), FormatCategory::Image, "GIF image"),
+            (b"RIFF\0\0\0\0WEBPVP8 ".to_vec(), FormatCategory::Image, "WebP image"),
+            (header_with(32, &[(0, b"BM"), (14, &40u32.to_le_bytes())]), FormatCategory::Image, "BMP image"),
+            (b"\x27\x05\x19\x56\0\0".to_vec(), FormatCategory::Firmware, "U-Boot image"),
+            (b"\xD0\x0D\xFE\xED\0\0".to_vec(), FormatCategory::Firmware, "Device tree blob"),
+            (b"ANDROID!".to_vec(), FormatCategory::Firmware, "Android boot image"),
+            (header_with(32, &[(0x10, b"\x5A\xA5\xF0\x0F")]), FormatCategory::Firmware, "Intel flash descriptor"),
+            (b":10010000214601360121470136007EFE09D2190140\n".to_vec(), FormatCategory::Firmware, "Intel HEX firmware"),
+            (header_with(HEADER_LEN, &[(0x8001, b"CD001")]), FormatCategory::Filesystem, "ISO 9660 filesystem"),
+            (b"hsqs\0\0\0\0".to_vec(), FormatCategory::Filesystem, "SquashFS filesystem"),
+            (header_with(0x440, &[(0x438, b"\x53\xEF")]), FormatCategory::Filesystem, "ext2/3/4 filesystem"),
+            (header_with(512, &[(3, b"NTFS    "), (0x1FE, b"\x55\xAA")]), FormatCategory::Filesystem, "NTFS filesystem"),
+            (header_with(512, &[(0x52, b"FAT32   "), (0x1FE, b"\x55\xAA")]), FormatCategory::Filesystem, "FAT32 filesystem"),
+            (header_with(0x210, &[(0x1FE, b"\x55\xAA"), (0x200, b"EFI PART")]), FormatCategory::Filesystem, "GPT partitioned disk"),
+            (header_with(512, &[(0x1FE, b"\x55\xAA")]), FormatCategory::Filesystem, "MBR partitioned disk"),
+            (b"%PDF-1.7\n".to_vec(), FormatCategory::Document, "PDF document"),
+            (b"\xD0\xCF\x11\xE0\xA1\xB1\x1A\xE1".to_vec(), FormatCategory::Document, "Microsoft Office document"),
+            (b"SQLite format 3\0".to_vec(), FormatCategory::Document, "SQLite database"),
+        ];
+
+        for (header, category, description) in cases {
+            assert_eq!(describe(&header), Some((category, description.to_string())), "{}", description);
+        }
+    }
+
+    #[test]
+    fn test_unknown_headers() {
+        assert_eq!(classify_header(b""), None);
+        assert_eq!(classify_header(b"hello world, just some text"), None);
+        assert_eq!(classify_header(&[0u8; 4096]), None);
+    }
+
+    #[test]
+    fn test_image_keys_are_distinct() {
+        let categories = [
+            FormatCategory::Executable,
+            FormatCategory::Archive,
+            FormatCategory::Image,
+            FormatCategory::Firmware,
+            FormatCategory::Filesystem,
+            FormatCategory::Document,
+        ];
+        let mut keys: Vec<&str> = categories.iter().map(|category| category.image_key()).collect();
+        keys.sort();
+        keys.dedup();
+        assert_eq!(keys.len(), categories.len());
+    }
+
+    #[test]
+    fn test_classifier_caches_by_path_and_mtime() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("blob.bin");
+        fs::write(&path, b"%PDF-1.4").unwrap();
+        let file = ResolvedFile::from_path(&path).unwrap();
+
+        let mut classifier = FormatClassifier::new();
+        assert_eq!(classifier.classify(&file).unwrap().description, "PDF document");
+
+        // Same mtime: the cached verdict wins even though the contents changed
+        fs::write(&path, b"\x89PNG\r\n\x1A\n").unwrap();
+        File::options().write(true).open(&path).unwrap().set_modified(file.modified.unwrap()).unwrap();
+        assert_eq!(classifier.classify(&file).unwrap().description, "PDF document");
+
+        let later = file.modified.unwrap() + Duration::from_secs(10);
+        File::options().write(true).open(&path).unwrap().set_modified(later).unwrap();
+        let changed = ResolvedFile::from_path(&path).unwrap();
+        assert_eq!(changed.modified, Some(later));
+        assert_eq!(classifier.classify(&changed).unwrap().description, "PNG image");
+    }
+
+    #[test]
+    fn test_classifier_attaches_binary_summary() {
+        let dir = tempdir().unwrap();
+        let elf_path = dir.path().join("a.out");
+        fs::write(&elf_path, elf(2, 1, 2, 0x3E)).unwrap();
+        let pdf_path = dir.path().join("doc.pdf");
+        fs::write(&pdf_path, b"%PDF-1.4").unwrap();
+
+        let mut classifier = FormatClassifier::new();
+        let elf = classifier.classify(&ResolvedFile::from_path(&elf_path).unwrap()).unwrap();
+        let binary = elf.binary.unwrap();
+        assert_eq!(binary.architecture, "x86-64");
+        assert_eq!(binary.bits, 64);
+
+        let pdf = classifier.classify(&ResolvedFile::from_path(&pdf_path).unwrap()).unwrap();
+        assert!(pdf.binary.is_none());
+    }
+
+    #[test]
+    fn test_classifier_missing_file() {
+        let dir = tempdir().unwrap();
+        let missing = ResolvedFile { path: dir.path().join("gone.bin"), size: 0, modified: Some(SystemTime::now()) };
+        assert_eq!(FormatClassifier::new().classify(&missing), None);
+    }
+}
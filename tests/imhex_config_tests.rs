@@ -0,0 +1,213 @@
+#[path = "../src/imhex_config.rs"]
+mod imhex_config;
+#[path = "../src/process.rs"]
+#[allow(dead_code)]
+mod process;
+#[path = "../src/resolver.rs"]
+#[allow(dead_code)]
+mod resolver;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use imhex_config::{discover_config_dirs, ConfigDir, DiscoveryEnv, ImhexConfig, ImhexSettings, InstallKind, FLATPAK_APP_ID};
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, SystemTime};
+    use tempfile::tempdir;
+
+    const SETTINGS: &str = r#"{
+        "hex.builtin.setting.general": {
+            "hex.builtin.setting.general.auto_load_patterns": true
+        },
+        "hex.builtin.setting.interface": {
+            "hex.builtin.setting.interface.color": "Dark",
+            "hex.builtin.setting.interface.language": "de-DE",
+            "hex.builtin.setting.interface.fps": 60
+        },
+        "hex.builtin.setting.workspace": {
+            "hex.builtin.setting.workspace.current_workspace": "Reversing"
+        },
+        "hex.builtin.setting.imhex": {
+            "hex.builtin.setting.imhex.recent_files": ["/data/a.bin", 3, "/data/b.bin"]
+        }
+    }"#;
+
+    fn make_dir(path: PathBuf) -> PathBuf {
+        fs::create_dir_all(&path).unwrap();
+        path
+    }
+
+    // XDG layout rooted in a fixture home directory
+    fn home_env(home: &Path) -> DiscoveryEnv {
+        DiscoveryEnv {
+            home: Some(home.to_path_buf()),
+            config_home: Some(home.join(".config")),
+            data_home: Some(home.join(".local/share")),
+            config_dirs: vec![home.join("etc/xdg")],
+            data_dirs: vec![home.join("usr/share")],
+            ..DiscoveryEnv::default()
+        }
+    }
+
+    fn kinds(dirs: &[ConfigDir]) -> Vec<InstallKind> {
+        dirs.iter().map(|dir| dir.kind).collect()
+    }
+
+    #[test]
+    fn test_parse_settings() {
+        let settings = ImhexSettings::parse(SETTINGS).unwrap();
+        assert_eq!(settings.language.as_deref(), Some("de-DE"));
+        assert_eq!(settings.theme.as_deref(), Some("Dark"));
+        assert_eq!(settings.workspace.as_deref(), Some("Reversing"));
+        assert_eq!(settings.recent_files, vec![PathBuf::from("/data/a.bin"), PathBuf::from("/data/b.bin")]);
+
+        assert_eq!(ImhexSettings::parse("{}").unwrap(), ImhexSettings::default());
+        assert_eq!(ImhexSettings::parse("[1, 2]").unwrap(), ImhexSettings::default());
+        assert!(ImhexSettings::parse("{ broken").is_err());
+    }
+
+    #[test]
+    fn test_installed_dirs() {
+        let home = tempdir().unwrap();
+        let config = make_dir(home.path().join(".config/imhex"));
+        let system = make_dir(home.path().join("usr/share/imhex"));
+        make_dir(home.path().join("etc/xdg"));
+
+        let dirs = discover_config_dirs(&home_env(home.path()));
+        let paths: Vec<&Path> = dirs.iter().map(|dir| dir.path.as_path()).collect();
+        assert_eq!(paths, vec![config.as_path(), system.as_path()]);
+        assert_eq!(kinds(&dirs), vec![InstallKind::Installed, InstallKind::Installed]);
+    }
+
+    #[test]
+    fn test_flatpak_dirs_before_installed() {
+        let home = tempdir().unwrap();
+        let flatpak = make_dir(home.path().join(".var/app").join(FLATPAK_APP_ID).join("config/imhex"));
+        make_dir(home.path().join(".local/share/imhex"));
+
+        let dirs = discover_config_dirs(&home_env(home.path()));
+        assert_eq!(dirs[0].path, flatpak);
+        assert_eq!(kinds(&dirs), vec![InstallKind::Flatpak, InstallKind::Installed]);
+    }
+
+    #[test]
+    fn test_portable_dir_needs_marker() {
+        let root = tempdir().unwrap();
+        let install = make_dir(root.path().join("ImHex"));
+        make_dir(root.path().join(".config/imhex"));
+        let env = DiscoveryEnv { executable: Some(install.join("imhex-gui")), ..home_env(root.path()) };
+
+        assert_eq!(kinds(&discover_config_dirs(&env)), vec![InstallKind::Installed]);
+
+        fs::write(install.join("PORTABLE"), "").unwrap();
+        let dirs = discover_config_dirs(&env);
+        assert_eq!(dirs[0], ConfigDir { path: install, kind: InstallKind::Portable });
+        assert_eq!(dirs.len(), 2);
+    }
+
+    #[test]
+    fn test_appimage_sibling_dirs() {
+        let root = tempdir().unwrap();
+        let appimage = root.path().join("ImHex-x86_64.AppImage");
+        let home_dir = make_dir(root.path().join("ImHex-x86_64.AppImage.home/.config/imhex"));
+        let env = DiscoveryEnv { appimage: Some(appimage), ..DiscoveryEnv::default() };
+
+        assert_eq!(discover_config_dirs(&env), vec![ConfigDir { path: home_dir, kind: InstallKind::AppImage }]);
+        assert!(discover_config_dirs(&DiscoveryEnv::default()).is_empty());
+    }
+
+    #[test]
+    fn test_duplicate_dirs_listed_once() {
+        let home = tempdir().unwrap();
+        make_dir(home.path().join(".config/imhex"));
+        let env = DiscoveryEnv { config_dirs: vec![home.path().join(".config")], ..home_env(home.path()) };
+
+        assert_eq!(discover_config_dirs(&env).len(), 1);
+    }
+
+    #[test]
+    fn test_load_settings_from_first_dir() {
+        let home = tempdir().unwrap();
+        let flatpak = make_dir(home.path().join(".var/app").join(FLATPAK_APP_ID).join("config/imhex"));
+        let installed = make_dir(home.path().join(".config/imhex"));
+        fs::write(installed.join("settings.json"), r#"{"ui": {"hex.builtin.setting.interface.language": "en-US"}}"#).unwrap();
+
+        // The Flatpak dir has no settings yet, so the installed one is used
+        let config = ImhexConfig::discover(&home_env(home.path())).unwrap();
+        assert_eq!(config.install_kind(), Some(InstallKind::Flatpak));
+        assert_eq!(config.settings.language.as_deref(), Some("en-US"));
+
+        fs::write(make_dir(flatpak.join("config")).join("settings.json"), SETTINGS).unwrap();
+        let config = ImhexConfig::discover(&home_env(home.path())).unwrap();
+        assert_eq!(config.settings_path(), Some(flatpak.join("config/settings.json").as_path()));
+        assert_eq!(config.settings.language.as_deref(), Some("de-DE"));
+        assert_eq!(
+            config.to_string(),
+            format!("{} (Flatpak), language de-DE, theme Dark, workspace Reversing", flatpak.display())
+        );
+    }
+
+    #[test]
+    fn test_load_without_settings() {
+        let config = ImhexConfig::load(Vec::new()).unwrap();
+        assert_eq!(config.settings, ImhexSettings::default());
+        assert_eq!(config.install_kind(), None);
+        assert_eq!(config.to_string(), "no config directory found");
+
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("settings.json"), "not json").unwrap();
+        let dirs = vec![ConfigDir { path: dir.path().to_path_buf(), kind: InstallKind::Installed }];
+        assert!(ImhexConfig::load(dirs).is_err());
+    }
+
+    #[test]
+    fn test_refresh_after_save() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("settings.json");
+        fs::write(&path, SETTINGS).unwrap();
+        let dirs = vec![ConfigDir { path: dir.path().to_path_buf(), kind: InstallKind::Installed }];
+        let mut config = ImhexConfig::load(dirs).unwrap();
+        assert!(!config.refresh().unwrap());
+
+        fs::write(&path, r#"{"ui": {"hex.builtin.setting.interface.color": "Light"}}"#).unwrap();
+        let later = SystemTime::now() + Duration::from_secs(10);
+        fs::File::options().write(true).open(&path).unwrap().set_modified(later).unwrap();
+        assert!(config.refresh().unwrap());
+        assert_eq!(config.settings.theme.as_deref(), Some("Light"));
+        assert_eq!(config.settings.language, None);
+    }
+
+    #[test]
+    fn test_recent_files_merge_entries_and_settings() {
+        let dir = tempdir().unwrap();
+        let recent = make_dir(dir.path().join("recent"));
+        fs::write(recent.join("1.json"), r#"{"type": "hex.builtin.provider.file", "path": "/data/b.bin"}"#).unwrap();
+        fs::write(recent.join("2.json"), r#"{"path": "/data/c.bin"}"#).unwrap();
+        let old = SystemTime::now() - Duration::from_secs(60);
+        fs::File::options().write(true).open(recent.join("1.json")).unwrap().set_modified(old).unwrap();
+        fs::write(dir.path().join("settings.json"), SETTINGS).unwrap();
+
+        let dirs = vec![ConfigDir { path: dir.path().to_path_buf(), kind: InstallKind::Installed }];
+        let config = ImhexConfig::load(dirs).unwrap();
+        let expected: Vec<PathBuf> = ["/data/c.bin", "/data/b.bin", "/data/a.bin"].iter().map(PathBuf::from).collect();
+        assert_eq!(config.recent_files(), expected);
+        assert_eq!(config.dir_paths(), vec![dir.path().to_path_buf()]);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_env_from_process() {
+        use std::os::unix::fs::symlink;
+
+        let root = tempdir().unwrap();
+        let proc_dir = make_dir(root.path().join("proc/77"));
+        symlink("/tmp/.mount_ImHex/usr/bin/imhex", proc_dir.join("exe")).unwrap();
+        fs::write(proc_dir.join("environ"), "APPIMAGE=/home/user/ImHex.AppImage\0").unwrap();
+
+        let env = DiscoveryEnv::default().with_process(&root.path().join("proc"), 77);
+        assert_eq!(env.executable, Some(PathBuf::from("/tmp/.mount_ImHex/usr/bin/imhex")));
+        assert_eq!(env.appimage, Some(PathBuf::from("/home/user/ImHex.AppImage")));
+        assert!(DiscoveryEnv::from_env().data_dirs.iter().all(|dir| dir.is_absolute()));
+    }
+}
@@ -0,0 +1,135 @@
+#[path = "../src/network.rs"]
+mod network;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use network::{NetworkClient, NetworkError, Selection, DEFAULT_ADDRESS, SELECTION_ENDPOINT};
+    use serde_json::Value;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::{SocketAddr, TcpListener};
+    use std::thread::{self, JoinHandle};
+    use std::time::Duration;
+
+    // Stand-in for ImHex: answers a single request && hands back what it received
+    fn spawn_server(reply: &'static str) -> (SocketAddr, JoinHandle<Value>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request = Vec::new();
+            reader.read_until(0, &mut request).unwrap();
+            request.pop();
+
+            let mut stream = reader.into_inner();
+            stream.write_all(reply.as_bytes()).unwrap();
+            stream.write_all(&[0]).unwrap();
+            serde_json::from_slice(&request).unwrap()
+        });
+        (address, handle)
+    }
+
+    fn client(address: SocketAddr) -> NetworkClient {
+        NetworkClient::new(address).with_timeout(Duration::from_secs(2))
+    }
+
+    #[test]
+    fn test_query_selection() {
+        let (address, server) =
+            spawn_server(r#"{"status":"success","data":{"offset":1024,"size":16}}"#);
+
+        let selection = client(address).query_selection().unwrap();
+        assert_eq!(selection, Some(Selection { offset: 0x400, length: 16, preview: Vec::new() }));
+
+        let request = server.join().unwrap();
+        assert_eq!(request["endpoint"], SELECTION_ENDPOINT);
+        assert_eq!(request["data"]["preview"], 0);
+    }
+
+    #[test]
+    fn test_query_selection_with_preview() {
+        let (address, server) = spawn_server(
+            r#"{"status":"success","data":{"offset":0,"size":64,"bytes":"7f454c46"}}"#,
+        );
+
+        let selection = client(address).with_preview_len(4).query_selection().unwrap().unwrap();
+        assert_eq!(selection.preview, vec![0x7F, 0x45, 0x4C, 0x46]);
+        assert_eq!(selection.to_string(), "0x0-0x3F (64 bytes) 7F 45 4C 46");
+        assert_eq!(server.join().unwrap()["data"]["preview"], 4);
+    }
+
+    #[test]
+    fn test_preview_truncated_to_requested_length() {
+        let (address, _server) = spawn_server(
+            r#"{"status":"success","data":{"offset":0,"size":8,"bytes":"0011223344556677"}}"#,
+        );
+
+        let selection = client(address).with_preview_len(2).query_selection().unwrap().unwrap();
+        assert_eq!(selection.preview, vec![0x00, 0x11]);
+    }
+
+    #[test]
+    fn test_no_selection() {
+        let (address, _server) = spawn_server(r#"{"status":"success","data":{}}"#);
+        assert_eq!(client(address).query_selection().unwrap(), None);
+
+        let (address, _server) =
+            spawn_server(r#"{"status":"success","data":{"offset":12,"size":0}}"#);
+        assert_eq!(client(address).query_selection().unwrap(), None);
+    }
+
+    #[test]
+    fn test_endpoint_error() {
+        let (address, _server) =
+            spawn_server(r#"{"status":"error","data":{"error":"Endpoint not found"}}"#);
+
+        match client(address).query_selection() {
+            Err(NetworkError::Endpoint(message)) => assert_eq!(message, "Endpoint not found"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_malformed_response() {
+        let (address, _server) = spawn_server("not json");
+        assert!(matches!(client(address).query_selection(), Err(NetworkError::Protocol(_))));
+
+        let (address, _server) =
+            spawn_server(r#"{"status":"success","data":{"offset":0,"size":2,"bytes":"zz"}}"#);
+        assert!(matches!(client(address).query_selection(), Err(NetworkError::Protocol(_))));
+    }
+
+    #[test]
+    fn test_interface_disabled() {
+        let address = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        assert!(matches!(client(address).query_selection(), Err(NetworkError::Io(_))));
+    }
+
+    #[test]
+    fn test_unresponsive_interface_times_out() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let _server = thread::spawn(move || {
+            let (_stream, _) = listener.accept().unwrap();
+            thread::sleep(Duration::from_secs(1));
+        });
+
+        let result =
+            NetworkClient::new(address).with_timeout(Duration::from_millis(100)).query_selection();
+        assert!(matches!(result, Err(NetworkError::Io(_))));
+    }
+
+    #[test]
+    fn test_selection_display() {
+        let single = Selection { offset: 0x10, length: 1, preview: Vec::new() };
+        assert_eq!(single.to_string(), "0x10-0x10 (1 byte)");
+        assert_eq!(single.end(), 0x10);
+    }
+
+    #[test]
+    fn test_default_address() {
+        let _client = NetworkClient::default();
+        assert!(DEFAULT_ADDRESS.ends_with(":31337"));
+    }
+}
@@ -0,0 +1,141 @@
+#[path = "../src/pattern.rs"]
+mod pattern;
+#[path = "../src/project.rs"]
+#[allow(dead_code)]
+mod project;
+#[path = "../src/resolver.rs"]
+#[allow(dead_code)]
+mod resolver;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pattern::{find_patterns, is_pattern_file, ActivePattern, PatternSource, PatternWatcher, PATTERNS_DIR};
+    use project::ProjectSummary;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, SystemTime};
+    use tempfile::tempdir;
+
+    fn write_pattern(path: &Path, source: &str, age: Duration) -> PathBuf {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, source).unwrap();
+        let modified = SystemTime::now() - age;
+        fs::File::options().write(true).open(path).unwrap().set_modified(modified).unwrap();
+        path.to_path_buf()
+    }
+
+    fn project(pattern: Option<&str>) -> ProjectSummary {
+        ProjectSummary {
+            name: "router".to_string(),
+            version: None,
+            providers: Vec::new(),
+            bookmarks: 0,
+            pattern: pattern.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_active_pattern_display() {
+        let saved = ActivePattern::saved(Path::new("/home/user/.config/imhex/patterns/elf.hexpat"));
+        assert_eq!(saved.to_string(), "Writing pattern: elf.hexpat");
+        assert_eq!(saved.source, PatternSource::Saved);
+
+        let attached = ActivePattern::from_project(&project(Some("u8 x @ 0;")), None).unwrap();
+        assert_eq!(attached.to_string(), "Writing pattern: pattern_source.hexpat");
+        assert_eq!(attached.source, PatternSource::Project);
+
+        let named = ActivePattern::from_project(&project(Some("u8 x @ 0;")), Some("/p/fw.hexpat".into())).unwrap();
+        assert_eq!(named.name, "fw.hexpat");
+        assert!(ActivePattern::from_project(&project(None), None).is_none());
+    }
+
+    #[test]
+    fn test_find_patterns_recurses() {
+        let dir = tempdir().unwrap();
+        write_pattern(&dir.path().join("elf.hexpat"), "", Duration::ZERO);
+        write_pattern(&dir.path().join("fs/ext4.hexpat"), "", Duration::ZERO);
+        fs::write(dir.path().join("notes.txt"), "").unwrap();
+
+        let mut names: Vec<PathBuf> = find_patterns(dir.path()).into_iter().map(|(path, _)| path).collect();
+        names.sort();
+        assert_eq!(names, vec![dir.path().join("elf.hexpat"), dir.path().join("fs/ext4.hexpat")]);
+        assert!(is_pattern_file(Path::new("a/b.hexpat")));
+        assert!(!is_pattern_file(Path::new("a/b.hexpat.bak")));
+    }
+
+    #[test]
+    fn test_recent_pattern_from_initial_scan() {
+        let config = tempdir().unwrap();
+        let patterns = config.path().join(PATTERNS_DIR);
+        write_pattern(&patterns.join("old.hexpat"), "", Duration::from_secs(3600));
+        let fresh = write_pattern(&patterns.join("pe.hexpat"), "", Duration::from_secs(30));
+
+        let mut watcher = PatternWatcher::for_config_dirs(&[config.path().to_path_buf(), config.path().join("missing")]);
+        assert_eq!(watcher.dirs(), [patterns]);
+        assert_eq!(watcher.recent_pattern(SystemTime::now()), Some(fresh));
+
+        let later = SystemTime::now() + Duration::from_secs(3600);
+        assert_eq!(watcher.recent_pattern(later), None);
+    }
+
+    #[test]
+    fn test_old_patterns_are_not_recent() {
+        let dir = tempdir().unwrap();
+        write_pattern(&dir.path().join("old.hexpat"), "", Duration::from_secs(3600));
+
+        let mut watcher = PatternWatcher::new(vec![dir.path().to_path_buf()]).with_recent_window(Duration::from_secs(60));
+        assert_eq!(watcher.recent_pattern(SystemTime::now()), None);
+    }
+
+    #[test]
+    fn test_detects_new_saves() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("nested")).unwrap();
+        let mut watcher = PatternWatcher::new(vec![dir.path().to_path_buf()]).with_rescan_interval(Duration::ZERO);
+        assert_eq!(watcher.recent_pattern(SystemTime::now()), None);
+        #[cfg(target_os = "linux")]
+        assert!(watcher.uses_inotify());
+
+        let saved = dir.path().join("nested/elf.hexpat");
+        fs::write(&saved, "struct Elf {};").unwrap();
+        fs::write(dir.path().join("scratch.txt"), "ignored").unwrap();
+        assert_eq!(watcher.recent_pattern(SystemTime::now()), Some(saved));
+
+        // Folders created after the watcher started are followed too
+        let created = dir.path().join("created");
+        fs::create_dir_all(&created).unwrap();
+        watcher.recent_pattern(SystemTime::now());
+        let saved = created.join("zip.hexpat");
+        fs::write(&saved, "struct Zip {};").unwrap();
+        assert_eq!(watcher.recent_pattern(SystemTime::now()), Some(saved));
+    }
+
+    #[test]
+    fn test_rescans_are_throttled() {
+        let dir = tempdir().unwrap();
+        let watcher = PatternWatcher::new(vec![dir.path().to_path_buf()]).with_rescan_interval(Duration::from_secs(5));
+        #[cfg(target_os = "linux")]
+        let watcher = watcher.without_inotify();
+        let mut watcher = watcher;
+
+        let start = SystemTime::now();
+        assert_eq!(watcher.recent_pattern(start), None);
+        let saved = dir.path().join("elf.hexpat");
+        fs::write(&saved, "struct Elf {};").unwrap();
+        // The scan from a second ago still stands
+        assert_eq!(watcher.recent_pattern(start + Duration::from_secs(1)), None);
+        assert_eq!(watcher.recent_pattern(start + Duration::from_secs(5)), Some(saved));
+    }
+
+    #[test]
+    fn test_find_source_matches_contents() {
+        let dir = tempdir().unwrap();
+        write_pattern(&dir.path().join("a.hexpat"), "u8 a @ 0;", Duration::ZERO);
+        let b = write_pattern(&dir.path().join("sub/b.hexpat"), "u16 b @ 0;\n", Duration::ZERO);
+
+        let watcher = PatternWatcher::new(vec![dir.path().to_path_buf()]);
+        assert_eq!(watcher.find_source("  u16 b @ 0;"), Some(b));
+        assert_eq!(watcher.find_source("u32 c @ 0;"), None);
+    }
+}
@@ -0,0 +1,696 @@
+#[path = "../src/activity.rs"]
+mod activity;
+#[path = "../src/entropy.rs"]
+#[allow(dead_code)]
+mod entropy;
+#[path = "../src/error.rs"]
+#[allow(dead_code)]
+mod error;
+#[path = "../src/binary.rs"]
+#[allow(dead_code)]
+mod binary;
+#[path = "../src/format.rs"]
+mod format;
+#[path = "../src/imhex_config.rs"]
+#[allow(dead_code)]
+mod imhex_config;
+#[path = "../src/network.rs"]
+#[allow(dead_code)]
+mod network;
+#[path = "../src/presence.rs"]
+#[allow(dead_code)]
+mod presence;
+#[path = "../src/pattern.rs"]
+#[allow(dead_code)]
+mod pattern;
+#[path = "../src/privacy.rs"]
+#[allow(dead_code)]
+mod privacy;
+#[path = "../src/probe.rs"]
+mod probe;
+#[path = "../src/process.rs"]
+#[allow(dead_code)]
+mod process;
+#[path = "../src/project.rs"]
+#[allow(dead_code)]
+mod project;
+#[path = "../src/resolver.rs"]
+#[allow(dead_code)]
+mod resolver;
+#[path = "../src/settings.rs"]
+#[allow(dead_code)]
+mod settings;
+#[path = "../src/template.rs"]
+#[allow(dead_code)]
+mod template;
+#[path = "../src/title.rs"]
+#[allow(dead_code)]
+mod title;
+#[path = "../src/window.rs"]
+#[allow(dead_code)]
+mod window;
+#[path = "../src/utils.rs"]
+#[allow(dead_code)]
+mod utils;
+#[path = "support/scripted_probe.rs"]
+#[allow(dead_code)]
+mod scripted_probe;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use error::AppError;
+    use imhex_config::ImhexSettings;
+    use presence::{tick, run_discord_loop, ActivityAssets, ActivityButton, ActivityImage, ActivitySink, AppState};
+    use probe::ImhexProbe;
+    use scripted_probe::{ProbeFrame, ScriptedProbe};
+    use settings::{ButtonSettings, ImageSettings, LineTemplates, PrivacyMode, PrivacySettings, Settings, TemplateSettings};
+    use std::sync::atomic::Ordering;
+    use std::time::Duration;
+
+    const START: i64 = 1_700_000_000;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    enum Update {
+        Set { state: String, details: String, start_time: Option<i64>, assets: ActivityAssets },
+        Clear,
+    }
+
+    #[derive(Default)]
+    struct RecordingSink {
+        updates: Vec<Update>,
+        fail_updates: bool,
+    }
+
+    impl ActivitySink for RecordingSink {
+        fn update_activity(&mut self, state: String, details: String, start_time: Option<i64>, assets: ActivityAssets) -> Result<(), AppError> {
+            if self.fail_updates {
+                return Err(AppError::Discord("pipe closed".to_string()));
+            }
+            self.updates.push(Update::Set { state, details, start_time, assets });
+            Ok(())
+        }
+
+        fn clear_activity(&mut self) -> Result<(), AppError> {
+            self.updates.push(Update::Clear);
+            Ok(())
+        }
+    }
+
+    fn set(state: &str, details: &str, start_time: Option<i64>) -> Update {
+        Update::Set { state: state.to_string(), details: details.to_string(), start_time, assets: ActivityAssets::default() }
+    }
+
+    fn set_with_image(state: &str, details: &str, start_time: Option<i64>, key: &str, text: &str) -> Update {
+        Update::Set {
+            state: state.to_string(),
+            details: details.to_string(),
+            start_time,
+            assets: ActivityAssets { large_image: Some(image(key, text)), ..ActivityAssets::default() },
+        }
+    }
+
+    fn image(key: &str, text: &str) -> ActivityImage {
+        ActivityImage { key: key.to_string(), text: text.to_string() }
+    }
+
+    fn run_script(frames: Vec<ProbeFrame>) -> (Vec<Update>, AppState) {
+        run_script_with_settings(frames, Settings::default())
+    }
+
+    fn run_script_with_settings(frames: Vec<ProbeFrame>, settings: Settings) -> (Vec<Update>, AppState) {
+        let mut probe = ScriptedProbe::new(frames);
+        let mut sink = RecordingSink::default();
+        let mut state = AppState::with_settings(settings);
+        while !probe.is_exhausted() {
+            tick(&mut probe, &mut sink, &mut state).unwrap();
+        }
+        (sink.updates, state)
+    }
+
+    #[test]
+    fn test_not_running_sends_nothing() {
+        let (updates, state) = run_script(vec![ProbeFrame::stopped(), ProbeFrame::stopped()]);
+        assert!(updates.is_empty());
+        assert!(!state.imhex_running);
+    }
+
+    #[test]
+    fn test_analyzing_file() {
+        let (updates, state) = run_script(vec![ProbeFrame::running(42, START)
+            .with_window_title("ImHex - firmware.bin")
+            .with_selection(0x10, 0x20)]);
+
+        assert_eq!(updates, vec![set("Bytes: [0x10-0x2F (32 bytes)]", "Analyzing: [firmware.bin]", Some(START))]);
+        assert!(state.imhex_running);
+        assert_eq!(state.start_time, Some(START));
+    }
+
+    #[test]
+    fn test_opened_file_follows_primary_window() {
+        let (_, state) = run_script(vec![ProbeFrame::running(42, START)
+            .with_window_title("ImHex - firmware.bin")
+            .with_file("/tmp/firmware.bin", 1024)]);
+        let file = state.opened_file.unwrap();
+        assert_eq!(file.path.to_str(), Some("/tmp/firmware.bin"));
+        assert_eq!(file.size, 1024);
+
+        let (_, state) = run_script(vec![
+            ProbeFrame::running(42, START).with_window_title("ImHex - a.bin").with_file("/tmp/a.bin", 1),
+            ProbeFrame::running(42, START).with_window_title("ImHex - Project fw").with_file("/tmp/a.bin", 1),
+        ]);
+        assert!(state.opened_file.is_none());
+
+        let (_, state) = run_script(vec![
+            ProbeFrame::running(42, START).with_window_title("ImHex - a.bin").with_file("/tmp/a.bin", 1),
+            ProbeFrame::stopped(),
+        ]);
+        assert!(state.opened_file.is_none());
+    }
+
+    #[test]
+    fn test_imhex_settings_follow_the_process() {
+        let settings = ImhexSettings { language: Some("de-DE".to_string()), theme: Some("Dark".to_string()), ..ImhexSettings::default() };
+        let (_, state) = run_script(vec![ProbeFrame::running(42, START).with_imhex_settings(settings.clone())]);
+        assert_eq!(state.imhex_settings, Some(settings.clone()));
+
+        let (_, state) = run_script(vec![ProbeFrame::running(42, START).with_imhex_settings(settings), ProbeFrame::stopped()]);
+        assert!(state.imhex_settings.is_none());
+    }
+
+    #[test]
+    fn test_file_format_in_details_and_image() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logo.png");
+        std::fs::write(&path, b"\x89PNG\r\n\x1A\n\0\0\0\rIHDR").unwrap();
+        let path = path.to_str().unwrap();
+
+        let (updates, _) = run_script(vec![
+            ProbeFrame::running(42, START).with_window_title("ImHex - logo.png").with_file(path, 16),
+            ProbeFrame::running(42, START)
+                .with_window_title("ImHex - logo.png")
+                .with_window_title("ImHex - b.bin")
+                .with_file(path, 16),
+        ]);
+        assert_eq!(
+            updates,
+            vec![
+                set_with_image("Bytes: [None]", "Analyzing PNG image: [logo.png]", Some(START), "format_image", "PNG image"),
+                set_with_image(
+                    "Bytes: [None]",
+                    "Analyzing PNG image: [logo.png] (2 files open)",
+                    Some(START),
+                    "format_image",
+                    "PNG image"
+                ),
+            ]
+        );
+    }
+
+    fn asset_settings() -> Settings {
+        Settings {
+            large_image: ImageSettings { key: "imhex".to_string(), text: "ImHex in {language}".to_string() },
+            small_image: ImageSettings { key: "theme".to_string(), text: "{theme|Default} theme{?workspace} in {workspace}{/workspace}".to_string() },
+            buttons: vec![ButtonSettings { label: "Look up {file}".to_string(), url: "https://example.com/?q={file}".to_string() }],
+            ..Settings::default()
+        }
+    }
+
+    #[test]
+    fn test_configured_images_and_buttons() {
+        let imhex = ImhexSettings { language: Some("de-DE".to_string()), theme: Some("Dark".to_string()), ..ImhexSettings::default() };
+        let light = ImhexSettings { theme: Some("Light".to_string()), ..imhex.clone() };
+        let frame = || ProbeFrame::running(42, START).with_window_title("ImHex - my fw&1.bin");
+        let (updates, _) = run_script_with_settings(
+            vec![frame().with_imhex_settings(imhex.clone()), frame().with_imhex_settings(light), ProbeFrame::running(42, START)],
+            asset_settings(),
+        );
+
+        let assets = |theme: &str| ActivityAssets {
+            large_image: Some(image("imhex", "ImHex in de-DE")),
+            small_image: Some(image("theme", &format!("{} theme", theme))),
+            buttons: vec![ActivityButton { label: "Look up my fw&1.bin".to_string(), url: "https://example.com/?q=my%20fw%261.bin".to_string() }],
+        };
+        let analyzing = |assets| Update::Set {
+            state: "Bytes: [None]".to_string(),
+            details: "Analyzing: [my fw&1.bin]".to_string(),
+            start_time: Some(START),
+            assets,
+        };
+        // Without a window or ImHex settings the placeholders are empty or fall back
+        let idle = ActivityAssets {
+            large_image: Some(image("imhex", "ImHex in ")),
+            small_image: Some(image("theme", "Default theme")),
+            buttons: vec![ActivityButton { label: "Look up ".to_string(), url: "https://example.com/?q=".to_string() }],
+        };
+        assert_eq!(
+            updates,
+            vec![
+                analyzing(assets("Dark")),
+                analyzing(assets("Light")),
+                Update::Set { state: String::new(), details: "Idle".to_string(), start_time: None, assets: idle },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_icon_takes_the_configured_text() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logo.png");
+        std::fs::write(&path, b"\x89PNG\r\n\x1A\n\0\0\0\rIHDR").unwrap();
+        let settings = Settings { large_image: ImageSettings { key: "imhex".to_string(), text: "{format}: {file}".to_string() }, ..Settings::default() };
+
+        let (updates, _) = run_script_with_settings(
+            vec![ProbeFrame::running(42, START).with_window_title("ImHex - logo.png").with_file(path.to_str().unwrap(), 16)],
+            settings,
+        );
+        assert_eq!(
+            updates,
+            vec![set_with_image("Bytes: [None]", "Analyzing PNG image: [logo.png]", Some(START), "format_image", "PNG image: logo.png")]
+        );
+    }
+
+    #[test]
+    fn test_privacy_applies_to_details_and_placeholders() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("acme.hexproj");
+        write_project(&project, &[]);
+        let settings = Settings {
+            privacy: PrivacySettings { mode: PrivacyMode::Extension, deny: vec!["acme*".to_string()], ..PrivacySettings::default() },
+            buttons: vec![ButtonSettings { label: "{file}".to_string(), url: "https://example.com/{file}/{project}".to_string() }],
+            ..Settings::default()
+        };
+
+        let (updates, _) = run_script_with_settings(
+            vec![
+                ProbeFrame::running(42, START).with_window_title("ImHex - acme_fw.bin"),
+                ProbeFrame::running(42, START).with_window_title("ImHex - router.img"),
+                ProbeFrame::running(42, START).with_window_title("ImHex - Project: acme").with_project(project.to_str().unwrap()),
+            ],
+            settings,
+        );
+
+        let details: Vec<&str> = updates
+            .iter()
+            .map(|update| match update {
+                Update::Set { details, .. } => details.as_str(),
+                Update::Clear => "",
+            })
+            .collect();
+        assert_eq!(details, vec!["Analyzing: [a file]", "Analyzing: [*.img]", "Project: a file (1 file, 0 bookmarks)"]);
+        let urls: Vec<&str> = updates
+            .iter()
+            .filter_map(|update| match update {
+                Update::Set { assets, .. } => Some(assets.buttons[0].url.as_str()),
+                Update::Clear => None,
+            })
+            .collect();
+        assert_eq!(urls, vec!["https://example.com/a%20file/", "https://example.com/%2A.img/", "https://example.com/a%20file/a%20file"]);
+    }
+
+    #[test]
+    fn test_away_keeps_configured_assets() {
+        let (updates, _) = run_script_with_settings(
+            vec![ProbeFrame::running(42, START).with_window_title("ImHex - a.bin").with_idle_secs(86_400)],
+            asset_settings(),
+        );
+        match &updates[..] {
+            [Update::Set { details, assets, .. }] => {
+                assert_eq!(details, "Away");
+                assert_eq!(assets.large_image, Some(image("imhex", "ImHex in ")));
+                assert_eq!(assets.buttons[0].url, "https://example.com/?q=");
+            }
+            other => panic!("unexpected updates {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_executable_summary_in_image_text_and_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("init");
+        let mut elf = vec![0u8; 64];
+        elf[..7].copy_from_slice(b"\x7fELF\x02\x01\x01");
+        elf[16] = 2;
+        elf[18] = 0xB7;
+        elf[24..32].copy_from_slice(&0x40_0000u64.to_le_bytes());
+        std::fs::write(&path, &elf).unwrap();
+        let path = path.to_str().unwrap();
+
+        let frame = ProbeFrame::running(42, START).with_window_title("ImHex - init").with_file(path, 64);
+        let mut probe = ScriptedProbe::new(vec![frame.clone(), frame]);
+        let mut sink = RecordingSink::default();
+        let mut state = AppState::new();
+        while !probe.is_exhausted() {
+            tick(&mut probe, &mut sink, &mut state).unwrap();
+        }
+
+        assert_eq!(
+            sink.updates[0],
+            set_with_image(
+                "Bytes: [None]",
+                "Analyzing ELF AArch64 executable: [init]",
+                Some(START),
+                "format_executable",
+                "AArch64, 64-bit little-endian, entry 0x400000, stripped, 0 sections"
+            )
+        );
+        // The entropy profile may finish on either tick, so only the format log is checked
+        let format_events: Vec<&String> = probe.events().iter().filter(|event| event.starts_with("Analyzing")).collect();
+        assert_eq!(
+            format_events,
+            [&format!(
+                "Analyzing {}: ELF AArch64 executable (AArch64, 64-bit little-endian, entry 0x400000, stripped, 0 sections)",
+                path
+            )]
+        );
+    }
+
+    #[test]
+    fn test_entropy_verdict_is_logged() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("zeros.bin");
+        std::fs::write(&path, [0u8; 8192]).unwrap();
+        let path = path.to_str().unwrap();
+
+        let mut probe =
+            ScriptedProbe::new(vec![ProbeFrame::running(42, START).with_window_title("ImHex - zeros.bin").with_file(path, 8192)]);
+        let mut sink = RecordingSink::default();
+        let mut state = AppState::new();
+        let expected = format!("Entropy of {}: 0.00 bits/byte, mostly padding (0% of 2 blocks high entropy)", path);
+        for _ in 0..500 {
+            tick(&mut probe, &mut sink, &mut state).unwrap();
+            if probe.events().contains(&expected) {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        assert_eq!(probe.events().iter().filter(|event| **event == expected).count(), 1);
+        assert!(state.entropy.profile().is_some());
+
+        // Ticking on keeps the profile without logging it again
+        tick(&mut probe, &mut sink, &mut state).unwrap();
+        assert_eq!(probe.events().iter().filter(|event| event.starts_with("Entropy")).count(), 1);
+    }
+
+    #[test]
+    fn test_entropy_verdict_is_a_placeholder() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("zeros.bin");
+        std::fs::write(&path, [0u8; 8192]).unwrap();
+        let frame = ProbeFrame::running(42, START).with_window_title("ImHex - zeros.bin").with_file(path.to_str().unwrap(), 8192);
+
+        let templates = TemplateSettings {
+            analyzing: LineTemplates { details: Some("{file}{?entropy}, {entropy}{/entropy}".to_string()), state: Some(String::new()) },
+            ..TemplateSettings::default()
+        };
+        let mut probe = ScriptedProbe::new(vec![frame]);
+        let mut sink = RecordingSink::default();
+        let mut state = AppState::with_settings(Settings { templates, ..Settings::default() });
+        tick(&mut probe, &mut sink, &mut state).unwrap();
+        assert!(state.entropy.wait(Duration::from_secs(5)));
+        tick(&mut probe, &mut sink, &mut state).unwrap();
+
+        assert_eq!(sink.updates.last(), Some(&set("", "zeros.bin, mostly padding", Some(START))));
+    }
+
+    #[test]
+    fn test_welcome_screen_is_idle() {
+        let (updates, _) = run_script(vec![ProbeFrame::running(42, START).with_window_title("ImHex")]);
+        assert_eq!(updates, vec![set("", "Idle", Some(START))]);
+    }
+
+    #[test]
+    fn test_project_title() {
+        let (updates, _) =
+            run_script(vec![ProbeFrame::running(42, START).with_window_title("ImHex - Project firmware (*)")]);
+        assert_eq!(updates, vec![set("Bytes: [None]", "Analyzing: [firmware]", Some(START))]);
+    }
+
+    // Writes a .hexproj with one file provider && the given extra entries
+    fn write_project(path: &std::path::Path, extra: &[(&str, &str)]) {
+        let mut builder = tar::Builder::new(Vec::new());
+        let provider = r#"{"type": "hex.builtin.provider.file", "settings": {"path": "/data/fw.bin"}}"#;
+        let entries = [("IMHEX_METADATA", "HEX\n1.35.4\n"), ("providers/0.json", provider)];
+        for (name, contents) in entries.iter().chain(extra) {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_cksum();
+            builder.append_data(&mut header, name, contents.as_bytes()).unwrap();
+        }
+        std::fs::write(path, builder.into_inner().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_project_summary_in_details() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("firmware.hexproj");
+        write_project(&path, &[("0/bookmarks.json", r#"{"bookmarks": [{}, {}]}"#)]);
+
+        let frame = ProbeFrame::running(42, START)
+            .with_window_title("ImHex - Project firmware")
+            .with_project(path.to_str().unwrap());
+        let mut probe = ScriptedProbe::new(vec![frame.clone(), frame, ProbeFrame::running(42, START)]);
+        let mut sink = RecordingSink::default();
+        let mut state = AppState::new();
+        for _ in 0..2 {
+            tick(&mut probe, &mut sink, &mut state).unwrap();
+        }
+
+        assert_eq!(sink.updates[0], set("Bytes: [None]", "Project: firmware (1 file, 2 bookmarks)", Some(START)));
+        assert_eq!(sink.updates[0], sink.updates[1]);
+        assert_eq!(probe.events(), ["Project: firmware (1 file, 2 bookmarks) [/data/fw.bin], no pattern"]);
+        assert_eq!(state.project.as_ref().map(|project| project.bookmarks), Some(2));
+
+        tick(&mut probe, &mut sink, &mut state).unwrap();
+        assert!(state.project.is_none());
+    }
+
+    #[test]
+    fn test_saved_pattern_replaces_details() {
+        let running = ProbeFrame::running(42, START).with_window_title("ImHex - a.out");
+        let saved = running.clone().with_saved_pattern("/home/user/.config/imhex/patterns/elf.hexpat");
+        let mut probe = ScriptedProbe::new(vec![running.clone(), saved.clone(), saved, running]);
+        let mut sink = RecordingSink::default();
+        let mut state = AppState::new();
+        while !probe.is_exhausted() {
+            tick(&mut probe, &mut sink, &mut state).unwrap();
+        }
+
+        let analyzing = set("Bytes: [None]", "Analyzing: [a.out]", Some(START));
+        let writing = set("Bytes: [None]", "Writing pattern: elf.hexpat", Some(START));
+        assert_eq!(sink.updates, vec![analyzing.clone(), writing.clone(), writing, analyzing]);
+        assert_eq!(probe.events(), ["Writing pattern: elf.hexpat (/home/user/.config/imhex/patterns/elf.hexpat)"]);
+        assert!(state.pattern.is_none());
+    }
+
+    #[test]
+    fn test_project_pattern_is_tracked() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fw.hexproj");
+        write_project(&path, &[("0/pattern_source.hexpat", "u32 magic @ 0;")]);
+
+        let (updates, state) = run_script(vec![ProbeFrame::running(42, START)
+            .with_window_title("ImHex - Project fw")
+            .with_project(path.to_str().unwrap())]);
+        assert_eq!(updates, vec![set("Bytes: [None]", "Project: fw (1 file, 0 bookmarks)", Some(START))]);
+        let pattern = state.pattern.unwrap();
+        assert_eq!(pattern.source, pattern::PatternSource::Project);
+        assert_eq!(pattern.to_string(), "Writing pattern: pattern_source.hexpat");
+    }
+
+    #[test]
+    fn test_multiple_windows_prefers_focused() {
+        let (updates, _) = run_script(vec![ProbeFrame::running(42, START)
+            .with_window_title("ImHex - a.bin")
+            .with_focused_window_title("ImHex - b.bin")
+            .with_window_title("ImHex - a.bin")]);
+        assert_eq!(updates, vec![set("Bytes: [None]", "Analyzing: [b.bin] (2 files open)", Some(START))]);
+    }
+
+    #[test]
+    fn test_multiple_windows_without_focus_uses_most_recent() {
+        let (updates, _) = run_script(vec![ProbeFrame::running(42, START)
+            .with_window_title("ImHex - top.bin")
+            .with_window_title("ImHex")]);
+        assert_eq!(updates, vec![set("Bytes: [None]", "Analyzing: [top.bin]", Some(START))]);
+    }
+
+    #[test]
+    fn test_running_without_window() {
+        let (updates, _) = run_script(vec![ProbeFrame::running(42, START)]);
+        assert_eq!(updates, vec![set("", "Idle", None)]);
+    }
+
+    #[test]
+    fn test_session_lifecycle() {
+        let (updates, state) = run_script(vec![
+            ProbeFrame::stopped(),
+            ProbeFrame::running(42, START).with_window_title("ImHex"),
+            ProbeFrame::running(42, START).with_window_title("ImHex - a.bin"),
+            ProbeFrame::stopped(),
+            ProbeFrame::stopped(),
+            ProbeFrame::running(43, START + 60).with_window_title("ImHex - b.bin"),
+        ]);
+
+        assert_eq!(
+            updates,
+            vec![
+                set("", "Idle", Some(START)),
+                set("Bytes: [None]", "Analyzing: [a.bin]", Some(START)),
+                Update::Clear,
+                set("Bytes: [None]", "Analyzing: [b.bin]", Some(START + 60)),
+            ]
+        );
+        assert_eq!(state.start_time, Some(START + 60));
+    }
+
+    #[test]
+    fn test_start_time_kept_across_files() {
+        let (updates, _) = run_script(vec![
+            ProbeFrame::running(42, START).with_window_title("ImHex - a.bin"),
+            ProbeFrame::running(42, START + 5).with_window_title("ImHex - b.bin"),
+        ]);
+
+        assert!(updates.iter().all(|update| match update {
+            Update::Set { start_time, .. } => *start_time == Some(START),
+            Update::Clear => false,
+        }));
+    }
+
+    #[test]
+    fn test_idle_user_is_away() {
+        let (updates, _) = run_script(vec![
+            ProbeFrame::running(42, START).with_focused_window_title("ImHex - a.bin").with_idle_secs(10),
+            ProbeFrame::running(42, START).with_focused_window_title("ImHex - a.bin").with_idle_secs(300),
+            ProbeFrame::running(42, START).with_focused_window_title("ImHex - a.bin").with_idle_secs(0),
+        ]);
+
+        assert_eq!(
+            updates,
+            vec![
+                set("Bytes: [None]", "Analyzing: [a.bin]", Some(START)),
+                set("", "Away", Some(START)),
+                set("Bytes: [None]", "Analyzing: [a.bin]", Some(START)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_away_text_and_timeout_are_configurable() {
+        let away = LineTemplates { details: Some("Getting coffee".to_string()), state: Some("brb".to_string()) };
+        let settings = Settings { away_timeout_secs: 60, templates: TemplateSettings { away, ..TemplateSettings::default() }, ..Settings::default() };
+        let (updates, _) = run_script_with_settings(
+            vec![ProbeFrame::running(42, START).with_window_title("ImHex - a.bin").with_idle_secs(61)],
+            settings,
+        );
+        assert_eq!(updates, vec![set("brb", "Getting coffee", Some(START))]);
+    }
+
+    #[test]
+    fn test_background_imhex_is_paused() {
+        let away = LineTemplates { details: Some("Away from keyboard".to_string()), state: None };
+        let settings = Settings { away_timeout_secs: 1, templates: TemplateSettings { away, ..TemplateSettings::default() }, ..Settings::default() };
+        let mut probe = ScriptedProbe::new(vec![
+            ProbeFrame::running(42, START).with_focused_window_title("ImHex - a.bin").with_idle_secs(0),
+            ProbeFrame::running(42, START).with_window_title("ImHex - a.bin").with_idle_secs(0),
+        ]);
+        let mut sink = RecordingSink::default();
+        let mut state = AppState::with_settings(settings);
+
+        tick(&mut probe, &mut sink, &mut state).unwrap();
+        std::thread::sleep(Duration::from_millis(1100));
+        tick(&mut probe, &mut sink, &mut state).unwrap();
+
+        // Paused shows the away lines unless it has its own
+        assert_eq!(sink.updates, vec![set("Bytes: [None]", "Analyzing: [a.bin]", Some(START)), set("", "Away from keyboard", Some(START))]);
+    }
+
+    #[test]
+    fn test_templates_per_state() {
+        let line = |details: &str, state: &str| LineTemplates { details: Some(details.to_string()), state: Some(state.to_string()) };
+        let templates = TemplateSettings {
+            idle: line("Waiting", "{theme|no theme}"),
+            analyzing: line("{file} ({ext}, {size}){?format} as {format}{/format}", "{selection|nothing} selected{!project}, no project{/project}"),
+            ..TemplateSettings::default()
+        };
+        let frame = || ProbeFrame::running(42, START).with_window_title("ImHex - dump.bin").with_file("/nonexistent/dump.bin", 1536);
+        let (updates, _) = run_script_with_settings(
+            vec![frame(), frame().with_selection(0x10, 32), ProbeFrame::running(42, START).with_window_title("ImHex")],
+            Settings { templates, ..Settings::default() },
+        );
+
+        assert_eq!(
+            updates,
+            vec![
+                set("nothing selected, no project", "dump.bin (bin, 1.5 KiB)", Some(START)),
+                set("0x10-0x2F (32 bytes) selected, no project", "dump.bin (bin, 1.5 KiB)", Some(START)),
+                set("no theme", "Waiting", Some(START)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_away_disabled() {
+        let settings = Settings { away_timeout_secs: 0, ..Settings::default() };
+        let (updates, _) = run_script_with_settings(
+            vec![ProbeFrame::running(42, START).with_window_title("ImHex - a.bin").with_idle_secs(86_400)],
+            settings,
+        );
+        assert_eq!(updates, vec![set("Bytes: [None]", "Analyzing: [a.bin]", Some(START))]);
+    }
+
+    #[test]
+    fn test_sink_error_propagates() {
+        let mut probe = ScriptedProbe::new(vec![ProbeFrame::running(42, START).with_window_title("ImHex - a.bin")]);
+        let mut sink = RecordingSink { fail_updates: true, ..RecordingSink::default() };
+        let mut state = AppState::new();
+
+        assert!(matches!(tick(&mut probe, &mut sink, &mut state), Err(AppError::Discord(_))));
+    }
+
+    #[test]
+    fn test_scripted_probe_repeats_last_frame() {
+        let mut probe = ScriptedProbe::new(vec![ProbeFrame::running(7, START).with_window_title("ImHex - x.bin")]);
+        assert_eq!(probe.imhex_process().map(|process| process.pid), Some(7));
+        assert!(probe.is_exhausted());
+        assert_eq!(probe.imhex_process().map(|process| process.pid), Some(7));
+        assert_eq!(probe.windows().primary().unwrap().state.display_name(), Some("x.bin"));
+        assert_eq!(probe.selection(), None);
+        assert_eq!(probe.imhex_focused(), Some(false));
+        assert_eq!(probe.idle_time(), None);
+    }
+
+    #[test]
+    fn test_window_events_wake_the_loop() {
+        let frame = ProbeFrame::running(42, START).with_window_title("ImHex - a.bin").with_window_event();
+        let renamed = ProbeFrame::running(42, START).with_window_title("ImHex - b.bin").with_window_event();
+        let mut state = AppState::new();
+        let mut probe = ScriptedProbe::new(vec![frame, renamed]).stop_when_exhausted(state.running.clone());
+        let mut sink = RecordingSink::default();
+
+        // Without the events each wait would sleep for a minute
+        let started = std::time::Instant::now();
+        run_discord_loop(&mut probe, &mut sink, &mut state, Duration::from_secs(60)).unwrap();
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert_eq!(
+            sink.updates,
+            vec![
+                set("Bytes: [None]", "Analyzing: [a.bin]", Some(START)),
+                set("Bytes: [None]", "Analyzing: [b.bin]", Some(START)),
+                Update::Clear
+            ]
+        );
+    }
+
+    #[test]
+    fn test_run_loop_clears_on_shutdown() {
+        let mut probe = ScriptedProbe::new(vec![ProbeFrame::running(42, START).with_window_title("ImHex - a.bin")]);
+        let mut sink = RecordingSink::default();
+        let mut state = AppState::new();
+        state.running.store(false, Ordering::SeqCst);
+
+        run_discord_loop(&mut probe, &mut sink, &mut state, Duration::from_millis(1)).unwrap();
+        assert_eq!(sink.updates, vec![Update::Clear]);
+    }
+}
@@ -0,0 +1,82 @@
+#[path = "../src/privacy.rs"]
+mod privacy;
+#[path = "../src/settings.rs"]
+#[allow(dead_code)]
+mod settings;
+#[path = "../src/template.rs"]
+#[allow(dead_code)]
+mod template;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use privacy::{extension_label, hashed_label, PrivacyFilter};
+    use settings::{PrivacyMode, PrivacySettings};
+    use std::path::Path;
+
+    fn filter(mode: PrivacyMode, allow: &[&str], deny: &[&str]) -> PrivacyFilter {
+        let settings = PrivacySettings {
+            mode,
+            allow: allow.iter().map(|glob| glob.to_string()).collect(),
+            deny: deny.iter().map(|glob| glob.to_string()).collect(),
+            salt: "pepper".to_string(),
+            ..PrivacySettings::default()
+        };
+        PrivacyFilter::new(&settings).unwrap()
+    }
+
+    #[test]
+    fn test_modes() {
+        let name = "acme_router_fw.bin";
+        assert_eq!(filter(PrivacyMode::Full, &[], &[]).label(name, None), name);
+        assert_eq!(filter(PrivacyMode::Extension, &[], &[]).label(name, None), "*.bin");
+        assert_eq!(filter(PrivacyMode::Extension, &[], &[]).label("Makefile", None), "a file");
+        assert_eq!(filter(PrivacyMode::Generic, &[], &[]).label(name, None), "a file");
+
+        let hashed = filter(PrivacyMode::Hashed, &[], &[]).label(name, None);
+        assert_eq!(hashed, hashed_label(name, "pepper"));
+        assert!(!hashed.contains("acme"));
+    }
+
+    #[test]
+    fn test_hashed_labels_are_stable_and_salted() {
+        let label = hashed_label("sample.exe", "");
+        assert_eq!(label.len(), "file-".len() + 8);
+        assert!(label.starts_with("file-"));
+        assert_eq!(label, hashed_label("sample.exe", ""));
+        assert_ne!(label, hashed_label("sample2.exe", ""));
+        assert_ne!(label, hashed_label("sample.exe", "salt"));
+    }
+
+    #[test]
+    fn test_extension_label() {
+        assert_eq!(extension_label("dump.tar.gz"), Some("*.gz".to_string()));
+        assert_eq!(extension_label(".bashrc"), None);
+        assert_eq!(extension_label("README"), None);
+    }
+
+    #[test]
+    fn test_rules_match_name_and_path() {
+        let filter = filter(PrivacyMode::Hashed, &["*.hexpat", "/home/*/ctf/**"], &["**/customers/**", "*secret*"]);
+        let path = |path: &'static str| Some(Path::new(path));
+
+        // Allowed names && paths are shown in full
+        assert_eq!(filter.mode_for("elf.hexpat", None), PrivacyMode::Full);
+        assert_eq!(filter.mode_for("chall.bin", path("/home/user/ctf/pwn/chall.bin")), PrivacyMode::Full);
+        // Deny wins over allow && the mode, matched case-insensitively
+        assert_eq!(filter.mode_for("acme.hexpat", path("/srv/Customers/acme/acme.hexpat")), PrivacyMode::Generic);
+        assert_eq!(filter.mode_for("TOP-SECRET.bin", None), PrivacyMode::Generic);
+        assert_eq!(filter.label("TOP-SECRET.bin", None), "a file");
+        // Everything else falls back to the mode
+        assert_eq!(filter.mode_for("fw.bin", path("/tmp/fw.bin")), PrivacyMode::Hashed);
+    }
+
+    #[test]
+    fn test_invalid_globs() {
+        let settings = PrivacySettings { deny: vec!["[unclosed".to_string()], ..PrivacySettings::default() };
+        assert!(PrivacyFilter::new(&settings).is_err());
+
+        let generic = PrivacyFilter::generic(&PrivacySettings { generic_label: "something".to_string(), ..settings });
+        assert_eq!(generic.label("fw.bin", None), "something");
+    }
+}
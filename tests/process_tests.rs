@@ -0,0 +1,190 @@
+#[path = "../src/process.rs"]
+mod process;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use process::{find_imhex_processes, is_imhex_executable_name, list_processes};
+
+    #[test]
+    fn test_imhex_executable_names() {
+        assert!(is_imhex_executable_name("imhex"));
+        assert!(is_imhex_executable_name("imhex-gui"));
+        assert!(is_imhex_executable_name("imhex-gui.exe"));
+        assert!(is_imhex_executable_name("ImHex.exe"));
+        assert!(is_imhex_executable_name("ImHex-1.35.4-x86_64.AppImage"));
+        assert!(!is_imhex_executable_name("imhex-updater"));
+        assert!(!is_imhex_executable_name("discord-imhex"));
+        assert!(!is_imhex_executable_name("bash"));
+    }
+
+    #[test]
+    fn test_find_imhex_processes_oldest_first() {
+        let processes = find_imhex_processes();
+        let start_times: Vec<i64> =
+            processes.iter().map(|process| process.start_time.unwrap_or(i64::MAX)).collect();
+        assert!(start_times.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+
+    #[test]
+    fn test_list_processes_finds_itself() {
+        let own_name = std::env::current_exe().unwrap().file_name().unwrap().to_string_lossy().into_owned();
+        let processes = list_processes(|name| name == own_name);
+        if cfg!(any(target_os = "linux", windows)) {
+            assert!(processes.iter().any(|process| process.pid == std::process::id()));
+        }
+        assert!(list_processes(|_| false).is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    mod proc_scan {
+        use super::super::process::{read_environ_var, read_executable, read_processes, scan_proc, ImhexProcess, ProcessEntry};
+        use std::fs;
+        use std::os::unix::fs::symlink;
+        use std::path::Path;
+        use tempfile::tempdir;
+
+        const BOOT_TIME: i64 = 1_700_000_000;
+
+        fn write_process(root: &Path, pid: u32, comm: &str, exe: &str, cmdline: &[&str], start_ticks: i64) {
+            let dir = root.join(pid.to_string());
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("comm"), format!("{}\n", comm)).unwrap();
+            symlink(exe, dir.join("exe")).unwrap();
+            fs::write(dir.join("cmdline"), cmdline.join("\0")).unwrap();
+            let stat = format!(
+                "{} ({}) S 1 {} {} 0 -1 4194560 100 0 0 0 10 5 0 0 20 0 12 0 {} 1000 200",
+                pid, comm, pid, pid, start_ticks
+            );
+            fs::write(dir.join("stat"), stat).unwrap();
+        }
+
+        fn setup_proc() -> tempfile::TempDir {
+            let root = tempdir().unwrap();
+            fs::write(root.path().join("stat"), format!("cpu  1 2 3 4\nbtime {}\nprocesses 10\n", BOOT_TIME)).unwrap();
+            fs::create_dir_all(root.path().join("self")).unwrap();
+            root
+        }
+
+        #[test]
+        fn test_scan_native_install() {
+            let root = setup_proc();
+            write_process(root.path(), 4242, "imhex", "/usr/bin/imhex", &["imhex", "sample.bin"], 12_345);
+            write_process(root.path(), 100, "bash", "/usr/bin/bash", &["bash"], 50);
+
+            let processes = scan_proc(root.path());
+            assert_eq!(processes, vec![ImhexProcess { pid: 4242, start_time: Some(BOOT_TIME + 123) }]);
+        }
+
+        #[test]
+        fn test_scan_appimage_launch() {
+            let root = setup_proc();
+            write_process(
+                root.path(),
+                777,
+                "AppRun.wrapped",
+                "/home/user/Apps/ImHex-1.35.4-x86_64.AppImage",
+                &["/home/user/Apps/ImHex-1.35.4-x86_64.AppImage"],
+                200,
+            );
+
+            let processes = scan_proc(root.path());
+            assert_eq!(processes.len(), 1);
+            assert_eq!(processes[0].pid, 777);
+        }
+
+        #[test]
+        fn test_scan_flatpak_launch() {
+            let root = setup_proc();
+            write_process(root.path(), 900, "ld-linux-x86-64", "/app/lib/ld-linux", &["/app/bin/imhex"], 300);
+
+            let processes = scan_proc(root.path());
+            assert_eq!(processes.len(), 1);
+            assert_eq!(processes[0].pid, 900);
+        }
+
+        #[test]
+        fn test_scan_comm_with_parentheses() {
+            let root = setup_proc();
+            write_process(root.path(), 55, "imhex", "/usr/bin/imhex", &["imhex"], 1_000);
+            let dir = root.path().join("55");
+            fs::write(
+                dir.join("stat"),
+                "55 (im (hex) x) S 1 55 55 0 -1 4194560 100 0 0 0 10 5 0 0 20 0 12 0 1000 1000 200",
+            )
+            .unwrap();
+
+            let processes = scan_proc(root.path());
+            assert_eq!(processes[0].start_time, Some(BOOT_TIME + 10));
+        }
+
+        #[test]
+        fn test_scan_missing_stat() {
+            let root = tempdir().unwrap();
+            write_process(root.path(), 12, "imhex-gui", "/opt/imhex/imhex-gui", &["imhex-gui"], 100);
+
+            let processes = scan_proc(root.path());
+            assert_eq!(processes, vec![ImhexProcess { pid: 12, start_time: None }]);
+        }
+
+        #[test]
+        fn test_read_executable_and_environ() {
+            let root = setup_proc();
+            write_process(root.path(), 31, "AppRun", "/tmp/.mount_ImHex/usr/bin/imhex", &["imhex"], 100);
+            fs::write(root.path().join("31").join("environ"), "HOME=/home/user\0APPIMAGE=/home/user/ImHex.AppImage\0EMPTY=\0").unwrap();
+
+            assert_eq!(read_executable(root.path(), 31), Some("/tmp/.mount_ImHex/usr/bin/imhex".into()));
+            assert_eq!(read_environ_var(root.path(), 31, "APPIMAGE").as_deref(), Some("/home/user/ImHex.AppImage"));
+            assert_eq!(read_environ_var(root.path(), 31, "EMPTY").as_deref(), Some(""));
+            assert_eq!(read_environ_var(root.path(), 31, "APP"), None);
+            assert_eq!(read_executable(root.path(), 32), None);
+        }
+
+        #[test]
+        fn test_scan_missing_root() {
+            assert!(scan_proc(Path::new("/nonexistent/proc")).is_empty());
+            assert!(read_processes(Path::new("/nonexistent/proc"), |_| true).is_empty());
+        }
+
+        #[test]
+        fn test_read_processes() {
+            let root = setup_proc();
+            write_process(root.path(), 4242, "imhex", "/usr/bin/imhex", &["imhex", "sample.bin"], 12_345);
+            write_process(root.path(), 100, "Discord", "/opt/discord/Discord", &["/opt/discord/Discord", "--type=renderer"], 50);
+
+            let mut processes = read_processes(root.path(), |_| true);
+            processes.sort_by_key(|process| process.pid);
+            assert_eq!(
+                processes,
+                vec![
+                    ProcessEntry {
+                        pid: 100,
+                        name: "Discord".to_string(),
+                        exe: Some("/opt/discord/Discord".into()),
+                        args: vec!["/opt/discord/Discord".to_string(), "--type=renderer".to_string()],
+                    },
+                    ProcessEntry {
+                        pid: 4242,
+                        name: "imhex".to_string(),
+                        exe: Some("/usr/bin/imhex".into()),
+                        args: vec!["imhex".to_string(), "sample.bin".to_string()],
+                    },
+                ]
+            );
+        }
+
+        #[test]
+        fn test_read_processes_filters_on_comm_and_argv0() {
+            let root = setup_proc();
+            write_process(root.path(), 4242, "imhex", "/usr/bin/imhex", &["imhex"], 12_345);
+            write_process(root.path(), 100, "Discord", "/opt/discord/Discord", &["/opt/discord/Discord"], 50);
+            write_process(root.path(), 200, "MainThread", "/usr/lib/electron/electron", &["/opt/vesktop/vesktop"], 60);
+            write_process(root.path(), 300, "bash", "/opt/discord/Discord", &["bash"], 70);
+
+            let mut pids: Vec<u32> = read_processes(root.path(), |name| name == "Discord" || name == "vesktop").iter().map(|process| process.pid).collect();
+            pids.sort();
+            // The exe link alone doesn't make a candidate
+            assert_eq!(pids, vec![100, 200]);
+        }
+    }
+}
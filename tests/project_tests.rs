@@ -0,0 +1,126 @@
+#[path = "../src/project.rs"]
+mod project;
+#[path = "../src/resolver.rs"]
+#[allow(dead_code)]
+mod resolver;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use project::{project_file_name, read_project, read_project_file, ProjectError, ProjectReader};
+    use resolver::ResolvedFile;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use tempfile::tempdir;
+
+    const METADATA: &str = "HEX\n1.35.4\n";
+
+    fn build_tar(entries: &[(&str, &str)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (path, contents) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, path, contents.as_bytes()).unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    fn provider(path: &str) -> String {
+        format!(r#"{{"type": "hex.builtin.provider.file", "settings": {{"displayName": "x", "path": "{}"}}}}"#, path)
+    }
+
+    fn bookmarks(count: usize) -> String {
+        let bookmark = r#"{"name": "header", "region": {"address": 0, "size": 16}}"#;
+        format!(r#"{{"bookmarks": [{}]}}"#, vec![bookmark; count].join(", "))
+    }
+
+    fn sample_project() -> Vec<u8> {
+        build_tar(&[
+            ("IMHEX_METADATA", METADATA),
+            ("providers/providers.json", r#"{"providers": [0, 1, 2]}"#),
+            ("providers/2.json", r#"{"type": "hex.builtin.provider.mem_file", "settings": {}}"#),
+            ("providers/0.json", &provider("/data/firmware.bin")),
+            ("providers/1.json", &provider("/data/bootloader.bin")),
+            ("0/bookmarks.json", &bookmarks(7)),
+            ("1/bookmarks.json", &bookmarks(5)),
+            ("1/pattern_source.hexpat", "struct Header { u32 magic; };\nHeader header @ 0x00;\n"),
+            ("0/pattern_source.hexpat", "  \n"),
+            ("0/data_processor.json", "{}"),
+        ])
+    }
+
+    #[test]
+    fn test_read_project_summary() {
+        let summary = read_project(sample_project().as_slice(), "router").unwrap();
+        assert_eq!(summary.name, "router");
+        assert_eq!(summary.version.as_deref(), Some("1.35.4"));
+        assert_eq!(summary.providers.iter().map(|provider| provider.id).collect::<Vec<_>>(), vec![0, 1, 2]);
+        assert_eq!(summary.providers[2].kind, "hex.builtin.provider.mem_file");
+        assert_eq!(summary.provider_paths(), vec![Path::new("/data/firmware.bin"), Path::new("/data/bootloader.bin")]);
+        assert_eq!(summary.bookmarks, 12);
+        assert!(summary.has_pattern());
+        assert!(summary.pattern.unwrap().starts_with("struct Header"));
+    }
+
+    #[test]
+    fn test_summary_display() {
+        let summary = read_project(sample_project().as_slice(), "foo").unwrap();
+        assert_eq!(summary.to_string(), "Project: foo (3 files, 12 bookmarks)");
+
+        let single = build_tar(&[
+            ("IMHEX_METADATA", METADATA),
+            ("providers/0.json", &provider("/data/a.bin")),
+            ("0/bookmarks.json", "[{}]"),
+        ]);
+        let summary = read_project(single.as_slice(), "bar").unwrap();
+        assert_eq!(summary.to_string(), "Project: bar (1 file, 1 bookmark)");
+        assert!(!summary.has_pattern());
+    }
+
+    #[test]
+    fn test_empty_project() {
+        let summary = read_project(build_tar(&[("IMHEX_METADATA", "HEX\n")]).as_slice(), "empty").unwrap();
+        assert_eq!(summary.version, None);
+        assert_eq!(summary.to_string(), "Project: empty (0 files, 0 bookmarks)");
+    }
+
+    #[test]
+    fn test_rejects_non_projects() {
+        let archive = build_tar(&[("providers/0.json", &provider("/data/a.bin"))]);
+        assert!(matches!(read_project(archive.as_slice(), "x"), Err(ProjectError::Malformed(_))));
+
+        let archive = build_tar(&[("IMHEX_METADATA", METADATA), ("0/bookmarks.json", "{ broken")]);
+        assert!(matches!(read_project(archive.as_slice(), "x"), Err(ProjectError::Malformed(_))));
+
+        assert!(read_project(&b"{\"legacy\": \"json project\"}"[..], "x").is_err());
+        assert!(matches!(read_project_file(Path::new("/nonexistent/x.hexproj")), Err(ProjectError::Io(_))));
+    }
+
+    #[test]
+    fn test_project_file_name() {
+        assert_eq!(project_file_name("firmware"), "firmware.hexproj");
+        assert_eq!(project_file_name("firmware.hexproj"), "firmware.hexproj");
+        assert_eq!(project_file_name("v1.2"), "v1.2.hexproj");
+    }
+
+    #[test]
+    fn test_reader_caches_until_modified() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("router.hexproj");
+        fs::write(&path, sample_project()).unwrap();
+        let file = ResolvedFile::from_path(&path).unwrap();
+
+        let mut reader = ProjectReader::new();
+        assert_eq!(reader.read(&file).unwrap().name, "router");
+
+        // Same mtime, so the cached summary is returned even though the file changed
+        fs::write(&path, b"garbage").unwrap();
+        let stale = ResolvedFile { modified: file.modified, ..file.clone() };
+        assert_eq!(reader.read(&stale).unwrap().bookmarks, 12);
+
+        let changed = ResolvedFile { path: PathBuf::from(&path), size: 7, modified: None };
+        assert!(reader.read(&changed).is_none());
+    }
+}
@@ -0,0 +1,139 @@
+#[path = "../src/resolver.rs"]
+mod resolver;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use resolver::{recent_files, FileResolver, ResolvedFile};
+    use std::fs::{self, File};
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, SystemTime};
+    use tempfile::tempdir;
+
+    fn write_file(path: &Path, contents: &[u8]) -> PathBuf {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+        path.to_path_buf()
+    }
+
+    fn write_recent(config_dir: &Path, entry: &str, target: &Path, age_secs: u64) {
+        let json = serde_json::json!({
+            "type": "hex.builtin.provider.file",
+            "displayName": target.file_name().unwrap().to_string_lossy(),
+            "path": target,
+        });
+        let path = write_file(&config_dir.join("recent").join(entry), json.to_string().as_bytes());
+        let modified = SystemTime::now() - Duration::from_secs(age_secs);
+        File::options().write(true).open(path).unwrap().set_modified(modified).unwrap();
+    }
+
+    #[test]
+    fn test_resolved_file_metadata() {
+        let dir = tempdir().unwrap();
+        let path = write_file(&dir.path().join("firmware.bin"), &[0u8; 42]);
+
+        let file = ResolvedFile::from_path(&path).unwrap();
+        assert_eq!(file.path, path);
+        assert_eq!(file.size, 42);
+        assert!(file.modified.is_some());
+        assert!(ResolvedFile::from_path(dir.path()).is_none());
+        assert!(ResolvedFile::from_path(&dir.path().join("missing.bin")).is_none());
+    }
+
+    #[test]
+    fn test_recent_files_newest_first() {
+        let dir = tempdir().unwrap();
+        let config_dir = dir.path().join("imhex");
+        write_recent(&config_dir, "1.json", Path::new("/old/a.bin"), 600);
+        write_recent(&config_dir, "2.json", Path::new("/new/b.bin"), 10);
+        write_file(&config_dir.join("recent").join("broken.json"), b"{ not json");
+
+        assert_eq!(recent_files(&config_dir), vec![PathBuf::from("/new/b.bin"), PathBuf::from("/old/a.bin")]);
+        assert!(recent_files(&dir.path().join("missing")).is_empty());
+    }
+
+    #[test]
+    fn test_resolve_from_recent_files() {
+        let dir = tempdir().unwrap();
+        let config_dir = dir.path().join("imhex");
+        let older = write_file(&dir.path().join("old").join("dump.bin"), b"old");
+        let newer = write_file(&dir.path().join("new").join("dump.bin"), b"newer");
+        let deleted = dir.path().join("gone").join("only.bin");
+        write_recent(&config_dir, "1.json", &older, 600);
+        write_recent(&config_dir, "2.json", &newer, 10);
+        write_recent(&config_dir, "3.json", &deleted, 0);
+
+        let resolver = FileResolver::new(vec![dir.path().join("missing"), config_dir]);
+        let file = resolver.resolve("dump.bin", None).unwrap();
+        assert_eq!(file.path, newer);
+        assert_eq!(file.size, 5);
+        assert!(resolver.resolve("only.bin", None).is_none());
+        assert!(resolver.resolve("other.bin", None).is_none());
+    }
+
+    #[test]
+    fn test_resolve_absolute_title() {
+        let dir = tempdir().unwrap();
+        let path = write_file(&dir.path().join("direct.bin"), b"abc");
+
+        let resolver = FileResolver::new(Vec::new());
+        assert_eq!(resolver.resolve(path.to_str().unwrap(), None).unwrap().size, 3);
+    }
+
+    #[test]
+    fn test_resolve_from_extra_recent_files() {
+        let dir = tempdir().unwrap();
+        let path = write_file(&dir.path().join("settings").join("firmware.bin"), b"abcd");
+
+        let resolver = FileResolver::default();
+        assert!(resolver.resolve("firmware.bin", None).is_none());
+
+        let resolver = FileResolver::default().with_recent_files(vec![dir.path().join("missing.bin"), path.clone()]);
+        assert_eq!(resolver.resolve("firmware.bin", None).unwrap().path, path);
+    }
+
+    #[cfg(target_os = "linux")]
+    mod proc_fd {
+        use super::super::resolver::{open_files, FileResolver};
+        use super::{write_file, write_recent};
+        use std::fs;
+        use std::os::unix::fs::symlink;
+        use tempfile::tempdir;
+
+        #[test]
+        fn test_open_files_skips_non_files() {
+            let dir = tempdir().unwrap();
+            let proc_root = dir.path().join("proc");
+            let fd_dir = proc_root.join("42").join("fd");
+            fs::create_dir_all(&fd_dir).unwrap();
+            let file = write_file(&dir.path().join("data").join("a.bin"), b"a");
+            symlink(&file, fd_dir.join("3")).unwrap();
+            symlink(&file, fd_dir.join("4")).unwrap();
+            symlink("socket:[1234]", fd_dir.join("5")).unwrap();
+            symlink("/dev/null", fd_dir.join("0")).unwrap();
+            symlink(dir.path(), fd_dir.join("6")).unwrap();
+
+            assert_eq!(open_files(&proc_root, 42), vec![file]);
+            assert!(open_files(&proc_root, 7).is_empty());
+        }
+
+        #[test]
+        fn test_open_descriptor_beats_recent_files() {
+            let dir = tempdir().unwrap();
+            let config_dir = dir.path().join("imhex");
+            let proc_root = dir.path().join("proc");
+            let fd_dir = proc_root.join("42").join("fd");
+            fs::create_dir_all(&fd_dir).unwrap();
+
+            let recent = write_file(&dir.path().join("recent-copy").join("fw.bin"), b"recent");
+            let open = write_file(&dir.path().join("open-copy").join("fw.bin"), b"open");
+            write_recent(&config_dir, "1.json", &recent, 0);
+            symlink(&open, fd_dir.join("9")).unwrap();
+
+            let resolver = FileResolver::new(vec![config_dir]).with_proc_root(&proc_root);
+            assert_eq!(resolver.resolve("fw.bin", Some(42)).unwrap().path, open);
+            // Another process falls back to the recent entries
+            assert_eq!(resolver.resolve("fw.bin", Some(43)).unwrap().path, recent);
+        }
+    }
+}
@@ -0,0 +1,252 @@
+#[path = "../src/activity.rs"]
+#[allow(dead_code)]
+mod activity;
+#[path = "../src/binary.rs"]
+#[allow(dead_code)]
+mod binary;
+#[path = "../src/entropy.rs"]
+#[allow(dead_code)]
+mod entropy;
+#[path = "../src/error.rs"]
+#[allow(dead_code)]
+mod error;
+#[path = "../src/format.rs"]
+#[allow(dead_code)]
+mod format;
+#[path = "../src/imhex_config.rs"]
+#[allow(dead_code)]
+mod imhex_config;
+#[path = "../src/network.rs"]
+#[allow(dead_code)]
+mod network;
+#[path = "../src/pattern.rs"]
+#[allow(dead_code)]
+mod pattern;
+#[path = "../src/presence.rs"]
+#[allow(dead_code)]
+mod presence;
+#[path = "../src/privacy.rs"]
+#[allow(dead_code)]
+mod privacy;
+#[path = "../src/probe.rs"]
+#[allow(dead_code)]
+mod probe;
+#[path = "../src/process.rs"]
+#[allow(dead_code)]
+mod process;
+#[path = "../src/project.rs"]
+#[allow(dead_code)]
+mod project;
+#[path = "../src/resolver.rs"]
+#[allow(dead_code)]
+mod resolver;
+#[path = "../src/scheduler.rs"]
+#[allow(dead_code)]
+mod scheduler;
+#[path = "../src/settings.rs"]
+#[allow(dead_code)]
+mod settings;
+#[path = "../src/template.rs"]
+#[allow(dead_code)]
+mod template;
+#[path = "../src/title.rs"]
+#[allow(dead_code)]
+mod title;
+#[path = "../src/utils.rs"]
+#[allow(dead_code)]
+mod utils;
+#[path = "../src/window.rs"]
+#[allow(dead_code)]
+mod window;
+#[path = "support/scripted_probe.rs"]
+#[allow(dead_code)]
+mod scripted_probe;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use error::AppError;
+    use presence::{tick, ActivityAssets, ActivitySink, AppState};
+    use scripted_probe::{ProbeFrame, ScriptedProbe};
+    use scheduler::{Clock, RateLimitedSink, TokenBucket, VirtualClock, UPDATE_BURST};
+    use std::time::Duration;
+
+    const START: i64 = 1_700_000_000;
+    // Time for one token to come back, 20 seconds / 5 updates
+    const REFILL: Duration = Duration::from_secs(4);
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    enum Update {
+        Set(String),
+        Clear,
+    }
+
+    #[derive(Default)]
+    struct RecordingSink {
+        updates: Vec<Update>,
+        fail_updates: bool,
+    }
+
+    impl ActivitySink for RecordingSink {
+        fn update_activity(&mut self, _state: String, details: String, _start_time: Option<i64>, _assets: ActivityAssets) -> Result<(), AppError> {
+            if self.fail_updates {
+                return Err(AppError::Discord("pipe closed".to_string()));
+            }
+            self.updates.push(Update::Set(details));
+            Ok(())
+        }
+
+        fn clear_activity(&mut self) -> Result<(), AppError> {
+            self.updates.push(Update::Clear);
+            Ok(())
+        }
+    }
+
+    fn scheduler() -> (RateLimitedSink<RecordingSink, VirtualClock>, VirtualClock) {
+        let clock = VirtualClock::new();
+        (RateLimitedSink::with_clock(RecordingSink::default(), clock.clone()), clock)
+    }
+
+    fn update(sink: &mut impl ActivitySink, details: &str) {
+        sink.update_activity(String::new(), details.to_string(), Some(START), ActivityAssets::default()).unwrap();
+    }
+
+    fn set(details: &str) -> Update {
+        Update::Set(details.to_string())
+    }
+
+    fn sent(sink: &RateLimitedSink<RecordingSink, VirtualClock>) -> Vec<Update> {
+        sink.sink().updates.clone()
+    }
+
+    #[test]
+    fn test_token_bucket() {
+        let clock = VirtualClock::new();
+        let mut bucket = TokenBucket::new(5, Duration::from_secs(20), clock.now());
+        for _ in 0..5 {
+            assert!(bucket.try_take(clock.now()));
+        }
+        assert!(!bucket.try_take(clock.now()));
+        assert_eq!(bucket.wait_time(clock.now()), REFILL);
+
+        clock.advance(Duration::from_secs(3));
+        assert_eq!(bucket.wait_time(clock.now()), Duration::from_secs(1));
+        clock.advance(Duration::from_secs(1));
+        assert!(bucket.try_take(clock.now()));
+        assert!(!bucket.try_take(clock.now()));
+
+        // Partial progress towards the next token is kept
+        clock.advance(Duration::from_secs(6));
+        assert!(bucket.try_take(clock.now()));
+        assert_eq!(bucket.wait_time(clock.now()), Duration::from_secs(2));
+
+        // A long pause refills the bucket but never beyond its capacity
+        clock.advance(Duration::from_secs(3600));
+        for _ in 0..5 {
+            assert!(bucket.try_take(clock.now()));
+        }
+        assert!(!bucket.try_take(clock.now()));
+    }
+
+    #[test]
+    fn test_burst_then_rate_limited() {
+        let (mut sink, clock) = scheduler();
+        for index in 0..UPDATE_BURST {
+            update(&mut sink, &format!("update {}", index));
+        }
+        update(&mut sink, "held back");
+        assert_eq!(sent(&sink).len(), UPDATE_BURST as usize);
+        assert!(sink.has_pending());
+        assert_eq!(sink.flush().unwrap(), Some(REFILL));
+
+        clock.advance(REFILL);
+        assert_eq!(sink.flush().unwrap(), None);
+        assert!(!sink.has_pending());
+        assert_eq!(sent(&sink).last(), Some(&set("held back")));
+    }
+
+    #[test]
+    fn test_updates_without_tokens_are_coalesced() {
+        let (mut sink, clock) = scheduler();
+        for index in 0..UPDATE_BURST {
+            update(&mut sink, &format!("update {}", index));
+        }
+        for index in 0..30 {
+            update(&mut sink, &format!("selection {}", index));
+            clock.advance(Duration::from_millis(100));
+        }
+        // 3 seconds in, no token has come back yet
+        assert_eq!(sent(&sink).len(), UPDATE_BURST as usize);
+
+        clock.advance(Duration::from_secs(1));
+        update(&mut sink, "selection 29");
+        assert_eq!(sent(&sink)[UPDATE_BURST as usize..], [set("selection 29")]);
+    }
+
+    #[test]
+    fn test_unchanged_activity_costs_nothing() {
+        let (mut sink, _) = scheduler();
+        for _ in 0..50 {
+            update(&mut sink, "Analyzing: [a.bin]");
+        }
+        assert_eq!(sent(&sink), vec![set("Analyzing: [a.bin]")]);
+
+        // Going back to what Discord shows drops the held back update
+        for index in 1..UPDATE_BURST {
+            update(&mut sink, &format!("update {}", index));
+        }
+        update(&mut sink, "b.bin");
+        update(&mut sink, &format!("update {}", UPDATE_BURST - 1));
+        assert!(!sink.has_pending());
+    }
+
+    #[test]
+    fn test_clear_is_never_held_back() {
+        let (mut sink, clock) = scheduler();
+        for index in 0..=UPDATE_BURST {
+            update(&mut sink, &format!("update {}", index));
+        }
+        sink.clear_activity().unwrap();
+        assert!(!sink.has_pending());
+        assert_eq!(sent(&sink).last(), Some(&Update::Clear));
+
+        // The last activity before the clear is sent again afterwards
+        clock.advance(REFILL);
+        update(&mut sink, &format!("update {}", UPDATE_BURST - 1));
+        assert_eq!(sent(&sink).last(), Some(&set(&format!("update {}", UPDATE_BURST - 1))));
+    }
+
+    #[test]
+    fn test_failed_update_is_kept() {
+        let failing = RecordingSink { fail_updates: true, ..RecordingSink::default() };
+        let mut sink = RateLimitedSink::with_clock(failing, VirtualClock::new());
+        assert!(sink.update_activity(String::new(), "a".to_string(), None, ActivityAssets::default()).is_err());
+        assert!(sink.has_pending());
+        assert!(sink.flush().is_err());
+        assert!(sink.has_pending());
+    }
+
+    #[test]
+    fn test_presence_loop_is_rate_limited() {
+        let frames = (0..100).map(|index| ProbeFrame::running(42, START).with_focused_window_title(&format!("ImHex - file{}.bin", index)));
+        let mut probe = ScriptedProbe::new(frames);
+        let (mut sink, clock) = scheduler();
+        let mut state = AppState::new();
+        // 100 ticks at the loop's 100 ms interval
+        while !probe.is_exhausted() {
+            tick(&mut probe, &mut sink, &mut state).unwrap();
+            clock.advance(Duration::from_millis(100));
+        }
+        tick(&mut probe, &mut sink, &mut state).unwrap();
+
+        // 5 at once, then one every 4 seconds over the 10 seconds
+        let updates = sent(&sink);
+        assert_eq!(updates.len(), UPDATE_BURST as usize + 2);
+        assert_eq!(updates[..2], [set("Analyzing: [file0.bin]"), set("Analyzing: [file1.bin]")]);
+        assert!(sink.has_pending());
+
+        clock.advance(REFILL);
+        tick(&mut probe, &mut sink, &mut state).unwrap();
+        assert_eq!(sent(&sink).last(), Some(&set("Analyzing: [file99.bin]")));
+    }
+}
@@ -0,0 +1,138 @@
+#[path = "../src/settings.rs"]
+mod settings;
+#[path = "../src/template.rs"]
+#[allow(dead_code)]
+mod template;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use settings::{ButtonSettings, ImageSettings, PrivacyMode, Settings, SettingsError, SETTINGS_FILE_NAME};
+    use std::fs;
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_missing_file_uses_defaults() {
+        let dir = tempdir().unwrap();
+        let settings = Settings::load(&dir.path().join(SETTINGS_FILE_NAME)).unwrap();
+
+        assert_eq!(settings, Settings::default());
+        assert_eq!(settings.away_timeout(), Some(Duration::from_secs(300)));
+        assert!(!settings.broadcast);
+    }
+
+    #[test]
+    fn test_partial_file_keeps_other_defaults() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(SETTINGS_FILE_NAME);
+        fs::write(&path, r#"{ "away_timeout_secs": 0, "broadcast": true }"#).unwrap();
+
+        let settings = Settings::load(&path).unwrap();
+        assert_eq!(settings.away_timeout(), None);
+        assert_eq!(settings.buttons, Vec::new());
+        assert!(settings.broadcast);
+    }
+
+    #[test]
+    fn test_invalid_file_is_an_error() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(SETTINGS_FILE_NAME);
+        fs::write(&path, r#"{ "away_timeout_secs": "soon" }"#).unwrap();
+
+        assert!(matches!(Settings::load(&path), Err(SettingsError::Parse(_))));
+    }
+
+    #[test]
+    fn test_images_and_buttons() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(SETTINGS_FILE_NAME);
+        fs::write(
+            &path,
+            r#"{
+                "small_image": { "key": "imhex", "text": "{theme} theme" },
+                "large_image": { "key": "logo" },
+                "buttons": [{ "label": "Search {file}", "url": "https://example.com/?q={file}" }]
+            }"#,
+        )
+        .unwrap();
+
+        let settings = Settings::load(&path).unwrap();
+        assert_eq!(settings.small_image, ImageSettings { key: "imhex".to_string(), text: "{theme} theme".to_string() });
+        assert_eq!(settings.large_image.text, "");
+        assert_eq!(settings.buttons, vec![ButtonSettings { label: "Search {file}".to_string(), url: "https://example.com/?q={file}".to_string() }]);
+    }
+
+    #[test]
+    fn test_invalid_buttons_are_rejected() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(SETTINGS_FILE_NAME);
+        let button = |label: &str, url: &str| format!(r#"{{ "label": "{}", "url": "{}" }}"#, label, url);
+        let invalid = [
+            vec![button("a", "https://a"), button("b", "https://b"), button("c", "https://c")],
+            vec![button(" ", "https://a")],
+            vec![button(&"x".repeat(33), "https://a")],
+            vec![button("a", "ftp://a")],
+        ];
+
+        for buttons in invalid {
+            fs::write(&path, format!(r#"{{ "buttons": [{}] }}"#, buttons.join(","))).unwrap();
+            assert!(matches!(Settings::load(&path), Err(SettingsError::Invalid(_))), "{:?}", buttons);
+        }
+        fs::write(&path, format!(r#"{{ "buttons": [{}] }}"#, button(&"x".repeat(32), "http://a"))).unwrap();
+        assert!(Settings::load(&path).is_ok());
+    }
+
+    #[test]
+    fn test_privacy_settings() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(SETTINGS_FILE_NAME);
+        fs::write(&path, r#"{ "privacy": { "mode": "extension", "deny": ["**/clients/**"] } }"#).unwrap();
+
+        let settings = Settings::load(&path).unwrap();
+        assert_eq!(settings.privacy.mode, PrivacyMode::Extension);
+        assert_eq!(settings.privacy.deny, vec!["**/clients/**"]);
+        assert_eq!(settings.privacy.generic_label, "a file");
+        assert_eq!(Settings::default().privacy.mode, PrivacyMode::Full);
+
+        fs::write(&path, r#"{ "privacy": { "allow": ["{a,b"] } }"#).unwrap();
+        assert!(matches!(Settings::load(&path), Err(SettingsError::Invalid(_))));
+        fs::write(&path, r#"{ "privacy": { "mode": "secret" } }"#).unwrap();
+        assert!(matches!(Settings::load(&path), Err(SettingsError::Parse(_))));
+
+        fs::write(&path, r#"{ "privacy": { "mode": "hashed" } }"#).unwrap();
+        assert_eq!(Settings::load(&path).unwrap_err().to_string(), r#"Invalid settings: privacy mode "hashed" needs a salt"#);
+        fs::write(&path, r#"{ "privacy": { "mode": "hashed", "salt": "k3P9x" } }"#).unwrap();
+        assert_eq!(Settings::load(&path).unwrap().privacy.mode, PrivacyMode::Hashed);
+    }
+
+    #[test]
+    fn test_templates() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(SETTINGS_FILE_NAME);
+        fs::write(&path, r#"{ "templates": { "analyzing": { "details": "{file|a file} ({size})" }, "paused": { "state": "{elapsed}" } } }"#).unwrap();
+
+        let settings = Settings::load(&path).unwrap();
+        assert_eq!(settings.templates.analyzing.details.as_deref(), Some("{file|a file} ({size})"));
+        assert_eq!(settings.templates.analyzing.state, None);
+        assert_eq!(settings.templates.paused.state.as_deref(), Some("{elapsed}"));
+        assert_eq!(settings.templates.idle, Default::default());
+    }
+
+    #[test]
+    fn test_invalid_templates_are_rejected() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(SETTINGS_FILE_NAME);
+        for (contents, expected) in [
+            (r#"{ "templates": { "idle": { "state": "{flie}" } } }"#, "template templates.idle: unknown placeholder {flie} at 0"),
+            (r#"{ "templates": { "away": { "details": "Away {?file}" } } }"#, "template templates.away: section {?file} is never closed with {/file}"),
+            (r#"{ "small_image": { "text": "{theme" } }"#, "template small_image.text: unclosed { at 0"),
+        ] {
+            fs::write(&path, contents).unwrap();
+            match Settings::load(&path) {
+                Err(SettingsError::Invalid(message)) => assert_eq!(message, expected),
+                other => panic!("expected invalid settings for {}, got {:?}", contents, other),
+            }
+        }
+    }
+}
@@ -0,0 +1,126 @@
+// Discord-compatible IPC server for tests: completes the handshake && records every command it gets.
+// Include it next to the discord module:
+//     #[path = "support/fake_discord.rs"]
+//     mod fake_discord;
+
+use std::io;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use serde_json::{json, Value};
+
+use crate::discord::{read_frame, write_frame, Opcode, SOCKET_NAME};
+
+pub const FAKE_USER_ID: &str = "1234567890";
+pub const FAKE_USERNAME: &str = "fake-discord";
+
+#[derive(Default)]
+struct Recording {
+    handshakes: Vec<Value>,
+    commands: Vec<Value>,
+}
+
+pub struct FakeDiscord {
+    path: PathBuf,
+    recording: Arc<Mutex<Recording>>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl FakeDiscord {
+    // Listens as discord-ipc-0 inside dir, where a client scanning that folder looks first
+    pub fn start(dir: &Path) -> Self {
+        Self::bind(&dir.join(format!("{}0", SOCKET_NAME)))
+    }
+
+    pub fn bind(path: &Path) -> Self {
+        let listener = UnixListener::bind(path).unwrap();
+        let recording = Arc::new(Mutex::new(Recording::default()));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let thread = {
+            let (recording, stop) = (recording.clone(), stop.clone());
+            thread::spawn(move || {
+                // One session at a time, like a single Discord client
+                for stream in listener.incoming() {
+                    if stop.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    if let Ok(stream) = stream {
+                        let _ = serve_session(stream, &recording);
+                    }
+                }
+            })
+        };
+
+        Self { path: path.to_path_buf(), recording, stop, thread: Some(thread) }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // Handshake payloads, one per connection
+    pub fn handshakes(&self) -> Vec<Value> {
+        self.recording.lock().unwrap().handshakes.clone()
+    }
+
+    // Every command frame received, in order
+    pub fn commands(&self) -> Vec<Value> {
+        self.recording.lock().unwrap().commands.clone()
+    }
+
+    // The activity of every SET_ACTIVITY frame, None for the ones clearing it
+    pub fn activities(&self) -> Vec<Option<Value>> {
+        self.commands()
+            .into_iter()
+            .filter(|command| command["cmd"] == "SET_ACTIVITY")
+            .map(|command| Some(command["args"]["activity"].clone()).filter(|activity| !activity.is_null()))
+            .collect()
+    }
+}
+
+impl Drop for FakeDiscord {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        // Wakes the accept loop so the thread sees the stop flag
+        let _ = UnixStream::connect(&self.path);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn serve_session(mut stream: UnixStream, recording: &Mutex<Recording>) -> io::Result<()> {
+    let handshake = match read_frame(&mut stream) {
+        Ok((Opcode::Handshake, handshake)) => handshake,
+        _ => return Ok(()),
+    };
+    recording.lock().unwrap().handshakes.push(handshake);
+    let ready = json!({
+        "cmd": "DISPATCH",
+        "evt": "READY",
+        "data": { "v": 1, "user": { "id": FAKE_USER_ID, "username": FAKE_USERNAME } },
+        "nonce": null,
+    });
+    write_frame(&mut stream, Opcode::Frame, &ready).map_err(io::Error::other)?;
+
+    // Records each command before answering, so it is visible once the client's call returns
+    while let Ok((opcode, frame)) = read_frame(&mut stream) {
+        match opcode {
+            Opcode::Frame => {
+                recording.lock().unwrap().commands.push(frame.clone());
+                let data = if frame["cmd"] == "SET_ACTIVITY" { frame["args"]["activity"].clone() } else { Value::Null };
+                let reply = json!({ "cmd": frame["cmd"], "evt": null, "data": data, "nonce": frame["nonce"] });
+                write_frame(&mut stream, Opcode::Frame, &reply).map_err(io::Error::other)?;
+            }
+            Opcode::Ping => write_frame(&mut stream, Opcode::Pong, &frame).map_err(io::Error::other)?,
+            Opcode::Close => break,
+            _ => {}
+        }
+    }
+    Ok(())
+}
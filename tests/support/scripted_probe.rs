@@ -0,0 +1,210 @@
+// Fake ImHex probe for tests, replaying scripted frames through the presence loop.
+// Include it next to the probe module && the modules it depends on:
+//     #[path = "support/scripted_probe.rs"]
+//     mod scripted_probe;
+
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use crate::imhex_config::ImhexSettings;
+use crate::network::Selection;
+use crate::probe::ImhexProbe;
+use crate::process::ImhexProcess;
+use crate::resolver::ResolvedFile;
+use crate::title::OpenedContent;
+use crate::window::{ImhexWindow, ImhexWindows};
+
+// What a scripted probe reports during a single loop tick
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProbeFrame {
+    pub process: Option<ImhexProcess>,
+    pub windows: Vec<ImhexWindow>,
+    pub selection: Option<Selection>,
+    pub idle: Option<Duration>,
+    pub file: Option<ResolvedFile>,
+    pub project: Option<ResolvedFile>,
+    pub pattern: Option<PathBuf>,
+    // Set when a window event should end the wait after this tick
+    pub window_event: bool,
+    pub settings: Option<ImhexSettings>,
+}
+
+impl ProbeFrame {
+    // ImHex is not running
+    pub fn stopped() -> Self {
+        Self::default()
+    }
+
+    // ImHex is running without a window
+    pub fn running(pid: u32, start_time: i64) -> Self {
+        Self {
+            process: Some(ImhexProcess { pid, start_time: Some(start_time) }),
+            ..Self::default()
+        }
+    }
+
+    // Adds a background window, parsed like a real window title
+    pub fn with_window_title(self, window_title: &str) -> Self {
+        self.with_window(window_title, false)
+    }
+
+    // Adds the window that currently has focus
+    pub fn with_focused_window_title(self, window_title: &str) -> Self {
+        self.with_window(window_title, true)
+    }
+
+    fn with_window(mut self, window_title: &str, focused: bool) -> Self {
+        let pid = self.process.as_ref().map(|process| process.pid);
+        let window = ImhexWindow::from_title(window_title, pid, focused)
+            .unwrap_or_else(|| panic!("'{}' is not an ImHex window title", window_title));
+        self.windows.push(window);
+        self
+    }
+
+    pub fn with_selection(mut self, offset: u64, length: u64) -> Self {
+        self.selection = Some(Selection { offset, length, preview: Vec::new() });
+        self
+    }
+
+    // Resolves every file window to this path
+    pub fn with_file(mut self, path: &str, size: u64) -> Self {
+        self.file = Some(ResolvedFile { path: path.into(), size, modified: None });
+        self
+    }
+
+    // Resolves every project window to this .hexproj file
+    pub fn with_project(mut self, path: &str) -> Self {
+        self.project = ResolvedFile::from_path(Path::new(path));
+        self
+    }
+
+    // Reports this .hexpat file as recently saved
+    pub fn with_saved_pattern(mut self, path: &str) -> Self {
+        self.pattern = Some(PathBuf::from(path));
+        self
+    }
+
+    // Wakes the loop right after this tick, like a title or focus change would
+    pub fn with_window_event(mut self) -> Self {
+        self.window_event = true;
+        self
+    }
+
+    pub fn with_idle_secs(mut self, secs: u64) -> Self {
+        self.idle = Some(Duration::from_secs(secs));
+        self
+    }
+
+    pub fn with_imhex_settings(mut self, settings: ImhexSettings) -> Self {
+        self.settings = Some(settings);
+        self
+    }
+}
+
+// Fake probe replaying a fixed sequence of frames, one per loop tick
+pub struct ScriptedProbe {
+    frames: VecDeque<ProbeFrame>,
+    current: ProbeFrame,
+    events: Vec<String>,
+    stop_when_exhausted: Option<Arc<AtomicBool>>,
+}
+
+impl ScriptedProbe {
+    pub fn new<I: IntoIterator<Item = ProbeFrame>>(frames: I) -> Self {
+        Self {
+            frames: frames.into_iter().collect(),
+            current: ProbeFrame::default(),
+            events: Vec::new(),
+            stop_when_exhausted: None,
+        }
+    }
+
+    // Clears the loop's running flag once every frame has been replayed
+    pub fn stop_when_exhausted(mut self, running: Arc<AtomicBool>) -> Self {
+        self.stop_when_exhausted = Some(running);
+        self
+    }
+
+    // Checks if every frame has been replayed
+    pub fn is_exhausted(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    // Messages logged so far
+    pub fn events(&self) -> &[String] {
+        &self.events
+    }
+}
+
+impl ImhexProbe for ScriptedProbe {
+    // Every tick starts with a process check, so that is where the script advances
+    fn imhex_process(&mut self) -> Option<ImhexProcess> {
+        if let Some(frame) = self.frames.pop_front() {
+            self.current = frame;
+        }
+        self.current.process.clone()
+    }
+
+    fn windows(&mut self) -> ImhexWindows {
+        ImhexWindows::new(self.current.windows.clone())
+    }
+
+    fn selection(&mut self) -> Option<Selection> {
+        self.current.selection.clone()
+    }
+
+    // Focus follows the scripted windows
+    fn imhex_focused(&mut self) -> Option<bool> {
+        Some(self.current.windows.iter().any(|window| window.focused))
+    }
+
+    fn idle_time(&mut self) -> Option<Duration> {
+        self.current.idle
+    }
+
+    fn opened_file(&mut self, window: &ImhexWindow) -> Option<ResolvedFile> {
+        match window.state.content {
+            OpenedContent::File(_) => self.current.file.clone(),
+            _ => None,
+        }
+    }
+
+    fn project_file(&mut self, window: &ImhexWindow) -> Option<ResolvedFile> {
+        match window.state.content {
+            OpenedContent::Project(_) => self.current.project.clone(),
+            _ => None,
+        }
+    }
+
+    fn recent_pattern(&mut self) -> Option<PathBuf> {
+        self.current.pattern.clone()
+    }
+
+    // Scripted patterns are only known by path, so project sources never match
+    fn find_pattern(&mut self, _source: &str) -> Option<PathBuf> {
+        None
+    }
+
+    fn imhex_settings(&mut self) -> Option<ImhexSettings> {
+        self.current.settings.clone()
+    }
+
+    fn wait_for_change(&mut self, timeout: Duration) -> bool {
+        if let Some(running) = self.stop_when_exhausted.as_ref().filter(|_| self.frames.is_empty()) {
+            running.store(false, Ordering::SeqCst);
+        }
+        if self.current.window_event {
+            return true;
+        }
+        thread::sleep(timeout);
+        false
+    }
+
+    fn log_event(&mut self, message: &str) {
+        self.events.push(message.to_string());
+    }
+}
@@ -0,0 +1,123 @@
+#[path = "../src/settings.rs"]
+#[allow(dead_code)]
+mod settings;
+#[path = "../src/template.rs"]
+mod template;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use settings::{ButtonSettings, LineTemplates, Settings, TemplateSettings};
+    use template::{preview, PresenceState, Template, TemplateError, Templates};
+
+    fn render(source: &str, values: &[(&str, &str)]) -> String {
+        let values: Vec<(&str, String)> = values.iter().map(|&(name, value)| (name, value.to_string())).collect();
+        Template::parse(source).unwrap().render(&values)
+    }
+
+    #[test]
+    fn test_placeholders_and_fallbacks() {
+        let values = [("file", "fw.bin"), ("size", "1.5 MiB"), ("selection", "")];
+        assert_eq!(render("Analyzing: [{file}] ({size})", &values), "Analyzing: [fw.bin] (1.5 MiB)");
+        assert_eq!(render("Bytes: [{selection|None}]", &values), "Bytes: [None]");
+        assert_eq!(render("{format|an unknown format}", &values), "an unknown format");
+        assert_eq!(render("{ file }", &values), "fw.bin");
+        assert_eq!(render("no placeholders", &values), "no placeholders");
+        assert_eq!(render("", &values), "");
+    }
+
+    #[test]
+    fn test_conditionals() {
+        let values = [("file", "fw.bin"), ("format", "ELF"), ("project", "")];
+        let source = "{file}{?format} as {format}{/format}{?project} in {project}{/project}{!project} (no project){/project}";
+        assert_eq!(render(source, &values), "fw.bin as ELF (no project)");
+        // Sections nest && may hold fallbacks
+        assert_eq!(render("{?file}[{?format}{format}: {/format}{file}]{/file}", &values), "[ELF: fw.bin]");
+        assert_eq!(render("{!file}{project|nothing}{/file}", &[]), "nothing");
+    }
+
+    #[test]
+    fn test_braces_are_escaped_by_doubling() {
+        assert_eq!(render("{{file}} is {file}}}", &[("file", "x")]), "{file} is x}");
+    }
+
+    #[test]
+    fn test_render_with_encodes_values_only() {
+        let template = Template::parse("https://example.com/?q={file}&f={format|none}").unwrap();
+        let values = vec![("file", "a b".to_string())];
+        assert_eq!(template.render_with(&values, |value| value.replace(' ', "%20")), "https://example.com/?q=a%20b&f=none");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let unknown = |name: &str, position| TemplateError::UnknownPlaceholder { name: name.to_string(), position };
+        assert_eq!(Template::parse("Analyzing {flie}"), Err(unknown("flie", 10)));
+        assert_eq!(Template::parse("{?nope}{/nope}"), Err(unknown("nope", 0)));
+        assert_eq!(Template::parse("{}"), Err(unknown("", 0)));
+        assert_eq!(Template::parse("x {file"), Err(TemplateError::Unclosed(2)));
+        assert_eq!(Template::parse("a } b"), Err(TemplateError::UnmatchedBrace(2)));
+        assert_eq!(Template::parse("{?file}x"), Err(TemplateError::UnclosedSection("file".to_string())));
+        assert_eq!(
+            Template::parse("{?file}{?format}{/file}{/format}"),
+            Err(TemplateError::UnexpectedEnd { name: "file".to_string(), position: 16 })
+        );
+        assert_eq!(Template::parse("{/file}"), Err(TemplateError::UnexpectedEnd { name: "file".to_string(), position: 0 }));
+    }
+
+    #[test]
+    fn test_default_templates() {
+        let templates = Templates::default();
+        let values = vec![("file", "a.bin".to_string()), ("open_files", "3".to_string())];
+        assert_eq!(templates.line(PresenceState::Analyzing).render(&values), ("Analyzing: [a.bin] (3 files open)".to_string(), "Bytes: [None]".to_string()));
+
+        // A saved pattern wins over the project, which wins over the file
+        let details = |extra: &[(&'static str, &str)]| {
+            let mut values = vec![("file", "a.elf".to_string()), ("format", "ELF executable".to_string())];
+            values.extend(extra.iter().map(|(name, value)| (*name, value.to_string())));
+            templates.line(PresenceState::Analyzing).render(&values).0
+        };
+        assert_eq!(details(&[]), "Analyzing ELF executable: [a.elf]");
+        let project = [("project", "fw"), ("project_files", "1 file"), ("bookmarks", "2 bookmarks")];
+        assert_eq!(details(&project), "Project: fw (1 file, 2 bookmarks)");
+        assert_eq!(details(&[project[0], project[1], project[2], ("writing", "elf.hexpat")]), "Writing pattern: elf.hexpat");
+        assert_eq!(templates.line(PresenceState::Idle).render(&values), ("Idle".to_string(), String::new()));
+        assert_eq!(templates.line(PresenceState::Away).render(&values), ("Away".to_string(), String::new()));
+        assert_eq!(templates.line(PresenceState::Paused).render(&values), ("Away".to_string(), String::new()));
+    }
+
+    #[test]
+    fn test_paused_falls_back_to_away() {
+        let away = LineTemplates { details: Some("Away for {elapsed}".to_string()), state: Some("brb".to_string()) };
+        let settings = Settings { templates: TemplateSettings { away, ..TemplateSettings::default() }, ..Settings::default() };
+        let templates = Templates::from_settings(&settings).unwrap();
+        let values = vec![("elapsed", "5m".to_string())];
+        assert_eq!(templates.line(PresenceState::Paused).render(&values), ("Away for 5m".to_string(), "brb".to_string()));
+
+        let paused = LineTemplates { details: Some("In another window".to_string()), state: Some(String::new()) };
+        let settings = Settings { templates: TemplateSettings { paused, ..settings.templates.clone() }, ..settings };
+        let templates = Templates::from_settings(&settings).unwrap();
+        assert_eq!(templates.line(PresenceState::Paused).render(&values), ("In another window".to_string(), String::new()));
+    }
+
+    #[test]
+    fn test_errors_name_the_setting() {
+        let settings = Settings {
+            buttons: vec![ButtonSettings { label: "Open".to_string(), url: "https://example.com/{path}".to_string() }],
+            ..Settings::default()
+        };
+        let (name, error) = Templates::from_settings(&settings).unwrap_err();
+        assert_eq!(name, "buttons[0].url");
+        assert_eq!(error.to_string(), "unknown placeholder {path} at 20");
+    }
+
+    #[test]
+    fn test_preview_renders_every_state() {
+        let preview = preview(&Templates::default());
+        let lines: Vec<&str> = preview.lines().collect();
+        assert_eq!(lines.len(), 2 * PresenceState::ALL.len());
+        assert_eq!(lines[0], r#"idle       details: "Idle""#);
+        assert_eq!(lines[2], r#"analyzing  details: "Project: router (3 files, 12 bookmarks) (2 files open)""#);
+        assert_eq!(lines[3], r#"           state:   "Bytes: [0x10-0x2F (32 bytes)]""#);
+        assert!(lines[6].starts_with("paused"));
+    }
+}
@@ -0,0 +1,129 @@
+#[path = "../src/title.rs"]
+mod title;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use title::{parse_window_title, ImhexWindowState, OpenedContent, ProviderKind};
+
+    fn file(name: &str) -> OpenedContent {
+        OpenedContent::File(name.to_string())
+    }
+
+    fn project(name: &str) -> OpenedContent {
+        OpenedContent::Project(name.to_string())
+    }
+
+    fn provider(kind: ProviderKind, name: &str) -> OpenedContent {
+        OpenedContent::Provider { kind, name: name.to_string() }
+    }
+
+    struct Case {
+        title: &'static str,
+        content: OpenedContent,
+        unsaved: bool,
+        read_only: bool,
+        version: Option<&'static str>,
+        debug_build: bool,
+    }
+
+    fn case(title: &'static str, content: OpenedContent) -> Case {
+        Case { title, content, unsaved: false, read_only: false, version: None, debug_build: false }
+    }
+
+    impl Case {
+        fn unsaved(mut self) -> Self {
+            self.unsaved = true;
+            self
+        }
+
+        fn read_only(mut self) -> Self {
+            self.read_only = true;
+            self
+        }
+
+        fn version(mut self, version: &'static str) -> Self {
+            self.version = Some(version);
+            self
+        }
+
+        fn debug_build(mut self) -> Self {
+            self.debug_build = true;
+            self
+        }
+    }
+
+    // Titles observed across ImHex 1.10 through 1.35 && nightly builds
+    fn corpus() -> Vec<Case> {
+        vec![
+            case("ImHex", OpenedContent::Welcome),
+            case("ImHex - firmware.bin", file("firmware.bin")),
+            case("ImHex - libc.so.6", file("libc.so.6")),
+            case("ImHex - my file - copy.bin", file("my file - copy.bin")),
+            case("ImHex - firmware.bin*", file("firmware.bin")).unsaved(),
+            case("ImHex - firmware.bin (*)", file("firmware.bin")).unsaved(),
+            case("ImHex - notepad.exe (Read Only)", file("notepad.exe")).read_only(),
+            case("ImHex - dump.bin (*) (Read Only)", file("dump.bin")).unsaved().read_only(),
+            case("ImHex - Project router-fw", project("router-fw")),
+            case("ImHex - Project router-fw (*)", project("router-fw")).unsaved(),
+            case("ImHex - Project: router-fw", project("router-fw")),
+            case("ImHex - Process Memory (firefox.exe)", provider(ProviderKind::ProcessMemory, "Process Memory (firefox.exe)")),
+            case(r"ImHex - \\.\PhysicalDrive0", provider(ProviderKind::Disk, r"\\.\PhysicalDrive0")),
+            case(r"ImHex - \\.\C:", provider(ProviderKind::Disk, r"\\.\C:")),
+            case("ImHex - /dev/sda (Read Only)", provider(ProviderKind::Disk, "/dev/sda")).read_only(),
+            case("ImHex - GDB Server <localhost:1234>", provider(ProviderKind::Gdb, "GDB Server <localhost:1234>")),
+            case("ImHex - New File (*)", provider(ProviderKind::Memory, "New File")).unsaved(),
+            case("ImHex - View (0x100 - 0x1FF)", provider(ProviderKind::View, "View (0x100 - 0x1FF)")),
+            case("ImHex 1.35.4", OpenedContent::Welcome).version("1.35.4"),
+            case("ImHex v1.26.2 - image.png", file("image.png")).version("1.26.2"),
+            case("ImHex Nightly - image.png", file("image.png")).version("Nightly"),
+            case("ImHex [Debug Build]", OpenedContent::Welcome).debug_build(),
+            case("ImHex 1.34.0.WIP [Debug Build] - a.elf", file("a.elf")).version("1.34.0.WIP").debug_build(),
+            case(r"C:\Program Files\ImHex\imhex-gui.exe", OpenedContent::Welcome),
+            case("ImHex - ", OpenedContent::Welcome),
+        ]
+    }
+
+    #[test]
+    fn test_title_corpus() {
+        for case in corpus() {
+            let expected = ImhexWindowState {
+                content: case.content,
+                unsaved: case.unsaved,
+                read_only: case.read_only,
+                version: case.version.map(str::to_string),
+                debug_build: case.debug_build,
+            };
+            assert_eq!(parse_window_title(case.title), Some(expected), "title: {:?}", case.title);
+        }
+    }
+
+    #[test]
+    fn test_non_imhex_titles() {
+        for title in ["", "Terminal", "discord-imhex", "ImHexagon", "notes about ImHex - todo.txt"] {
+            assert_eq!(parse_window_title(title), None, "title: {:?}", title);
+        }
+    }
+
+    #[test]
+    fn test_display_name() {
+        let state = parse_window_title("ImHex - Project router-fw (*)").unwrap();
+        assert_eq!(state.display_name(), Some("router-fw"));
+        assert!(!state.is_welcome());
+
+        let state = parse_window_title("ImHex").unwrap();
+        assert_eq!(state.display_name(), None);
+        assert!(state.is_welcome());
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(ImhexWindowState::welcome().to_string(), "Welcome screen");
+        assert_eq!(parse_window_title("ImHex - a.bin (*)").unwrap().to_string(), "a.bin (unsaved)");
+        assert_eq!(parse_window_title("ImHex - Project fw").unwrap().to_string(), "Project fw");
+        assert_eq!(
+            parse_window_title("ImHex - /dev/sda (Read Only)").unwrap().to_string(),
+            "/dev/sda (read only)"
+        );
+    }
+}
@@ -1,13 +1,15 @@
 #[path = "../src/tray.rs"]
 mod tray;
 
-use std::sync::{Arc, atomic::{AtomicBool, Ordering}};
+use std::sync::{Arc, mpsc, atomic::{AtomicBool, Ordering}};
 use std::error::Error;
+use std::thread;
+use std::time::Duration;
 use std::env;
 use std::fs;
 use std::path::PathBuf;
 use tempfile::{tempdir, TempDir};
-use tray::{create_tray_icon, VERSION, ICON};
+use tray::{create_tray_icon, show_connection_state, VERSION, ICON};
 struct TestContext {
     _temp_dir: TempDir,
     test_path: PathBuf,
@@ -54,6 +56,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_show_connection_state_while_listening() -> Result<(), Box<dyn Error>> {
+        let running = Arc::new(AtomicBool::new(true));
+        // Without a system tray there is nothing to update
+        let Ok(tooltip) = create_tray_icon(&running) else {
+            return Ok(());
+        };
+
+        // create_tray_icon has started the message listener, which never returns
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || sender.send(show_connection_state(&tooltip, "Connecting").is_ok()));
+        let updated = receiver.recv_timeout(Duration::from_secs(5)).expect("the tooltip update blocked");
+        assert!(updated);
+        Ok(())
+    }
+
     #[test]
     fn test_log_error() -> Result<(), Box<dyn Error>> {
         let context = setup_test_env();
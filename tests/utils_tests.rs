@@ -8,7 +8,7 @@ mod tests {
     use std::time::Duration;
     use chrono::DateTime;
     use regex::Regex;
-    use utils::{current_timestamp, get_current_timestamp};
+    use utils::{current_timestamp, format_elapsed, format_size, get_current_timestamp};
 
     #[test]
     fn test_get_current_timestamp() {
@@ -95,4 +95,21 @@ mod tests {
     
         assert!(dt2 > dt1);
     }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(1023), "1023 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(5 * 1024 * 1024), "5.0 MiB");
+        assert_eq!(format_size(3 * 1024 * 1024 * 1024 / 2), "1.5 GiB");
+    }
+
+    #[test]
+    fn test_format_elapsed() {
+        assert_eq!(format_elapsed(59), "0m");
+        assert_eq!(format_elapsed(5 * 60 + 59), "5m");
+        assert_eq!(format_elapsed(3600 + 5 * 60), "1h 05m");
+        assert_eq!(format_elapsed(26 * 3600), "26h 00m");
+    }
 }
\ No newline at end of file
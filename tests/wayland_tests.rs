@@ -0,0 +1,184 @@
+#![cfg(target_os = "linux")]
+
+#[path = "../src/wayland.rs"]
+mod wayland;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::io::{Read, Write};
+    use std::os::unix::net::UnixListener;
+    use std::path::{Path, PathBuf};
+    use std::thread::{self, JoinHandle};
+    use tempfile::tempdir;
+    use wayland::{Compositor, CompositorWindow, HyprlandIpc, SwayIpc, WaylandError};
+
+    const SWAY_TREE: &str = r#"{
+        "id": 1, "type": "root", "name": "root", "focus": [3],
+        "nodes": [{
+            "id": 3, "type": "output", "name": "eDP-1", "focus": [4],
+            "nodes": [{
+                "id": 4, "type": "workspace", "name": "1", "focus": [7, 5, 6],
+                "nodes": [
+                    {"id": 5, "type": "con", "name": "ImHex - a.bin", "pid": 100, "app_id": "imhex", "focused": false, "nodes": []},
+                    {"id": 6, "type": "con", "name": "ImHex - b.bin", "pid": 100, "app_id": null, "window": 4194310, "focused": false, "nodes": []}
+                ],
+                "floating_nodes": [
+                    {"id": 7, "type": "floating_con", "name": "Terminal", "pid": 200, "app_id": "foot", "focused": true, "nodes": []}
+                ]
+            }, {
+                "id": 8, "type": "workspace", "name": "2", "focus": [], "nodes": [], "floating_nodes": []
+            }]
+        }]
+    }"#;
+
+    // Answers one i3-ipc request per connection with the given payload
+    fn serve_sway(socket: &Path, reply_type: u32, payload: &'static str, connections: usize) -> JoinHandle<Vec<u32>> {
+        let listener = UnixListener::bind(socket).unwrap();
+        thread::spawn(move || {
+            let mut requests = Vec::new();
+            for stream in listener.incoming().take(connections) {
+                let mut stream = stream.unwrap();
+                let mut header = [0u8; 14];
+                stream.read_exact(&mut header).unwrap();
+                assert_eq!(&header[..6], b"i3-ipc");
+                requests.push(u32::from_ne_bytes(header[10..14].try_into().unwrap()));
+
+                let mut reply = b"i3-ipc".to_vec();
+                reply.extend_from_slice(&(payload.len() as u32).to_ne_bytes());
+                reply.extend_from_slice(&reply_type.to_ne_bytes());
+                reply.extend_from_slice(payload.as_bytes());
+                stream.write_all(&reply).unwrap();
+            }
+            requests
+        })
+    }
+
+    // Answers Hyprland commands like the real socket: read the command, write the reply, close
+    fn serve_hyprland(socket: &Path, replies: HashMap<&'static str, &'static str>, connections: usize) -> JoinHandle<Vec<String>> {
+        let listener = UnixListener::bind(socket).unwrap();
+        thread::spawn(move || {
+            let mut commands = Vec::new();
+            for stream in listener.incoming().take(connections) {
+                let mut stream = stream.unwrap();
+                let mut command = [0u8; 256];
+                let length = stream.read(&mut command).unwrap();
+                let command = String::from_utf8_lossy(&command[..length]).to_string();
+                stream.write_all(replies.get(command.as_str()).unwrap_or(&"unknown request").as_bytes()).unwrap();
+                commands.push(command);
+            }
+            commands
+        })
+    }
+
+    fn window(title: &str, pid: Option<u32>, focused: bool) -> CompositorWindow {
+        CompositorWindow { title: title.to_string(), pid, focused }
+    }
+
+    #[test]
+    fn test_sway_windows_in_focus_order() {
+        let dir = tempdir().unwrap();
+        let socket = dir.path().join("sway-ipc.sock");
+        let server = serve_sway(&socket, 4, SWAY_TREE, 2);
+
+        let compositor = Compositor::Sway(SwayIpc::new(&socket));
+        assert_eq!(
+            compositor.windows().unwrap(),
+            vec![
+                window("Terminal", Some(200), true),
+                window("ImHex - a.bin", Some(100), false),
+                window("ImHex - b.bin", Some(100), false),
+            ]
+        );
+        assert_eq!(compositor.focused_pid().unwrap(), Some(200));
+        assert_eq!(server.join().unwrap(), vec![4, 4]);
+    }
+
+    #[test]
+    fn test_sway_rejects_mismatched_reply() {
+        let dir = tempdir().unwrap();
+        let socket = dir.path().join("sway-ipc.sock");
+        let server = serve_sway(&socket, 0, "[]", 1);
+
+        assert!(matches!(SwayIpc::new(&socket).windows(), Err(WaylandError::Protocol(_))));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_hyprland_windows_by_focus_history() {
+        let dir = tempdir().unwrap();
+        let socket = dir.path().join(".socket.sock");
+        let clients = r#"[
+            {"title": "ImHex - fw.bin", "pid": 300, "mapped": true, "focusHistoryID": 1},
+            {"title": "Firefox", "pid": 400, "mapped": true, "focusHistoryID": 0},
+            {"title": "hidden", "pid": 500, "mapped": false, "focusHistoryID": 2},
+            {"title": "ImHex - never.bin", "pid": -1, "mapped": true, "focusHistoryID": -1}
+        ]"#;
+        let server = serve_hyprland(&socket, HashMap::from([("j/clients", clients)]), 1);
+
+        let compositor = Compositor::Hyprland(HyprlandIpc::new(&socket));
+        assert_eq!(
+            compositor.windows().unwrap(),
+            vec![
+                window("Firefox", Some(400), true),
+                window("ImHex - fw.bin", Some(300), false),
+                window("ImHex - never.bin", None, false),
+            ]
+        );
+        assert_eq!(server.join().unwrap(), vec!["j/clients"]);
+    }
+
+    #[test]
+    fn test_hyprland_errors() {
+        let dir = tempdir().unwrap();
+        let socket = dir.path().join(".socket.sock");
+        let server = serve_hyprland(&socket, HashMap::new(), 1);
+        assert!(matches!(HyprlandIpc::new(&socket).windows(), Err(WaylandError::Json(_))));
+        server.join().unwrap();
+
+        assert!(matches!(HyprlandIpc::new(&dir.path().join("missing.sock")).windows(), Err(WaylandError::Io(_))));
+    }
+
+    #[test]
+    fn test_hyprland_socket_path() {
+        let runtime = tempdir().unwrap();
+        let expected = runtime.path().join("hypr/abc_123/.socket.sock");
+        assert_eq!(HyprlandIpc::socket_path("abc_123", Some(runtime.path())), expected);
+
+        std::fs::create_dir_all(expected.parent().unwrap()).unwrap();
+        std::fs::write(&expected, "").unwrap();
+        assert_eq!(HyprlandIpc::socket_path("abc_123", Some(runtime.path())), expected);
+        assert_eq!(HyprlandIpc::socket_path("abc_123", None), PathBuf::from("/tmp/hypr/abc_123/.socket.sock"));
+    }
+
+    #[test]
+    fn test_detect_from_environment() {
+        let detect = |vars: &[(&str, &str)]| {
+            let vars: HashMap<String, String> = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+            Compositor::detect(|name| vars.get(name).cloned()).map(|compositor| compositor.name())
+        };
+
+        assert_eq!(detect(&[("SWAYSOCK", "/run/user/1000/sway-ipc.sock")]), Some("sway"));
+        // i3 on X11 keeps the X11 watcher
+        assert_eq!(detect(&[("I3SOCK", "/run/user/1000/i3/ipc-socket"), ("DISPLAY", ":0")]), None);
+        assert_eq!(detect(&[("HYPRLAND_INSTANCE_SIGNATURE", "abc"), ("XDG_RUNTIME_DIR", "/run/user/1000")]), Some("Hyprland"));
+        assert_eq!(detect(&[("SWAYSOCK", ""), ("WAYLAND_DISPLAY", "wayland-0")]), None);
+        assert_eq!(detect(&[]), None);
+
+        let from_env = Compositor::from_env().map(|compositor| compositor.name());
+        assert_eq!(from_env, Compositor::detect(|name| std::env::var(name).ok()).map(|compositor| compositor.name()));
+    }
+
+    #[test]
+    fn test_detected_sway_talks_to_socket() {
+        let dir = tempdir().unwrap();
+        let socket = dir.path().join("sway-ipc.sock");
+        let server = serve_sway(&socket, 4, r#"{"id": 1, "focus": [], "nodes": []}"#, 1);
+
+        let path = socket.to_str().unwrap().to_string();
+        let compositor = Compositor::detect(|name| (name == "SWAYSOCK").then(|| path.clone())).unwrap();
+        assert_eq!(compositor.focused_pid().unwrap(), None);
+        server.join().unwrap();
+    }
+}
@@ -0,0 +1,81 @@
+#[path = "../src/title.rs"]
+#[allow(dead_code)]
+mod title;
+#[path = "../src/window.rs"]
+mod window;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use window::{ImhexWindow, ImhexWindows};
+
+    fn window(title: &str, pid: Option<u32>, focused: bool) -> ImhexWindow {
+        ImhexWindow::from_title(title, pid, focused).unwrap()
+    }
+
+    #[test]
+    fn test_from_title_rejects_other_windows() {
+        assert!(ImhexWindow::from_title("Terminal", Some(1), false).is_none());
+        assert_eq!(window("ImHex - a.bin", Some(1), true).title, "ImHex - a.bin");
+    }
+
+    #[test]
+    fn test_primary_prefers_focused() {
+        let windows = ImhexWindows::new(vec![
+            window("ImHex - a.bin", Some(1), false),
+            window("ImHex - b.bin", Some(2), true),
+        ]);
+        assert_eq!(windows.primary().unwrap().state.display_name(), Some("b.bin"));
+    }
+
+    #[test]
+    fn test_primary_falls_back_to_most_recent() {
+        let windows = ImhexWindows::new(vec![
+            window("ImHex - a.bin", Some(1), false),
+            window("ImHex - b.bin", Some(2), false),
+        ]);
+        assert_eq!(windows.primary().unwrap().state.display_name(), Some("a.bin"));
+        assert!(ImhexWindows::default().primary().is_none());
+    }
+
+    #[test]
+    fn test_open_names_are_distinct() {
+        let windows = ImhexWindows::new(vec![
+            window("ImHex - a.bin", Some(1), false),
+            window("ImHex", Some(1), false),
+            window("ImHex - a.bin (*)", Some(2), false),
+            window("ImHex - Project fw", Some(2), false),
+        ]);
+        assert_eq!(windows.open_names(), vec!["a.bin", "fw"]);
+        assert_eq!(windows.len(), 4);
+        assert_eq!(windows.iter().filter(|window| window.state.is_welcome()).count(), 1);
+    }
+
+    #[test]
+    fn test_instance_count() {
+        let windows = ImhexWindows::new(vec![
+            window("ImHex - a.bin", Some(1), false),
+            window("ImHex - b.bin", Some(1), false),
+            window("ImHex - c.bin", Some(7), false),
+        ]);
+        assert_eq!(windows.instance_count(), 2);
+
+        let unknown_owner = ImhexWindows::new(vec![window("ImHex - a.bin", None, false)]);
+        assert_eq!(unknown_owner.instance_count(), 1);
+        assert_eq!(ImhexWindows::default().instance_count(), 0);
+    }
+
+    #[test]
+    fn test_retain_processes() {
+        let mut windows = ImhexWindows::new(vec![
+            window("ImHex - a.bin", Some(1), false),
+            window("ImHex - Wikipedia", Some(99), true),
+            window("ImHex - b.bin", None, false),
+        ]);
+        windows.retain_processes(&[1]);
+
+        assert_eq!(windows.open_names(), vec!["a.bin", "b.bin"]);
+        assert!(!windows.is_empty());
+        assert!(windows.primary().is_some_and(|window| !window.focused));
+    }
+}
@@ -0,0 +1,198 @@
+// Needs an X server, e.g. `xvfb-run cargo test --test x11_tests -- --ignored`
+#![cfg(target_os = "linux")]
+
+#[path = "../src/x11.rs"]
+mod x11;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Mutex, MutexGuard};
+    use std::time::Duration;
+    use x11::{WindowEvent, X11Session, X11Watcher};
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{
+        AtomEnum, ConnectionExt, CreateWindowAux, PropMode, Window, WindowClass,
+    };
+    use x11rb::rust_connection::RustConnection;
+    use x11rb::wrapper::ConnectionExt as _;
+
+    // The tests share the root window properties, so the watcher would see the changes of the others
+    static X_SERVER: Mutex<()> = Mutex::new(());
+
+    fn lock_x_server() -> MutexGuard<'static, ()> {
+        X_SERVER.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn create_window(conn: &RustConnection, root: Window) -> Window {
+        let window = conn.generate_id().unwrap();
+        conn.create_window(
+            0,
+            window,
+            root,
+            0,
+            0,
+            1,
+            1,
+            0,
+            WindowClass::INPUT_OUTPUT,
+            0,
+            &CreateWindowAux::new(),
+        )
+        .unwrap();
+        window
+    }
+
+    fn intern(conn: &RustConnection, name: &[u8]) -> u32 {
+        conn.intern_atom(false, name).unwrap().reply().unwrap().atom
+    }
+
+    #[test]
+    #[ignore = "needs an X server"]
+    fn test_find_windows_from_client_list() {
+        let _x_server = lock_x_server();
+        let (conn, screen_num) = x11rb::connect(None).unwrap();
+        let root = conn.setup().roots[screen_num].root;
+        let net_client_list = intern(&conn, b"_NET_CLIENT_LIST");
+        let net_wm_name = intern(&conn, b"_NET_WM_NAME");
+        let utf8_string = intern(&conn, b"UTF8_STRING");
+
+        let utf8_window = create_window(&conn, root);
+        conn.change_property8(
+            PropMode::REPLACE,
+            utf8_window,
+            net_wm_name,
+            utf8_string,
+            "ImHex - sämple.bin".as_bytes(),
+        )
+        .unwrap();
+
+        let legacy_window = create_window(&conn, root);
+        conn.change_property8(
+            PropMode::REPLACE,
+            legacy_window,
+            AtomEnum::WM_NAME,
+            AtomEnum::STRING,
+            b"ImHex - legacy.elf",
+        )
+        .unwrap();
+
+        let other_window = create_window(&conn, root);
+        conn.change_property8(
+            PropMode::REPLACE,
+            other_window,
+            net_wm_name,
+            utf8_string,
+            b"Terminal",
+        )
+        .unwrap();
+
+        conn.change_property32(
+            PropMode::APPEND,
+            root,
+            net_client_list,
+            AtomEnum::WINDOW,
+            &[utf8_window, legacy_window, other_window],
+        )
+        .unwrap();
+        conn.sync().unwrap();
+
+        let session = X11Session::connect().unwrap();
+        let windows = session.find_windows(|title| title.starts_with("ImHex")).unwrap();
+        let titles: Vec<String> = windows.into_iter().map(|window| window.title).collect();
+
+        assert!(titles.contains(&"ImHex - sämple.bin".to_string()));
+        assert!(titles.contains(&"ImHex - legacy.elf".to_string()));
+        assert!(!titles.contains(&"Terminal".to_string()));
+    }
+
+    #[test]
+    #[ignore = "needs an X server"]
+    fn test_find_windows_reports_pid_and_focus() {
+        let _x_server = lock_x_server();
+        let (conn, screen_num) = x11rb::connect(None).unwrap();
+        let root = conn.setup().roots[screen_num].root;
+        let net_client_list = intern(&conn, b"_NET_CLIENT_LIST");
+        let net_wm_name = intern(&conn, b"_NET_WM_NAME");
+        let net_wm_pid = intern(&conn, b"_NET_WM_PID");
+        let net_active_window = intern(&conn, b"_NET_ACTIVE_WINDOW");
+        let utf8_string = intern(&conn, b"UTF8_STRING");
+
+        let window = create_window(&conn, root);
+        conn.change_property8(PropMode::REPLACE, window, net_wm_name, utf8_string, b"ImHex - pid.bin")
+            .unwrap();
+        conn.change_property32(PropMode::REPLACE, window, net_wm_pid, AtomEnum::CARDINAL, &[4242])
+            .unwrap();
+        conn.change_property32(PropMode::APPEND, root, net_client_list, AtomEnum::WINDOW, &[window])
+            .unwrap();
+        conn.change_property32(PropMode::REPLACE, root, net_active_window, AtomEnum::WINDOW, &[window])
+            .unwrap();
+        conn.sync().unwrap();
+
+        let session = X11Session::connect().unwrap();
+        let found = session.find_windows(|title| title == "ImHex - pid.bin").unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].window, window);
+        assert_eq!(found[0].pid, Some(4242));
+        assert!(found[0].active);
+    }
+
+    #[test]
+    #[ignore = "needs an X server"]
+    fn test_window_title_missing() {
+        let _x_server = lock_x_server();
+        let (conn, screen_num) = x11rb::connect(None).unwrap();
+        let root = conn.setup().roots[screen_num].root;
+        let window = create_window(&conn, root);
+        conn.sync().unwrap();
+
+        let session = X11Session::connect().unwrap();
+        assert_eq!(session.window_title(window).unwrap(), None);
+    }
+
+    #[test]
+    #[ignore = "needs an X server"]
+    fn test_idle_time() {
+        let _x_server = lock_x_server();
+        let session = X11Session::connect().unwrap();
+        let first = session.idle_time().unwrap();
+        let second = session.idle_time().unwrap();
+        assert!(second >= first);
+    }
+
+    #[test]
+    #[ignore = "needs an X server"]
+    fn test_watcher_reports_title_and_focus_changes() {
+        let _x_server = lock_x_server();
+        let (conn, screen_num) = x11rb::connect(None).unwrap();
+        let root = conn.setup().roots[screen_num].root;
+        let net_wm_name = intern(&conn, b"_NET_WM_NAME");
+        let net_active_window = intern(&conn, b"_NET_ACTIVE_WINDOW");
+        let utf8_string = intern(&conn, b"UTF8_STRING");
+        let window = create_window(&conn, root);
+        let other = create_window(&conn, root);
+        conn.sync().unwrap();
+
+        let mut watcher = X11Watcher::connect().unwrap();
+        watcher.watch_windows(&[window]).unwrap();
+        assert!(watcher.is_watching(window));
+        assert_eq!(watcher.wait(Duration::from_millis(50)).unwrap(), None);
+
+        // Titles of unwatched windows are ignored
+        conn.change_property8(PropMode::REPLACE, other, net_wm_name, utf8_string, b"Terminal").unwrap();
+        conn.change_property8(PropMode::REPLACE, window, net_wm_name, utf8_string, b"ImHex - new.bin").unwrap();
+        conn.sync().unwrap();
+        assert_eq!(watcher.wait(Duration::from_secs(5)).unwrap(), Some(WindowEvent::Title(window)));
+
+        conn.change_property32(PropMode::REPLACE, root, net_active_window, AtomEnum::WINDOW, &[window]).unwrap();
+        conn.sync().unwrap();
+        assert_eq!(watcher.wait(Duration::from_secs(5)).unwrap(), Some(WindowEvent::ActiveWindow));
+
+        watcher.watch_windows(&[]).unwrap();
+        assert!(!watcher.is_watching(window));
+        conn.change_property8(PropMode::REPLACE, window, net_wm_name, utf8_string, b"ImHex - gone.bin").unwrap();
+        conn.sync().unwrap();
+        assert_eq!(watcher.wait(Duration::from_millis(200)).unwrap(), None);
+        assert_eq!(watcher.drain().unwrap(), 0);
+    }
+}